@@ -0,0 +1,125 @@
+//! Macros for the `wasvy-guest` crate. Use them through `wasvy_guest` instead of depending on
+//! this crate directly.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{FnArg, ItemFn, Type, parse_macro_input, spanned::Spanned};
+
+/// Turns a function whose parameters are `wasvy_guest::Query`s (or `wasvy_guest::Commands`) into
+/// a Wasvy system.
+///
+/// Next to the function, a module with the same name is generated with:
+/// - `NAME`: the name of the system, the function name in kebab-case like the WIT export.
+/// - `register()`: registers the system and its queries with the host, call it in `setup`.
+/// - `run(params)`: deserializes the query results and calls the function, call it from the
+///   exported function of your bindings. It returns the commands the system queued.
+///
+/// Systems that take a `wasvy_guest::LazyQuery` read their rows while they run, so `run` takes
+/// the `list<query-iter>` of your bindings instead of a `list<query-result>`.
+///
+/// ```rust,ignore
+/// #[wasvy_guest::system]
+/// fn print_players(players: Query<&Player, Without<Enemy>>) {
+///     for (entity, player) in &players {
+///         println!("{entity:?}: {player:?}");
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn system(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return syn::Error::new(
+            proc_macro2::TokenStream::from(attr).span(),
+            "`system` doesn't take any arguments",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let function = parse_macro_input!(item as ItemFn);
+    let visibility = &function.vis;
+    let ident = &function.sig.ident;
+    let name = ident.to_string().replace('_', "-");
+
+    let mut param_types = Vec::new();
+    for input in &function.sig.inputs {
+        match input {
+            FnArg::Typed(pattern) => param_types.push(pattern.ty.clone()),
+            FnArg::Receiver(receiver) => {
+                return syn::Error::new(receiver.span(), "systems can't take `self`")
+                    .to_compile_error()
+                    .into();
+            }
+        }
+    }
+
+    let lazy = param_types.iter().any(|ty| is_lazy_query(ty));
+    let run = if lazy {
+        quote! {
+            pub fn run<I: ::wasvy_guest::QueryIterHandle + 'static>(
+                params: ::std::vec::Vec<I>,
+            ) -> ::wasvy_guest::CommandList {
+                #[allow(unused_mut, unused_variables)]
+                let mut params = params.into_iter();
+                super::#ident(#(
+                    <#param_types as ::wasvy_guest::SystemParam>::from_iters(&mut params)
+                ),*);
+                ::wasvy_guest::CommandList::take()
+            }
+        }
+    } else {
+        quote! {
+            pub fn run<R: ::wasvy_guest::QueryRow>(
+                params: ::std::vec::Vec<::std::vec::Vec<R>>,
+            ) -> ::wasvy_guest::CommandList {
+                #[allow(unused_mut, unused_variables)]
+                let mut params = params.into_iter();
+                super::#ident(#(
+                    <#param_types as ::wasvy_guest::SystemParam>::from_results(&mut params)
+                ),*);
+                ::wasvy_guest::CommandList::take()
+            }
+        }
+    };
+
+    quote! {
+        #function
+
+        #[doc(hidden)]
+        #visibility mod #ident {
+            #[allow(unused_imports)]
+            use super::*;
+
+            pub const NAME: &str = #name;
+
+            pub fn queries() -> ::std::vec::Vec<::wasvy_guest::QueryDescriptor> {
+                #[allow(unused_mut)]
+                let mut queries = ::std::vec::Vec::new();
+                #(<#param_types as ::wasvy_guest::SystemParam>::queries(&mut queries);)*
+                queries
+            }
+
+            pub fn register() {
+                ::wasvy_guest::register_system(NAME, &queries());
+            }
+
+            #run
+        }
+    }
+    .into()
+}
+
+/// Whether the parameter is a `LazyQuery`, which is recognized by its name since the macro can't
+/// resolve types.
+fn is_lazy_query(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "LazyQuery"),
+        Type::Paren(paren) => is_lazy_query(&paren.elem),
+        Type::Group(group) => is_lazy_query(&group.elem),
+        _ => false,
+    }
+}
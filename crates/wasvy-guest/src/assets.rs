@@ -0,0 +1,45 @@
+//! Loading assets with the host's `AssetServer`.
+
+use bevy_reflect::Reflect;
+use serde::{Deserialize, Serialize};
+
+use crate::bindings::wasvy::ecs::functions;
+
+pub use crate::bindings::wasvy::ecs::types::LoadState;
+
+/// An asset loaded with [`load_asset`].
+///
+/// Use it in your components wherever the Rust type on the host has a `Handle<T>`, the host
+/// replaces it with the handle of the asset:
+///
+/// ```rust,ignore
+/// #[derive(TypePath, Serialize, Deserialize)]
+/// #[type_path = "bevy_scene::components"]
+/// struct SceneRoot(AssetHandle);
+///
+/// let ship = load_asset("ship.glb#Scene0").unwrap();
+/// spawn((SceneRoot(ship),));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AssetHandle(pub u64);
+
+impl AssetHandle {
+    /// Where the asset is in its loading process.
+    pub fn load_state(self) -> LoadState {
+        functions::asset_load_state(self.0)
+    }
+
+    pub fn is_loaded(self) -> bool {
+        self.load_state() == LoadState::Loaded
+    }
+}
+
+/// Start loading the asset at `path`, relative to the mod's asset directory on the host (e.g.
+/// `mods/my_mod/` for `mods/my_mod.wasm`).
+///
+/// Returns `None` if the path leaves that directory. Loading the same path again returns the same
+/// handle, so systems can call it every time they run.
+pub fn load_asset(path: &str) -> Option<AssetHandle> {
+    functions::load_asset(path).map(AssetHandle)
+}
@@ -0,0 +1,294 @@
+//! Typed wrappers of the host functions that change the world.
+
+use std::cell::RefCell;
+
+use crate::{
+    ComponentData, Entity, QueryDescriptor, QueryIterHandle, QueryRow, SystemParam,
+    bindings::wasvy::ecs::{functions, types},
+};
+
+/// The `command` variant that is returned to the host by systems that use [`Commands`].
+pub type Command = types::Command;
+
+pub use crate::bindings::wasvy::ecs::types::RegisterError;
+
+/// Register a component that is defined by the mod, so it can be spawned and queried.
+///
+/// Host components like `Transform` are already known by the host and can't be registered. The
+/// component is only known by this mod, other mods can have their own component with the same
+/// type path. The id only depends on the id of the mod and the type path, so it's the same in every
+/// run.
+pub fn register_component<T: ComponentData>() -> Result<types::ComponentId, RegisterError> {
+    functions::register_component(T::type_path())
+}
+
+/// Register a component that is defined by the mod and share it with the other mods, which use it
+/// by its type path without registering it.
+///
+/// Fails if another mod already exported a component with the same type path.
+pub fn export_component<T: ComponentData>() -> Result<types::ComponentId, RegisterError> {
+    functions::export_component(T::type_path())
+}
+
+/// Spawn an entity with the given components.
+///
+/// ```rust,ignore
+/// spawn((Player { speed: 5.0 }, Transform::default()));
+/// ```
+pub fn spawn(bundle: impl Bundle) -> Entity {
+    Entity(functions::spawn(&bundle.into_components()))
+}
+
+/// Spawn an entity as a child of `parent`, like spawning it with Bevy's `ChildOf`.
+pub fn spawn_child(parent: Entity, bundle: impl Bundle) -> Entity {
+    Entity(functions::spawn_child(parent.0, &bundle.into_components()))
+}
+
+/// Make `entity` a child of `parent`, moving it out of the children of its current parent.
+///
+/// Nothing happens if the host's permissions don't let the mod change the parents of these
+/// entities.
+pub fn set_parent(entity: Entity, parent: Entity) {
+    functions::set_parent(entity.0, parent.0);
+}
+
+/// Remove the parent of `entity`, making it a root entity.
+pub fn remove_parent(entity: Entity) {
+    functions::remove_parent(entity.0);
+}
+
+/// Despawn `entity` along with all its descendants.
+///
+/// Nothing happens if the host's permissions don't let the mod despawn one of them.
+pub fn despawn_recursive(entity: Entity) {
+    functions::despawn_recursive(entity.0);
+}
+
+thread_local! {
+    static QUEUE: RefCell<Vec<Command>> = const { RefCell::new(Vec::new()) };
+}
+
+fn push(command: Command) {
+    QUEUE.with_borrow_mut(|queue| queue.push(command));
+}
+
+/// Queues changes to the world, like Bevy's `Commands`.
+///
+/// The commands are returned by the system and applied by the host in order once the system
+/// returns, so their effects are only visible to the systems that run afterwards. The exported
+/// function of the system must return a `list<command>`:
+///
+/// ```rust,ignore
+/// #[wasvy_guest::system]
+/// fn kill_players(mut commands: Commands, players: Query<&Health, With<Player>>) {
+///     for (entity, health) in &players {
+///         if health.0 == 0 {
+///             commands.despawn(entity);
+///         }
+///     }
+/// }
+///
+/// impl Guest for MyMod {
+///     fn kill_players(params: Vec<bindings::QueryResult>) -> Vec<bindings::Command> {
+///         kill_players::run(params).into()
+///     }
+/// }
+/// ```
+pub struct Commands(());
+
+impl Commands {
+    /// Spawn an entity with the given components.
+    ///
+    /// The entity is only spawned once the system returns, use [`spawn`] in case you need its id.
+    pub fn spawn(&mut self, bundle: impl Bundle) {
+        push(Command::Spawn(bundle.into_components()));
+    }
+
+    /// Add the components to the entity, replacing the ones it already has.
+    pub fn insert(&mut self, entity: Entity, bundle: impl Bundle) {
+        push(Command::Insert(types::InsertCommand {
+            entity: entity.0,
+            components: bundle.into_components(),
+        }));
+    }
+
+    /// Remove the components of the bundle from the entity, e.g. `remove::<(Poisoned, Burning)>`.
+    pub fn remove<B: Bundle>(&mut self, entity: Entity) {
+        let mut components = Vec::new();
+        B::component_paths(&mut components);
+        push(Command::Remove(types::RemoveCommand {
+            entity: entity.0,
+            components,
+        }));
+    }
+
+    /// Spawn an entity with the given components as a child of `parent`.
+    ///
+    /// Like [`Commands::spawn`], use [`spawn_child`] in case you need its id.
+    pub fn spawn_child(&mut self, parent: Entity, bundle: impl Bundle) {
+        push(Command::SpawnChild(types::SpawnChildCommand {
+            parent: parent.0,
+            components: bundle.into_components(),
+        }));
+    }
+
+    /// Make `entity` a child of `parent`.
+    pub fn set_parent(&mut self, entity: Entity, parent: Entity) {
+        push(Command::SetParent(types::SetParentCommand {
+            entity: entity.0,
+            parent: parent.0,
+        }));
+    }
+
+    /// Remove the parent of `entity`, making it a root entity.
+    pub fn remove_parent(&mut self, entity: Entity) {
+        push(Command::RemoveParent(entity.0));
+    }
+
+    /// Despawn the entity along with all its descendants.
+    pub fn despawn(&mut self, entity: Entity) {
+        push(Command::Despawn(entity.0));
+    }
+
+    /// Insert (or replace) a resource. The resource must be registered in the host's type registry.
+    pub fn insert_resource<T: ComponentData>(&mut self, resource: T) {
+        push(Command::InsertResource(serialize(&resource)));
+    }
+
+    pub fn remove_resource<T: ComponentData>(&mut self) {
+        push(Command::RemoveResource(T::type_path().to_string()));
+    }
+}
+
+impl SystemParam for Commands {
+    fn queries(_queries: &mut Vec<QueryDescriptor>) {}
+
+    fn from_results<R: QueryRow>(_results: &mut std::vec::IntoIter<Vec<R>>) -> Self {
+        Commands(())
+    }
+
+    fn from_iters<I: QueryIterHandle + 'static>(_iters: &mut std::vec::IntoIter<I>) -> Self {
+        Commands(())
+    }
+}
+
+/// The commands that were queued while running a system, returned by `run()` of the module
+/// generated by `#[wasvy_guest::system]`.
+///
+/// Convert it into the `list<command>` of your bindings with `.into()`, after implementing the
+/// conversion with [`command_type!`](crate::command_type). Systems that don't return any commands
+/// can ignore it.
+#[derive(Debug, Default)]
+pub struct CommandList(pub Vec<Command>);
+
+impl CommandList {
+    /// Takes the commands that were queued since the last call.
+    pub fn take() -> Self {
+        Self(QUEUE.take())
+    }
+}
+
+impl<C: From<Command>> From<CommandList> for Vec<C> {
+    fn from(list: CommandList) -> Self {
+        list.0.into_iter().map(C::from).collect()
+    }
+}
+
+/// Implements `From<wasvy_guest::Command>` for the `command` variant generated for your mod,
+/// given the path of the `wasvy:ecs/types` module of your bindings.
+///
+/// ```rust,ignore
+/// wasvy_guest::command_type!(bindings::wasvy::ecs::types);
+/// ```
+#[macro_export]
+macro_rules! command_type {
+    ($($types:ident)::+) => {
+        impl ::std::convert::From<$crate::Command> for $($types)::+::Command {
+            fn from(command: $crate::Command) -> Self {
+                fn component(component: $crate::__private::Component) -> $($types)::+::Component {
+                    $($types)::+::Component {
+                        path: component.path,
+                        value: component.value,
+                    }
+                }
+
+                match command {
+                    $crate::Command::Spawn(components) => {
+                        Self::Spawn(components.into_iter().map(component).collect())
+                    }
+                    $crate::Command::Insert(insert) => {
+                        Self::Insert($($types)::+::InsertCommand {
+                            entity: insert.entity,
+                            components: insert.components.into_iter().map(component).collect(),
+                        })
+                    }
+                    $crate::Command::Remove(remove) => {
+                        Self::Remove($($types)::+::RemoveCommand {
+                            entity: remove.entity,
+                            components: remove.components,
+                        })
+                    }
+                    $crate::Command::Despawn(entity) => Self::Despawn(entity),
+                    $crate::Command::InsertResource(resource) => {
+                        Self::InsertResource(component(resource))
+                    }
+                    $crate::Command::RemoveResource(path) => Self::RemoveResource(path),
+                    $crate::Command::SpawnChild(spawn) => {
+                        Self::SpawnChild($($types)::+::SpawnChildCommand {
+                            parent: spawn.parent,
+                            components: spawn.components.into_iter().map(component).collect(),
+                        })
+                    }
+                    $crate::Command::SetParent(set) => {
+                        Self::SetParent($($types)::+::SetParentCommand {
+                            entity: set.entity,
+                            parent: set.parent,
+                        })
+                    }
+                    $crate::Command::RemoveParent(entity) => Self::RemoveParent(entity),
+                }
+            }
+        }
+    };
+}
+
+/// A tuple of components that can be spawned together.
+pub trait Bundle {
+    fn into_components(self) -> Vec<types::Component>;
+
+    /// The type paths of the components, used to remove them.
+    fn component_paths(paths: &mut Vec<String>);
+}
+
+fn serialize<T: ComponentData>(component: &T) -> types::Component {
+    types::Component {
+        path: T::type_path().to_string(),
+        value: serde_json::to_string(component)
+            .unwrap_or_else(|err| panic!("could not serialize {}: {err}", T::type_path())),
+    }
+}
+
+macro_rules! impl_bundle {
+    ($($name:ident),+) => {
+        impl<$($name: ComponentData),+> Bundle for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn into_components(self) -> Vec<types::Component> {
+                let ($($name,)+) = self;
+                vec![$(serialize(&$name)),+]
+            }
+
+            fn component_paths(paths: &mut Vec<String>) {
+                $(paths.push($name::type_path().to_string());)+
+            }
+        }
+    };
+}
+
+impl_bundle!(A);
+impl_bundle!(A, B);
+impl_bundle!(A, B, C);
+impl_bundle!(A, B, C, D);
+impl_bundle!(A, B, C, D, E);
+impl_bundle!(A, B, C, D, E, F);
+impl_bundle!(A, B, C, D, E, F, G);
+impl_bundle!(A, B, C, D, E, F, G, H);
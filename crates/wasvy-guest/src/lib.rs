@@ -0,0 +1,78 @@
+//! Typed helpers for writing [Wasvy](https://crates.io/crates/wasvy) mods in Rust.
+//!
+//! Instead of building `query` records out of type paths and deserializing every component by
+//! index, describe your systems with regular Rust types:
+//!
+//! ```rust,ignore
+//! use wasvy_guest::prelude::*;
+//!
+//! #[wasvy_guest::system]
+//! fn move_players(players: Query<(&Player, &Transform)>) {
+//!     for (entity, (player, transform)) in &players {
+//!         println!("{entity:?}: {player:?} at {transform:?}");
+//!     }
+//! }
+//!
+//! impl Guest for MyMod {
+//!     fn move_players(params: Vec<bindings::QueryResult>) {
+//!         move_players::run(params);
+//!     }
+//!
+//!     fn setup() {
+//!         register_component::<Player>().unwrap();
+//!         move_players::register();
+//!         spawn((Player::default(), Transform::default()));
+//!     }
+//! }
+//! ```
+
+mod bindings {
+    wit_bindgen::generate!({
+        path: "../../wit/ecs",
+        world: "host",
+    });
+}
+
+pub mod assets;
+pub mod commands;
+pub mod lookup;
+pub mod prelude;
+pub mod query;
+pub mod storage;
+pub mod time;
+
+pub use assets::{AssetHandle, LoadState, load_asset};
+pub use commands::{
+    Bundle, Command, CommandList, Commands, RegisterError, despawn_recursive, export_component,
+    register_component, remove_parent, set_parent, spawn, spawn_child,
+};
+pub use lookup::{get, get_many, set, set_many};
+pub use query::{
+    Added, Changed, Children, Entity, Has, LazyQuery, Parent, Query, QueryData, QueryDescriptor,
+    QueryFilter, QueryIterHandle, QueryRow, RemovedComponents, RowValues, SystemParam, With,
+    Without,
+};
+pub use time::{FrameTime, random, random_f64, time};
+pub use wasvy_guest_macros::system;
+
+use bevy_reflect::TypePath;
+use serde::{Serialize, de::DeserializeOwned};
+
+/// Any type that can be sent to and from the host as a Bevy component.
+///
+/// The [`TypePath`] is the key the component is registered under, so it must match the path of
+/// the type on the host for host components like `Transform`.
+pub trait ComponentData: TypePath + Serialize + DeserializeOwned {}
+
+impl<T: TypePath + Serialize + DeserializeOwned> ComponentData for T {}
+
+/// Register a system with the host. This is what `#[wasvy_guest::system]` calls in `register()`.
+pub fn register_system(name: &str, queries: &[QueryDescriptor]) {
+    bindings::wasvy::ecs::functions::register_system(name, queries);
+}
+
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::bindings::wasvy::ecs::types::Component;
+    pub use serde_json;
+}
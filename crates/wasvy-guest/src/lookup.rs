@@ -0,0 +1,75 @@
+//! Typed wrappers of the host functions that look up single entities, like Bevy's `Query::get`.
+
+use crate::{
+    Bundle, Entity, QueryData,
+    bindings::wasvy::ecs::{functions, types},
+    query::RowValues,
+};
+
+/// Look up the components of an entity, e.g. the target stored in another component:
+///
+/// ```rust,ignore
+/// #[wasvy_guest::system]
+/// fn follow(followers: Query<(&Follow, &Transform)>, _targets: Query<&Transform, With<Leader>>) {
+///     for (entity, (follow, transform)) in &followers {
+///         if let Some(target) = get::<&Transform>(follow.target) {
+///             // ...
+///         }
+///     }
+/// }
+/// ```
+///
+/// Inside a system, only the components that one of its queries reads can be looked up, and the
+/// entity must match that query. Returns `None` if the entity doesn't have all the components, or
+/// the system isn't allowed to read them.
+///
+/// Only `&T` (and tuples of them) can be looked up, `Option<&T>` and `Has<T>` aren't supported.
+pub fn get<D: QueryData>(entity: Entity) -> Option<D::Item> {
+    let components = functions::get(entity.0, &component_paths::<D>())?;
+    Some(from_components::<D>(components))
+}
+
+/// [`get`] for many entities at once, with a result for each entity.
+pub fn get_many<D: QueryData>(entities: &[Entity]) -> Vec<Option<D::Item>> {
+    let entities: Vec<_> = entities.iter().map(|entity| entity.0).collect();
+    functions::get_many(&entities, &component_paths::<D>())
+        .into_iter()
+        .map(|components| components.map(from_components::<D>))
+        .collect()
+}
+
+/// Replace the components of an entity, following the same access rules as [`get`]: the entity
+/// must already have all of them.
+///
+/// Like [`Commands::insert`](crate::Commands::insert), the change is applied after the system
+/// returns. Returns false (and changes nothing) if [`get`] wouldn't return the components, the host
+/// doesn't allow the mod to change one of them, or one of the values doesn't match its type.
+pub fn set(entity: Entity, bundle: impl Bundle) -> bool {
+    functions::set(entity.0, &bundle.into_components())
+}
+
+/// [`set`] for many entities at once, with a result for each entity.
+pub fn set_many<B: Bundle>(entities: impl IntoIterator<Item = (Entity, B)>) -> Vec<bool> {
+    let entities: Vec<_> = entities
+        .into_iter()
+        .map(|(entity, bundle)| types::InsertCommand {
+            entity: entity.0,
+            components: bundle.into_components(),
+        })
+        .collect();
+    functions::set_many(&entities)
+}
+
+fn component_paths<D: QueryData>() -> Vec<String> {
+    let mut paths = vec![];
+    D::component_paths(&mut paths);
+    paths
+}
+
+fn from_components<D: QueryData>(components: Vec<types::Component>) -> D::Item {
+    let values = components
+        .into_iter()
+        .map(|component| Some(component.value))
+        .collect();
+    D::from_values(&mut RowValues::from_components(values))
+}
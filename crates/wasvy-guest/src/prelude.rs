@@ -0,0 +1,11 @@
+pub use crate::assets::{AssetHandle, LoadState, load_asset};
+pub use crate::commands::{
+    Bundle, Commands, despawn_recursive, export_component, register_component, remove_parent,
+    set_parent, spawn, spawn_child,
+};
+pub use crate::lookup::{get, get_many, set, set_many};
+pub use crate::query::{
+    Added, Changed, Children, Entity, Has, LazyQuery, Parent, Query, RemovedComponents, With,
+    Without,
+};
+pub use crate::time::{random, random_f64, time};
@@ -0,0 +1,777 @@
+//! Typed queries that are passed as parameters to guest systems.
+
+use std::{marker::PhantomData, slice::Iter};
+
+use crate::{ComponentData, bindings::wasvy::ecs::types};
+
+/// The `query` record that is sent to the host when registering a system.
+pub type QueryDescriptor = types::Query;
+
+/// An entity on the host.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Entity(pub u64);
+
+/// One row of a query result in the bindings generated for your mod.
+///
+/// Implement it for the `query-result-entry` type of your bindings with [`query_row!`](crate::query_row).
+pub trait QueryRow {
+    fn entity(&self) -> u64;
+    /// The JSON serialized components, in the order they were requested, followed by the optional ones.
+    fn into_values(self) -> (Vec<Option<String>>, Vec<bool>);
+    /// The parent and children of the entity, for queries that fetch them.
+    fn hierarchy(&self) -> (Option<u64>, Vec<u64>);
+}
+
+/// Implements [`QueryRow`] for the `query-result-entry` record generated for your mod.
+///
+/// ```rust,ignore
+/// wasvy_guest::query_row!(bindings::wasvy::ecs::types::QueryResultEntry);
+/// ```
+#[macro_export]
+macro_rules! query_row {
+    ($row:path) => {
+        impl $crate::QueryRow for $row {
+            fn entity(&self) -> u64 {
+                self.entity
+            }
+
+            fn into_values(
+                self,
+            ) -> (
+                ::std::vec::Vec<::std::option::Option<::std::string::String>>,
+                ::std::vec::Vec<bool>,
+            ) {
+                let values = self
+                    .components
+                    .into_iter()
+                    .map(|component| component.map(|component| component.value))
+                    .collect();
+                (values, self.has)
+            }
+
+            fn hierarchy(&self) -> (::std::option::Option<u64>, ::std::vec::Vec<u64>) {
+                (self.parent, self.children.clone())
+            }
+        }
+    };
+}
+
+/// The `query-iter` resource in the bindings generated for your mod, which reads the rows of a
+/// query from the host as they're needed. See [`LazyQuery`].
+///
+/// Implement it for the resource of your bindings with [`query_iter!`](crate::query_iter).
+pub trait QueryIterHandle {
+    type Row: QueryRow;
+
+    /// The next rows of the query, at most `n` of them. Empty once all the rows were read.
+    fn next_batch(&self, n: u32) -> Vec<Self::Row>;
+
+    /// The number of rows that weren't read yet.
+    fn remaining(&self) -> u32;
+}
+
+/// Implements [`QueryIterHandle`] for the `query-iter` resource generated for your mod, whose
+/// rows also need [`query_row!`](crate::query_row).
+///
+/// ```rust,ignore
+/// wasvy_guest::query_row!(bindings::wasvy::ecs::types::QueryResultEntry);
+/// wasvy_guest::query_iter!(bindings::wasvy::ecs::types);
+/// ```
+#[macro_export]
+macro_rules! query_iter {
+    ($($types:ident)::+) => {
+        impl $crate::QueryIterHandle for $($types)::+::QueryIter {
+            type Row = $($types)::+::QueryResultEntry;
+
+            fn next_batch(&self, n: u32) -> ::std::vec::Vec<Self::Row> {
+                $($types)::+::QueryIter::next_batch(self, n)
+            }
+
+            fn remaining(&self) -> u32 {
+                $($types)::+::QueryIter::remaining(self)
+            }
+        }
+    };
+}
+
+/// All the rows of a query iterator.
+fn read_all<I: QueryIterHandle>(iter: &I) -> Vec<I::Row> {
+    iter.next_batch(u32::MAX)
+}
+
+/// The values of one row, split by the kind of slot they were requested in.
+pub struct RowValues {
+    components: std::vec::IntoIter<Option<String>>,
+    optional: std::vec::IntoIter<Option<String>>,
+    has: std::vec::IntoIter<bool>,
+    parent: Option<Entity>,
+    children: Vec<Entity>,
+}
+
+impl RowValues {
+    fn new<D: QueryData>(row: impl QueryRow) -> Self {
+        let mut paths = vec![];
+        D::component_paths(&mut paths);
+
+        let (parent, children) = row.hierarchy();
+        let (mut components, has) = row.into_values();
+        let optional = components.split_off(paths.len().min(components.len()));
+        Self {
+            components: components.into_iter(),
+            optional: optional.into_iter(),
+            has: has.into_iter(),
+            parent: parent.map(Entity),
+            children: children.into_iter().map(Entity).collect(),
+        }
+    }
+    /// The values of a row that only has required components.
+    pub(crate) fn from_components(components: Vec<Option<String>>) -> Self {
+        Self {
+            components: components.into_iter(),
+            optional: vec![].into_iter(),
+            has: vec![].into_iter(),
+            parent: None,
+            children: vec![],
+        }
+    }
+}
+
+/// The components that are fetched by a [`Query`], e.g. `&A`, `(&A, Option<&B>)` or `(&A, Has<B>)`.
+pub trait QueryData {
+    /// The deserialized components of one row.
+    type Item;
+
+    fn component_paths(_paths: &mut Vec<String>) {}
+
+    fn optional_paths(_paths: &mut Vec<String>) {}
+
+    fn has_paths(_paths: &mut Vec<String>) {}
+
+    /// Whether the parent and children of the entities are fetched.
+    fn hierarchy() -> bool {
+        false
+    }
+
+    fn from_values(values: &mut RowValues) -> Self::Item;
+}
+
+impl<T: ComponentData> QueryData for &T {
+    type Item = T;
+
+    fn component_paths(paths: &mut Vec<String>) {
+        paths.push(T::type_path().to_string());
+    }
+
+    fn from_values(values: &mut RowValues) -> Self::Item {
+        let value = values
+            .components
+            .next()
+            .flatten()
+            .unwrap_or_else(|| panic!("the host didn't send a value for {}", T::type_path()));
+        deserialize::<T>(&value)
+    }
+}
+
+/// Fetch the component `T` if the entity has it, without restricting which entities match.
+impl<T: ComponentData> QueryData for Option<&T> {
+    type Item = Option<T>;
+
+    fn optional_paths(paths: &mut Vec<String>) {
+        paths.push(T::type_path().to_string());
+    }
+
+    fn from_values(values: &mut RowValues) -> Self::Item {
+        let value = values
+            .optional
+            .next()
+            .unwrap_or_else(|| panic!("the host didn't send a slot for {}", T::type_path()));
+        value.map(|value| deserialize::<T>(&value))
+    }
+}
+
+/// Whether the entity has the component `T`, like Bevy's `Has<T>`.
+pub struct Has<T>(PhantomData<T>);
+
+impl<T: ComponentData> QueryData for Has<T> {
+    type Item = bool;
+
+    fn has_paths(paths: &mut Vec<String>) {
+        paths.push(T::type_path().to_string());
+    }
+
+    fn from_values(values: &mut RowValues) -> Self::Item {
+        values
+            .has
+            .next()
+            .unwrap_or_else(|| panic!("the host didn't send `has` for {}", T::type_path()))
+    }
+}
+
+/// The parent of the entity, like Bevy's `ChildOf`, or `None` if it's a root entity.
+pub struct Parent;
+
+impl QueryData for Parent {
+    type Item = Option<Entity>;
+
+    fn hierarchy() -> bool {
+        true
+    }
+
+    fn from_values(values: &mut RowValues) -> Self::Item {
+        values.parent
+    }
+}
+
+/// The children of the entity, like Bevy's `Children`.
+pub struct Children;
+
+impl QueryData for Children {
+    type Item = Vec<Entity>;
+
+    fn hierarchy() -> bool {
+        true
+    }
+
+    fn from_values(values: &mut RowValues) -> Self::Item {
+        values.children.clone()
+    }
+}
+
+fn deserialize<T: ComponentData>(value: &str) -> T {
+    serde_json::from_str(value)
+        .unwrap_or_else(|err| panic!("could not deserialize {}: {err}", T::type_path()))
+}
+
+/// Filters the entities that are matched by a [`Query`], e.g. `With<A>` or `(With<A>, Without<B>)`.
+pub trait QueryFilter {
+    fn with_paths(_paths: &mut Vec<String>) {}
+
+    fn without_paths(_paths: &mut Vec<String>) {}
+
+    fn added_paths(_paths: &mut Vec<String>) {}
+
+    fn changed_paths(_paths: &mut Vec<String>) {}
+}
+
+impl QueryFilter for () {}
+
+/// Only match entities that have the component `T`, without fetching it.
+pub struct With<T>(PhantomData<T>);
+
+impl<T: ComponentData> QueryFilter for With<T> {
+    fn with_paths(paths: &mut Vec<String>) {
+        paths.push(T::type_path().to_string());
+    }
+}
+
+/// Only match entities that don't have the component `T`.
+pub struct Without<T>(PhantomData<T>);
+
+impl<T: ComponentData> QueryFilter for Without<T> {
+    fn without_paths(paths: &mut Vec<String>) {
+        paths.push(T::type_path().to_string());
+    }
+}
+
+/// Only match entities whose component `T` was added since the system last ran.
+pub struct Added<T>(PhantomData<T>);
+
+impl<T: ComponentData> QueryFilter for Added<T> {
+    fn added_paths(paths: &mut Vec<String>) {
+        paths.push(T::type_path().to_string());
+    }
+}
+
+/// Only match entities whose component `T` was added or changed since the system last ran.
+pub struct Changed<T>(PhantomData<T>);
+
+impl<T: ComponentData> QueryFilter for Changed<T> {
+    fn changed_paths(paths: &mut Vec<String>) {
+        paths.push(T::type_path().to_string());
+    }
+}
+
+macro_rules! impl_tuples {
+    ($($name:ident),+) => {
+        impl<$($name: QueryData),+> QueryData for ($($name,)+) {
+            type Item = ($($name::Item,)+);
+
+            fn component_paths(paths: &mut Vec<String>) {
+                $($name::component_paths(paths);)+
+            }
+
+            fn optional_paths(paths: &mut Vec<String>) {
+                $($name::optional_paths(paths);)+
+            }
+
+            fn has_paths(paths: &mut Vec<String>) {
+                $($name::has_paths(paths);)+
+            }
+
+            fn hierarchy() -> bool {
+                $($name::hierarchy())||+
+            }
+
+            fn from_values(values: &mut RowValues) -> Self::Item {
+                ($($name::from_values(values),)+)
+            }
+        }
+
+        impl<$($name: QueryFilter),+> QueryFilter for ($($name,)+) {
+            fn with_paths(paths: &mut Vec<String>) {
+                $($name::with_paths(paths);)+
+            }
+
+            fn without_paths(paths: &mut Vec<String>) {
+                $($name::without_paths(paths);)+
+            }
+
+            fn added_paths(paths: &mut Vec<String>) {
+                $($name::added_paths(paths);)+
+            }
+
+            fn changed_paths(paths: &mut Vec<String>) {
+                $($name::changed_paths(paths);)+
+            }
+        }
+    };
+}
+
+impl_tuples!(A);
+impl_tuples!(A, B);
+impl_tuples!(A, B, C);
+impl_tuples!(A, B, C, D);
+impl_tuples!(A, B, C, D, E);
+impl_tuples!(A, B, C, D, E, F);
+impl_tuples!(A, B, C, D, E, F, G);
+impl_tuples!(A, B, C, D, E, F, G, H);
+
+/// The typed equivalent of Bevy's `Query`, holding the rows the host sent for this system.
+pub struct Query<D: QueryData, F: QueryFilter = ()> {
+    rows: Vec<(Entity, D::Item)>,
+    _filter: PhantomData<F>,
+}
+
+impl<D: QueryData, F: QueryFilter> Query<D, F> {
+    /// The `query` record that makes the host send the rows of this query.
+    pub fn descriptor() -> QueryDescriptor {
+        let mut descriptor = empty_descriptor();
+        D::component_paths(&mut descriptor.components);
+        D::optional_paths(&mut descriptor.optional);
+        D::has_paths(&mut descriptor.has);
+        descriptor.hierarchy = D::hierarchy();
+        F::with_paths(&mut descriptor.with);
+        F::without_paths(&mut descriptor.without);
+        F::added_paths(&mut descriptor.added);
+        F::changed_paths(&mut descriptor.changed);
+        descriptor
+    }
+
+    /// Deserialize the rows the host sent for this query.
+    pub fn from_rows<R: QueryRow>(rows: Vec<R>) -> Self {
+        let rows = rows.into_iter().map(item::<D>).collect();
+
+        Self {
+            rows,
+            _filter: PhantomData,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &D::Item)> {
+        self.into_iter()
+    }
+
+    /// Returns the components of the given entity, if it's matched by this query.
+    pub fn get(&self, entity: Entity) -> Option<&D::Item> {
+        self.rows
+            .iter()
+            .find(|(row_entity, _)| *row_entity == entity)
+            .map(|(_, item)| item)
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+}
+
+impl<'a, D: QueryData, F: QueryFilter> IntoIterator for &'a Query<D, F> {
+    type Item = (Entity, &'a D::Item);
+    type IntoIter = std::iter::Map<
+        Iter<'a, (Entity, D::Item)>,
+        fn(&'a (Entity, D::Item)) -> (Entity, &'a D::Item),
+    >;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.rows.iter().map(row_ref as _)
+    }
+}
+
+/// Deserialize one row of a query.
+fn item<D: QueryData>(row: impl QueryRow) -> (Entity, D::Item) {
+    let entity = Entity(row.entity());
+    let mut values = RowValues::new::<D>(row);
+    (entity, D::from_values(&mut values))
+}
+
+/// A [`Query`] that reads its rows from the host while it's iterated, instead of receiving all of
+/// them before the system runs.
+///
+/// The host only serializes the rows that are read, so systems that don't read every row of a
+/// large query (e.g. with `find` or `take`) don't pay for the others. A system that takes a
+/// `LazyQuery` gets a `list<query-iter>` instead of a `list<query-result>`, and its other
+/// parameters read all of their rows up front:
+///
+/// ```rust,ignore
+/// wasvy_guest::query_iter!(bindings::wasvy::ecs::types);
+///
+/// #[wasvy_guest::system]
+/// fn find_boss(enemies: LazyQuery<&Enemy>) {
+///     if let Some((entity, _)) = enemies.into_iter().find(|(_, enemy)| enemy.boss) {
+///         println!("{entity:?} is the boss");
+///     }
+/// }
+///
+/// impl Guest for MyMod {
+///     fn find_boss(params: Vec<bindings::QueryIter>) {
+///         find_boss::run(params);
+///     }
+/// }
+/// ```
+pub struct LazyQuery<D: QueryData, F: QueryFilter = ()> {
+    iter: Box<dyn ErasedQueryIter<D>>,
+    batch_size: u32,
+    /// The rows that were read from the host but weren't returned yet.
+    read: std::vec::IntoIter<(Entity, D::Item)>,
+    _filter: PhantomData<F>,
+}
+
+impl<D: QueryData, F: QueryFilter> LazyQuery<D, F> {
+    /// Read `n` rows at a time from the host instead of one, which is faster for systems that read
+    /// most of their rows.
+    pub fn with_batch_size(mut self, n: u32) -> Self {
+        self.batch_size = n.max(1);
+        self
+    }
+
+    /// The number of rows that weren't returned yet.
+    pub fn len(&self) -> usize {
+        self.read.len() + self.iter.remaining() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<D: QueryData, F: QueryFilter> Iterator for LazyQuery<D, F> {
+    type Item = (Entity, D::Item);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(item) = self.read.next() {
+            return Some(item);
+        }
+        self.read = self.iter.next_items(self.batch_size).into_iter();
+        self.read.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+/// A [`QueryIterHandle`] that deserializes its rows, so [`LazyQuery`] doesn't depend on the type
+/// of the bindings.
+trait ErasedQueryIter<D: QueryData> {
+    fn next_items(&self, n: u32) -> Vec<(Entity, D::Item)>;
+
+    fn remaining(&self) -> u32;
+}
+
+impl<D: QueryData, I: QueryIterHandle> ErasedQueryIter<D> for I {
+    fn next_items(&self, n: u32) -> Vec<(Entity, D::Item)> {
+        self.next_batch(n).into_iter().map(item::<D>).collect()
+    }
+
+    fn remaining(&self) -> u32 {
+        QueryIterHandle::remaining(self)
+    }
+}
+
+/// The iterator of a [`LazyQuery`] whose rows were all sent up front.
+struct NoMoreRows;
+
+impl<D: QueryData> ErasedQueryIter<D> for NoMoreRows {
+    fn next_items(&self, _n: u32) -> Vec<(Entity, D::Item)> {
+        vec![]
+    }
+
+    fn remaining(&self) -> u32 {
+        0
+    }
+}
+
+fn empty_descriptor() -> QueryDescriptor {
+    QueryDescriptor {
+        components: vec![],
+        optional: vec![],
+        has: vec![],
+        with: vec![],
+        without: vec![],
+        added: vec![],
+        changed: vec![],
+        removed: vec![],
+        hierarchy: false,
+    }
+}
+
+fn row_ref<Item>((entity, item): &(Entity, Item)) -> (Entity, &Item) {
+    (*entity, item)
+}
+
+/// A parameter of a system annotated with `#[wasvy_guest::system]`.
+pub trait SystemParam {
+    /// Add the queries that the host should run for this parameter.
+    fn queries(queries: &mut Vec<QueryDescriptor>);
+
+    /// Build the parameter, taking the results of the queries it added in [`SystemParam::queries`].
+    fn from_results<R: QueryRow>(results: &mut std::vec::IntoIter<Vec<R>>) -> Self;
+
+    /// Build the parameter of a system that takes a `list<query-iter>`, taking the iterators of
+    /// the queries it added in [`SystemParam::queries`].
+    fn from_iters<I: QueryIterHandle + 'static>(iters: &mut std::vec::IntoIter<I>) -> Self;
+}
+
+impl<D: QueryData, F: QueryFilter> SystemParam for Query<D, F> {
+    fn queries(queries: &mut Vec<QueryDescriptor>) {
+        queries.push(Query::<D, F>::descriptor());
+    }
+
+    fn from_results<R: QueryRow>(results: &mut std::vec::IntoIter<Vec<R>>) -> Self {
+        let rows = results
+            .next()
+            .expect("the host sent less query results than the system has queries");
+        Query::<D, F>::from_rows(rows)
+    }
+
+    fn from_iters<I: QueryIterHandle + 'static>(iters: &mut std::vec::IntoIter<I>) -> Self {
+        let iter = iters
+            .next()
+            .expect("the host sent less query iterators than the system has queries");
+        Query::<D, F>::from_rows(read_all(&iter))
+    }
+}
+
+impl<D: QueryData, F: QueryFilter> SystemParam for LazyQuery<D, F> {
+    fn queries(queries: &mut Vec<QueryDescriptor>) {
+        queries.push(Query::<D, F>::descriptor());
+    }
+
+    fn from_results<R: QueryRow>(results: &mut std::vec::IntoIter<Vec<R>>) -> Self {
+        let rows = results
+            .next()
+            .expect("the host sent less query results than the system has queries");
+        Self {
+            iter: Box::new(NoMoreRows),
+            batch_size: 1,
+            read: rows
+                .into_iter()
+                .map(item::<D>)
+                .collect::<Vec<_>>()
+                .into_iter(),
+            _filter: PhantomData,
+        }
+    }
+
+    fn from_iters<I: QueryIterHandle + 'static>(iters: &mut std::vec::IntoIter<I>) -> Self {
+        let iter = iters
+            .next()
+            .expect("the host sent less query iterators than the system has queries");
+        Self {
+            iter: Box::new(iter),
+            batch_size: 1,
+            read: vec![].into_iter(),
+            _filter: PhantomData,
+        }
+    }
+}
+
+/// The entities that had the component `T` removed (or were despawned) since the system last ran,
+/// like Bevy's `RemovedComponents`.
+pub struct RemovedComponents<T> {
+    entities: Vec<Entity>,
+    _component: PhantomData<T>,
+}
+
+impl<T> RemovedComponents<T> {
+    pub fn iter(&self) -> impl Iterator<Item = Entity> {
+        self.entities.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RemovedComponents<T> {
+    type Item = Entity;
+    type IntoIter = std::iter::Copied<Iter<'a, Entity>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entities.iter().copied()
+    }
+}
+
+impl<T: ComponentData> SystemParam for RemovedComponents<T> {
+    fn queries(queries: &mut Vec<QueryDescriptor>) {
+        let mut descriptor = empty_descriptor();
+        descriptor.removed.push(T::type_path().to_string());
+        queries.push(descriptor);
+    }
+
+    fn from_results<R: QueryRow>(results: &mut std::vec::IntoIter<Vec<R>>) -> Self {
+        let rows = results
+            .next()
+            .expect("the host sent less query results than the system has queries");
+        Self {
+            entities: rows.iter().map(|row| Entity(row.entity())).collect(),
+            _component: PhantomData,
+        }
+    }
+
+    fn from_iters<I: QueryIterHandle + 'static>(iters: &mut std::vec::IntoIter<I>) -> Self {
+        let iter = iters
+            .next()
+            .expect("the host sent less query iterators than the system has queries");
+        Self {
+            entities: read_all(&iter)
+                .iter()
+                .map(|row| Entity(row.entity()))
+                .collect(),
+            _component: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+    };
+
+    use bevy_reflect::TypePath;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, TypePath, Serialize, Deserialize)]
+    struct Health(u32);
+
+    struct Row {
+        entity: u64,
+        value: String,
+    }
+
+    impl QueryRow for Row {
+        fn entity(&self) -> u64 {
+            self.entity
+        }
+
+        fn into_values(self) -> (Vec<Option<String>>, Vec<bool>) {
+            (vec![Some(self.value)], vec![])
+        }
+
+        fn hierarchy(&self) -> (Option<u64>, Vec<u64>) {
+            (None, vec![])
+        }
+    }
+
+    /// A `query-iter` that counts the rows it serialized.
+    struct Rows {
+        rows: RefCell<std::vec::IntoIter<Row>>,
+        serialized: Rc<Cell<usize>>,
+    }
+
+    impl Rows {
+        fn new(n: u64, serialized: &Rc<Cell<usize>>) -> Self {
+            let rows = (0..n)
+                .map(|entity| Row {
+                    entity,
+                    value: entity.to_string(),
+                })
+                .collect::<Vec<_>>();
+            Self {
+                rows: RefCell::new(rows.into_iter()),
+                serialized: serialized.clone(),
+            }
+        }
+    }
+
+    impl QueryIterHandle for Rows {
+        type Row = Row;
+
+        fn next_batch(&self, n: u32) -> Vec<Row> {
+            let batch: Vec<Row> = self.rows.borrow_mut().by_ref().take(n as usize).collect();
+            self.serialized.set(self.serialized.get() + batch.len());
+            batch
+        }
+
+        fn remaining(&self) -> u32 {
+            self.rows.borrow().len() as u32
+        }
+    }
+
+    #[test]
+    fn lazy_queries_only_serialize_the_rows_that_are_read() {
+        let serialized = Rc::new(Cell::new(0));
+        let mut iters = vec![Rows::new(100, &serialized)].into_iter();
+        let query = LazyQuery::<&Health>::from_iters(&mut iters);
+        assert_eq!(query.len(), 100);
+
+        let read: Vec<_> = query.take(3).collect();
+        assert_eq!(
+            read,
+            [
+                (Entity(0), Health(0)),
+                (Entity(1), Health(1)),
+                (Entity(2), Health(2))
+            ]
+        );
+        assert_eq!(serialized.get(), 3);
+    }
+
+    #[test]
+    fn lazy_queries_read_in_batches() {
+        let serialized = Rc::new(Cell::new(0));
+        let mut iters = vec![Rows::new(100, &serialized)].into_iter();
+        let mut query = LazyQuery::<&Health>::from_iters(&mut iters).with_batch_size(10);
+
+        assert_eq!(query.next(), Some((Entity(0), Health(0))));
+        assert_eq!(serialized.get(), 10);
+        assert_eq!(query.len(), 99);
+        assert_eq!(query.count(), 99);
+        assert_eq!(serialized.get(), 100);
+    }
+
+    #[test]
+    fn queries_read_every_row_of_their_iterator() {
+        let serialized = Rc::new(Cell::new(0));
+        let mut iters = vec![Rows::new(5, &serialized), Rows::new(2, &serialized)].into_iter();
+        let query = Query::<&Health>::from_iters(&mut iters);
+        let lazy = LazyQuery::<&Health>::from_iters(&mut iters);
+
+        assert_eq!(query.len(), 5);
+        assert_eq!(query.get(Entity(4)), Some(&Health(4)));
+        assert_eq!(serialized.get(), 5);
+        assert_eq!(lazy.len(), 2);
+    }
+}
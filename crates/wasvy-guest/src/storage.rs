@@ -0,0 +1,46 @@
+//! Data that the mod keeps between sessions, saved by the host in a file per mod.
+//!
+//! Values are stored as JSON, so anything that implements `Serialize` can be saved:
+//!
+//! ```rust,ignore
+//! #[derive(Serialize, Deserialize, Default)]
+//! struct Progress {
+//!     level: u32,
+//! }
+//!
+//! let mut progress: Progress = storage::load("progress").unwrap_or_default();
+//! progress.level += 1;
+//! storage::save("progress", &progress).unwrap();
+//! ```
+//!
+//! The host limits how much each mod can store, see [`StorageError::QuotaExceeded`].
+
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::bindings::wasvy::ecs::storage;
+
+pub use crate::bindings::wasvy::ecs::storage::StorageError;
+
+/// The value saved under `key`.
+///
+/// Returns `None` if nothing is saved under `key`, or the value isn't a `T`.
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    serde_json::from_str(&storage::get(key)?).ok()
+}
+
+/// Save `value` under `key`, replacing the previous value. The host writes it to disk right away.
+pub fn save<T: Serialize>(key: &str, value: &T) -> Result<(), StorageError> {
+    let value = serde_json::to_string(value)
+        .unwrap_or_else(|err| panic!("could not serialize the value of {key}: {err}"));
+    storage::set(key, &value)
+}
+
+/// Remove the value saved under `key`, if there is one.
+pub fn remove(key: &str) -> Result<(), StorageError> {
+    storage::remove(key)
+}
+
+/// The keys that have a value saved.
+pub fn keys() -> Vec<String> {
+    storage::keys()
+}
@@ -0,0 +1,30 @@
+//! The time of the frame and random numbers, which are the same on every machine when the host
+//! runs mods in deterministic mode.
+//!
+//! Use them instead of the clocks and random numbers of the standard library, which the host fixes
+//! in deterministic mode.
+
+use crate::bindings::wasvy::ecs::functions;
+
+pub use crate::bindings::wasvy::ecs::types::FrameTime;
+
+/// The time of the current frame, like Bevy's `Time`.
+///
+/// In deterministic mode it advances by a fixed step every frame.
+pub fn time() -> FrameTime {
+    functions::time()
+}
+
+/// A random number from the mod's own generator on the host.
+///
+/// In deterministic mode the generator is seeded by the host, so every machine gets the same
+/// numbers as long as the mod asks for them in the same order.
+pub fn random() -> u64 {
+    functions::random()
+}
+
+/// A random number in `0.0..1.0`, see [`random`].
+pub fn random_f64() -> f64 {
+    // The 53 high bits fill the mantissa of a f64
+    (random() >> 11) as f64 / (1u64 << 53) as f64
+}
@@ -10,7 +10,7 @@ fn main() {
         .add_plugins(DefaultPlugins)
         .add_plugins((
             // Next, add the [`ModloaderPlugin`] ;)
-            ModloaderPlugin,
+            ModloaderPlugin::default(),
             // Plus some helpers for the example
             EguiPlugin,
             WorldInspectorPlugin::new(),
@@ -21,7 +21,7 @@ fn main() {
 
 /// Access the modloader's api through the Mods interface
 fn startup(mut mods: Mods) {
-    // Load one (or several) mods at once from the asset directory!
-    mods.load("mods/simple.wasm");
-    mods.load("mods/python.wasm");
+    // Load every mod in the asset directory's mods folder!
+    // New mods dropped into the folder are loaded while the game runs.
+    mods.load_folder("mods");
 }
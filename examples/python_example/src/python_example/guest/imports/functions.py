@@ -13,6 +13,22 @@ def register_system(name: str, queries: List[types.Query]) -> None:
     raise NotImplementedError
 
 def register_component(path: str) -> int:
+    """
+    Register a component defined by the mod. Registering the same path again returns the same id.
+    
+    The component is only known by this mod, other mods can have a component with the same path.
+    
+    Raises: `guest.types.Err(guest.imports.types.RegisterError)`
+    """
+    raise NotImplementedError
+
+def export_component(path: str) -> int:
+    """
+    Register a component defined by the mod that every other mod can use with its path, without
+    registering it.
+    
+    Raises: `guest.types.Err(guest.imports.types.RegisterError)`
+    """
     raise NotImplementedError
 
 def get_component_id(path: str) -> Optional[int]:
@@ -21,7 +37,103 @@ def get_component_id(path: str) -> Optional[int]:
 def spawn(components: List[types.Component]) -> int:
     raise NotImplementedError
 
-def this_function_does_nothing(entry: types.QueryResultEntry, query_result: List[types.QueryResultEntry]) -> None:
+def spawn_child(parent: int, components: List[types.Component]) -> int:
+    """
+    Spawn an entity as a child of `parent`, like spawning it with Bevy's `ChildOf`.
+    """
+    raise NotImplementedError
+
+def set_parent(entity: int, parent: int) -> None:
+    """
+    Make `entity` a child of `parent`, moving it out of the children of its current parent.
+    
+    Nothing happens if the host's permissions don't let the mod change the parents of these
+    entities.
+    """
+    raise NotImplementedError
+
+def remove_parent(entity: int) -> None:
+    """
+    Remove the parent of `entity`, making it a root entity.
+    """
+    raise NotImplementedError
+
+def despawn_recursive(entity: int) -> None:
+    """
+    Despawn `entity` along with all its descendants.
+    
+    Nothing happens if the host's permissions don't let the mod despawn one of them.
+    """
+    raise NotImplementedError
+
+def get(entity: int, components: List[str]) -> Optional[List[types.Component]]:
+    """
+    Look up the components of a single entity, in the order of `components`.
+    
+    Inside a system, only the components that one of the system's queries can read are accessible,
+    and the entity must match that query. Returns `none` if the entity doesn't exist, doesn't have
+    one of the components, or the system isn't allowed to read them. In `setup` every component can be read,
+    except the ones the host doesn't allow the mod to read.
+    """
+    raise NotImplementedError
+
+def get_many(entities: List[int], components: List[str]) -> List[Optional[List[types.Component]]]:
+    """
+    `get` for many entities at once, with a result for each entity.
+    """
+    raise NotImplementedError
+
+def set(entity: int, components: List[types.Component]) -> bool:
+    """
+    Replace the components of a single entity, following the same rules as `get`: the entity must
+    already have all of them. Like the `insert` command, the change is applied after the system returns.
+    
+    Returns false (and changes nothing) if `get` wouldn't return the components, the host doesn't
+    allow the mod to change one of them, or one of the values doesn't match its type.
+    """
+    raise NotImplementedError
+
+def set_many(entities: List[types.InsertCommand]) -> List[bool]:
+    """
+    `set` for many entities at once, with a result for each entity.
+    """
+    raise NotImplementedError
+
+def load_asset(path: str) -> Optional[int]:
+    """
+    Start loading the asset at `path` with Bevy's `AssetServer`.
+    
+    Paths are relative to the mod's own asset directory by default, the directory next to the mod
+    with the name of its file (e.g. `mods/my_mod/` for `mods/my_mod.wasm`). Returns `none` if the
+    path leaves that directory. Loading the same path again returns the same handle.
+    """
+    raise NotImplementedError
+
+def asset_load_state(asset: int) -> types.LoadState:
+    """
+    Where an asset loaded with `load-asset` is in its loading process.
+    """
+    raise NotImplementedError
+
+def time() -> types.FrameTime:
+    """
+    The time of the current frame.
+    
+    Mods should use it instead of the clocks of WASI, which are fixed in deterministic mode. In
+    that mode the time advances by a fixed step every frame, so it's the same on every machine.
+    """
+    raise NotImplementedError
+
+def random() -> int:
+    """
+    A random number from the mod's own generator.
+    
+    In deterministic mode the generator is seeded by the host, so every machine gets the same
+    numbers as long as the mod calls it in the same order.
+    """
+    raise NotImplementedError
+
+def this_function_does_nothing(entry: types.QueryResultEntry, query_result: List[types.QueryResultEntry], command: types.Command) -> None:
     """
     For some reason if the type isn't being used by a function, cargo component doesn't generate a binding for it.
     so this function is only to accumulate types so they are generated.
@@ -8,6 +8,41 @@ import weakref
 from ..types import Result, Ok, Err, Some
 
 
+@dataclass
+class RegisterError_HostType:
+    pass
+
+
+@dataclass
+class RegisterError_Exported:
+    value: str
+
+
+RegisterError = Union[RegisterError_HostType, RegisterError_Exported]
+"""
+Why a mod couldn't register a component.
+"""
+
+
+@dataclass
+class FrameTime:
+    """
+    The time of a frame, like Bevy's `Time`.
+    """
+    delta: float
+    elapsed: float
+    frame: int
+
+
+class LoadState(Enum):
+    """
+    Where an asset is in its loading process, like Bevy's `LoadState`.
+    """
+    NOT_LOADED = 0
+    LOADING = 1
+    LOADED = 2
+    FAILED = 3
+
 @dataclass
 class Component:
     """
@@ -35,10 +70,125 @@ class Query:
     
     The index for each component-id you put in `components` matters because that will be the order
     of retrival from the param argument in your system.
+    
+    # Optional components
+    
+    `optional` components work like Bevy's `Option<&T>`: they don't restrict which entities match,
+    and are sent after the `components` of each row, as `none` when the entity doesn't have them.
+    `has` works like Bevy's `Has<T>`: every row gets a bool per `has` component, telling if the entity has it.
+    
+    # Hierarchy
+    
+    When `hierarchy` is true, each row also has the `parent` and `children` of its entity, like Bevy's
+    `ChildOf` and `Children` relationship components.
+    
+    # Change detection
+    
+    `added` and `changed` work like Bevy's `Added<T>` and `Changed<T>` filters, relative to the
+    last time the system ran.
+    
+    When `removed` isn't empty the query works like Bevy's `RemovedComponents<T>` instead: its
+    results are the entities that had one of the `removed` components taken away (or were despawned)
+    since the last time the system ran, without any components. Leave the other lists empty in that case.
     """
     components: List[str]
+    optional: List[str]
+    has: List[str]
     with_: List[str]
     without: List[str]
+    added: List[str]
+    changed: List[str]
+    removed: List[str]
+    hierarchy: bool
+
+@dataclass
+class InsertCommand:
+    """
+    The components to add to an existing entity with the `insert` command.
+    """
+    entity: int
+    components: List[Component]
+
+@dataclass
+class SpawnChildCommand:
+    """
+    The parent and components of the entity to spawn with the `spawn-child` command.
+    """
+    parent: int
+    components: List[Component]
+
+@dataclass
+class SetParentCommand:
+    """
+    The entity to move under a new parent with the `set-parent` command.
+    """
+    entity: int
+    parent: int
+
+@dataclass
+class RemoveCommand:
+    """
+    The type paths of the components to take away from an entity with the `remove` command.
+    """
+    entity: int
+    components: List[str]
+
+
+@dataclass
+class Command_Spawn:
+    value: List[Component]
+
+
+@dataclass
+class Command_Insert:
+    value: InsertCommand
+
+
+@dataclass
+class Command_Remove:
+    value: RemoveCommand
+
+
+@dataclass
+class Command_Despawn:
+    value: int
+
+
+@dataclass
+class Command_InsertResource:
+    value: Component
+
+
+@dataclass
+class Command_RemoveResource:
+    value: str
+
+
+@dataclass
+class Command_SpawnChild:
+    value: SpawnChildCommand
+
+
+@dataclass
+class Command_SetParent:
+    value: SetParentCommand
+
+
+@dataclass
+class Command_RemoveParent:
+    value: int
+
+
+Command = Union[Command_Spawn, Command_Insert, Command_Remove, Command_Despawn, Command_InsertResource, Command_RemoveResource, Command_SpawnChild, Command_SetParent, Command_RemoveParent]
+"""
+A change to the world that a system wants to make.
+
+Systems can return a `list<command>`, and the host applies all of them in order, in one batch,
+after the system returns. Like Bevy's `Commands`, their effects are only visible to the systems that run afterwards.
+
+For example: `export my-system: func(params: list<query-result>) -> list<command>;`
+"""
+
 
 @dataclass
 class QueryResultEntry:
@@ -50,8 +200,47 @@ class QueryResultEntry:
     fn system(first_query: Query<(&Name, &Transform)>) {}
     
     query-result-entry is equal to one entry in `first_query`
+    
+    `components` has a slot for each of the query's `components` (always `some`), followed by a slot
+    for each of its `optional` components. `has` has a bool for each of the query's `has` components.
+    
+    `parent` and `children` are only filled in for queries with `hierarchy` set.
     """
-    components: List[Component]
+    components: List[Optional[Component]]
     entity: int
+    has: List[bool]
+    parent: Optional[int]
+    children: List[int]
+
+class QueryIter:
+    """
+    The rows of a query, which the host serializes as the guest reads them.
+    
+    Systems that take `list<query-result>` receive all the rows of their queries up front, with every
+    component serialized. Systems can take a `list<query-iter>` instead, with an iterator per query,
+    so only the rows that the guest reads are serialized:
+    
+    `export my-system: func(params: list<query-iter>);`
+    """
+    
+    def next_batch(self, n: int) -> List[QueryResultEntry]:
+        """
+        The next rows of the query, at most `n` of them. Returns an empty list once all the rows were read.
+        """
+        raise NotImplementedError
+    def remaining(self) -> int:
+        """
+        The number of rows that weren't read yet.
+        """
+        raise NotImplementedError
+    def __enter__(self) -> Self:
+        """Returns self"""
+        return self
+                                
+    def __exit__(self, exc_type: type[BaseException] | None, exc_value: BaseException | None, traceback: TracebackType | None) -> bool | None:
+        """
+        Release this resource.
+        """
+        raise NotImplementedError
 
 
@@ -1,210 +1,364 @@
-// Generated by `wit-bindgen` 0.41.0. DO NOT EDIT!
+// Generated by `wit-bindgen` 0.44.0. DO NOT EDIT!
 // Options used:
 //   * runtime_path: "wit_bindgen_rt"
 pub type QueryResult = wasvy::ecs::types::QueryResult;
 #[doc(hidden)]
-#[allow(non_snake_case)]
+#[allow(non_snake_case, unused_unsafe)]
 pub unsafe fn _export_hello_world_cabi<T: Guest>() -> *mut u8 {
-    #[cfg(target_arch = "wasm32")] _rt::run_ctors_once();
-    let result0 = T::hello_world();
-    let ptr1 = (&raw mut _RET_AREA.0).cast::<u8>();
-    let vec2 = (result0.into_bytes()).into_boxed_slice();
-    let ptr2 = vec2.as_ptr().cast::<u8>();
-    let len2 = vec2.len();
-    ::core::mem::forget(vec2);
-    *ptr1.add(::core::mem::size_of::<*const u8>()).cast::<usize>() = len2;
-    *ptr1.add(0).cast::<*mut u8>() = ptr2.cast_mut();
-    ptr1
+    unsafe {
+        #[cfg(target_arch = "wasm32")] _rt::run_ctors_once();
+        let result0 = { T::hello_world() };
+        let ptr1 = (&raw mut _RET_AREA.0).cast::<u8>();
+        let vec2 = (result0.into_bytes()).into_boxed_slice();
+        let ptr2 = vec2.as_ptr().cast::<u8>();
+        let len2 = vec2.len();
+        ::core::mem::forget(vec2);
+        *ptr1.add(::core::mem::size_of::<*const u8>()).cast::<usize>() = len2;
+        *ptr1.add(0).cast::<*mut u8>() = ptr2.cast_mut();
+        ptr1
+    }
 }
 #[doc(hidden)]
 #[allow(non_snake_case)]
 pub unsafe fn __post_return_hello_world<T: Guest>(arg0: *mut u8) {
-    let l0 = *arg0.add(0).cast::<*mut u8>();
-    let l1 = *arg0.add(::core::mem::size_of::<*const u8>()).cast::<usize>();
-    _rt::cabi_dealloc(l0, l1, 1);
+    unsafe {
+        let l0 = *arg0.add(0).cast::<*mut u8>();
+        let l1 = *arg0.add(::core::mem::size_of::<*const u8>()).cast::<usize>();
+        _rt::cabi_dealloc(l0, l1, 1);
+    }
 }
 #[doc(hidden)]
-#[allow(non_snake_case)]
+#[allow(non_snake_case, unused_unsafe)]
 pub unsafe fn _export_print_first_component_system_cabi<T: Guest>(
     arg0: *mut u8,
     arg1: usize,
 ) {
-    #[cfg(target_arch = "wasm32")] _rt::run_ctors_once();
-    let base13 = arg0;
-    let len13 = arg1;
-    let mut result13 = _rt::Vec::with_capacity(len13);
-    for i in 0..len13 {
-        let base = base13.add(i * (2 * ::core::mem::size_of::<*const u8>()));
-        let e13 = {
-            let l0 = *base.add(0).cast::<*mut u8>();
-            let l1 = *base.add(::core::mem::size_of::<*const u8>()).cast::<usize>();
-            let base12 = l0;
-            let len12 = l1;
-            let mut result12 = _rt::Vec::with_capacity(len12);
-            for i in 0..len12 {
-                let base = base12.add(i * (8 + 2 * ::core::mem::size_of::<*const u8>()));
-                let e12 = {
-                    let l2 = *base.add(0).cast::<*mut u8>();
-                    let l3 = *base
+    unsafe {
+        #[cfg(target_arch = "wasm32")] _rt::run_ctors_once();
+        {
+            let base23 = arg0;
+            let len23 = arg1;
+            let mut result23 = _rt::Vec::with_capacity(len23);
+            for i in 0..len23 {
+                let base = base23.add(i * (2 * ::core::mem::size_of::<*const u8>()));
+                let e23 = {
+                    let l0 = *base.add(0).cast::<*mut u8>();
+                    let l1 = *base
                         .add(::core::mem::size_of::<*const u8>())
                         .cast::<usize>();
-                    let base10 = l2;
-                    let len10 = l3;
-                    let mut result10 = _rt::Vec::with_capacity(len10);
-                    for i in 0..len10 {
-                        let base = base10
-                            .add(i * (4 * ::core::mem::size_of::<*const u8>()));
-                        let e10 = {
-                            let l4 = *base.add(0).cast::<*mut u8>();
-                            let l5 = *base
+                    let base22 = l0;
+                    let len22 = l1;
+                    let mut result22 = _rt::Vec::with_capacity(len22);
+                    for i in 0..len22 {
+                        let base = base22
+                            .add(i * (24 + 6 * ::core::mem::size_of::<*const u8>()));
+                        let e22 = {
+                            let l2 = *base.add(0).cast::<*mut u8>();
+                            let l3 = *base
                                 .add(::core::mem::size_of::<*const u8>())
                                 .cast::<usize>();
-                            let len6 = l5;
-                            let bytes6 = _rt::Vec::from_raw_parts(l4.cast(), len6, len6);
-                            let l7 = *base
+                            let base11 = l2;
+                            let len11 = l3;
+                            let mut result11 = _rt::Vec::with_capacity(len11);
+                            for i in 0..len11 {
+                                let base = base11
+                                    .add(i * (5 * ::core::mem::size_of::<*const u8>()));
+                                let e11 = {
+                                    let l4 = i32::from(*base.add(0).cast::<u8>());
+                                    match l4 {
+                                        0 => None,
+                                        1 => {
+                                            let e = {
+                                                let l5 = *base
+                                                    .add(::core::mem::size_of::<*const u8>())
+                                                    .cast::<*mut u8>();
+                                                let l6 = *base
+                                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                                    .cast::<usize>();
+                                                let len7 = l6;
+                                                let bytes7 = _rt::Vec::from_raw_parts(
+                                                    l5.cast(),
+                                                    len7,
+                                                    len7,
+                                                );
+                                                let l8 = *base
+                                                    .add(3 * ::core::mem::size_of::<*const u8>())
+                                                    .cast::<*mut u8>();
+                                                let l9 = *base
+                                                    .add(4 * ::core::mem::size_of::<*const u8>())
+                                                    .cast::<usize>();
+                                                let len10 = l9;
+                                                let bytes10 = _rt::Vec::from_raw_parts(
+                                                    l8.cast(),
+                                                    len10,
+                                                    len10,
+                                                );
+                                                wasvy::ecs::types::Component {
+                                                    path: _rt::string_lift(bytes7),
+                                                    value: _rt::string_lift(bytes10),
+                                                }
+                                            };
+                                            Some(e)
+                                        }
+                                        _ => _rt::invalid_enum_discriminant(),
+                                    }
+                                };
+                                result11.push(e11);
+                            }
+                            _rt::cabi_dealloc(
+                                base11,
+                                len11 * (5 * ::core::mem::size_of::<*const u8>()),
+                                ::core::mem::size_of::<*const u8>(),
+                            );
+                            let l12 = *base
                                 .add(2 * ::core::mem::size_of::<*const u8>())
+                                .cast::<i64>();
+                            let l13 = *base
+                                .add(8 + 2 * ::core::mem::size_of::<*const u8>())
                                 .cast::<*mut u8>();
-                            let l8 = *base
-                                .add(3 * ::core::mem::size_of::<*const u8>())
+                            let l14 = *base
+                                .add(8 + 3 * ::core::mem::size_of::<*const u8>())
+                                .cast::<usize>();
+                            let base16 = l13;
+                            let len16 = l14;
+                            let mut result16 = _rt::Vec::with_capacity(len16);
+                            for i in 0..len16 {
+                                let base = base16.add(i * 1);
+                                let e16 = {
+                                    let l15 = i32::from(*base.add(0).cast::<u8>());
+                                    _rt::bool_lift(l15 as u8)
+                                };
+                                result16.push(e16);
+                            }
+                            _rt::cabi_dealloc(base16, len16 * 1, 1);
+                            let l17 = i32::from(
+                                *base
+                                    .add(8 + 4 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<u8>(),
+                            );
+                            let l19 = *base
+                                .add(24 + 4 * ::core::mem::size_of::<*const u8>())
+                                .cast::<*mut u8>();
+                            let l20 = *base
+                                .add(24 + 5 * ::core::mem::size_of::<*const u8>())
                                 .cast::<usize>();
-                            let len9 = l8;
-                            let bytes9 = _rt::Vec::from_raw_parts(l7.cast(), len9, len9);
-                            wasvy::ecs::types::Component {
-                                path: _rt::string_lift(bytes6),
-                                value: _rt::string_lift(bytes9),
+                            let len21 = l20;
+                            wasvy::ecs::types::QueryResultEntry {
+                                components: result11,
+                                entity: l12 as u64,
+                                has: result16,
+                                parent: match l17 {
+                                    0 => None,
+                                    1 => {
+                                        let e = {
+                                            let l18 = *base
+                                                .add(16 + 4 * ::core::mem::size_of::<*const u8>())
+                                                .cast::<i64>();
+                                            l18 as u64
+                                        };
+                                        Some(e)
+                                    }
+                                    _ => _rt::invalid_enum_discriminant(),
+                                },
+                                children: _rt::Vec::from_raw_parts(l19.cast(), len21, len21),
                             }
                         };
-                        result10.push(e10);
+                        result22.push(e22);
                     }
                     _rt::cabi_dealloc(
-                        base10,
-                        len10 * (4 * ::core::mem::size_of::<*const u8>()),
-                        ::core::mem::size_of::<*const u8>(),
+                        base22,
+                        len22 * (24 + 6 * ::core::mem::size_of::<*const u8>()),
+                        8,
                     );
-                    let l11 = *base
-                        .add(2 * ::core::mem::size_of::<*const u8>())
-                        .cast::<i64>();
-                    wasvy::ecs::types::QueryResultEntry {
-                        components: result10,
-                        entity: l11 as u64,
-                    }
+                    result22
                 };
-                result12.push(e12);
+                result23.push(e23);
             }
             _rt::cabi_dealloc(
-                base12,
-                len12 * (8 + 2 * ::core::mem::size_of::<*const u8>()),
-                8,
+                base23,
+                len23 * (2 * ::core::mem::size_of::<*const u8>()),
+                ::core::mem::size_of::<*const u8>(),
             );
-            result12
+            T::print_first_component_system(result23)
         };
-        result13.push(e13);
-    }
-    _rt::cabi_dealloc(
-        base13,
-        len13 * (2 * ::core::mem::size_of::<*const u8>()),
-        ::core::mem::size_of::<*const u8>(),
-    );
-    T::print_first_component_system(result13);
+    }
 }
 #[doc(hidden)]
-#[allow(non_snake_case)]
+#[allow(non_snake_case, unused_unsafe)]
 pub unsafe fn _export_two_components_in_a_query_cabi<T: Guest>(
     arg0: *mut u8,
     arg1: usize,
 ) {
-    #[cfg(target_arch = "wasm32")] _rt::run_ctors_once();
-    let base13 = arg0;
-    let len13 = arg1;
-    let mut result13 = _rt::Vec::with_capacity(len13);
-    for i in 0..len13 {
-        let base = base13.add(i * (2 * ::core::mem::size_of::<*const u8>()));
-        let e13 = {
-            let l0 = *base.add(0).cast::<*mut u8>();
-            let l1 = *base.add(::core::mem::size_of::<*const u8>()).cast::<usize>();
-            let base12 = l0;
-            let len12 = l1;
-            let mut result12 = _rt::Vec::with_capacity(len12);
-            for i in 0..len12 {
-                let base = base12.add(i * (8 + 2 * ::core::mem::size_of::<*const u8>()));
-                let e12 = {
-                    let l2 = *base.add(0).cast::<*mut u8>();
-                    let l3 = *base
+    unsafe {
+        #[cfg(target_arch = "wasm32")] _rt::run_ctors_once();
+        {
+            let base23 = arg0;
+            let len23 = arg1;
+            let mut result23 = _rt::Vec::with_capacity(len23);
+            for i in 0..len23 {
+                let base = base23.add(i * (2 * ::core::mem::size_of::<*const u8>()));
+                let e23 = {
+                    let l0 = *base.add(0).cast::<*mut u8>();
+                    let l1 = *base
                         .add(::core::mem::size_of::<*const u8>())
                         .cast::<usize>();
-                    let base10 = l2;
-                    let len10 = l3;
-                    let mut result10 = _rt::Vec::with_capacity(len10);
-                    for i in 0..len10 {
-                        let base = base10
-                            .add(i * (4 * ::core::mem::size_of::<*const u8>()));
-                        let e10 = {
-                            let l4 = *base.add(0).cast::<*mut u8>();
-                            let l5 = *base
+                    let base22 = l0;
+                    let len22 = l1;
+                    let mut result22 = _rt::Vec::with_capacity(len22);
+                    for i in 0..len22 {
+                        let base = base22
+                            .add(i * (24 + 6 * ::core::mem::size_of::<*const u8>()));
+                        let e22 = {
+                            let l2 = *base.add(0).cast::<*mut u8>();
+                            let l3 = *base
                                 .add(::core::mem::size_of::<*const u8>())
                                 .cast::<usize>();
-                            let len6 = l5;
-                            let bytes6 = _rt::Vec::from_raw_parts(l4.cast(), len6, len6);
-                            let l7 = *base
+                            let base11 = l2;
+                            let len11 = l3;
+                            let mut result11 = _rt::Vec::with_capacity(len11);
+                            for i in 0..len11 {
+                                let base = base11
+                                    .add(i * (5 * ::core::mem::size_of::<*const u8>()));
+                                let e11 = {
+                                    let l4 = i32::from(*base.add(0).cast::<u8>());
+                                    match l4 {
+                                        0 => None,
+                                        1 => {
+                                            let e = {
+                                                let l5 = *base
+                                                    .add(::core::mem::size_of::<*const u8>())
+                                                    .cast::<*mut u8>();
+                                                let l6 = *base
+                                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                                    .cast::<usize>();
+                                                let len7 = l6;
+                                                let bytes7 = _rt::Vec::from_raw_parts(
+                                                    l5.cast(),
+                                                    len7,
+                                                    len7,
+                                                );
+                                                let l8 = *base
+                                                    .add(3 * ::core::mem::size_of::<*const u8>())
+                                                    .cast::<*mut u8>();
+                                                let l9 = *base
+                                                    .add(4 * ::core::mem::size_of::<*const u8>())
+                                                    .cast::<usize>();
+                                                let len10 = l9;
+                                                let bytes10 = _rt::Vec::from_raw_parts(
+                                                    l8.cast(),
+                                                    len10,
+                                                    len10,
+                                                );
+                                                wasvy::ecs::types::Component {
+                                                    path: _rt::string_lift(bytes7),
+                                                    value: _rt::string_lift(bytes10),
+                                                }
+                                            };
+                                            Some(e)
+                                        }
+                                        _ => _rt::invalid_enum_discriminant(),
+                                    }
+                                };
+                                result11.push(e11);
+                            }
+                            _rt::cabi_dealloc(
+                                base11,
+                                len11 * (5 * ::core::mem::size_of::<*const u8>()),
+                                ::core::mem::size_of::<*const u8>(),
+                            );
+                            let l12 = *base
                                 .add(2 * ::core::mem::size_of::<*const u8>())
+                                .cast::<i64>();
+                            let l13 = *base
+                                .add(8 + 2 * ::core::mem::size_of::<*const u8>())
                                 .cast::<*mut u8>();
-                            let l8 = *base
-                                .add(3 * ::core::mem::size_of::<*const u8>())
+                            let l14 = *base
+                                .add(8 + 3 * ::core::mem::size_of::<*const u8>())
+                                .cast::<usize>();
+                            let base16 = l13;
+                            let len16 = l14;
+                            let mut result16 = _rt::Vec::with_capacity(len16);
+                            for i in 0..len16 {
+                                let base = base16.add(i * 1);
+                                let e16 = {
+                                    let l15 = i32::from(*base.add(0).cast::<u8>());
+                                    _rt::bool_lift(l15 as u8)
+                                };
+                                result16.push(e16);
+                            }
+                            _rt::cabi_dealloc(base16, len16 * 1, 1);
+                            let l17 = i32::from(
+                                *base
+                                    .add(8 + 4 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<u8>(),
+                            );
+                            let l19 = *base
+                                .add(24 + 4 * ::core::mem::size_of::<*const u8>())
+                                .cast::<*mut u8>();
+                            let l20 = *base
+                                .add(24 + 5 * ::core::mem::size_of::<*const u8>())
                                 .cast::<usize>();
-                            let len9 = l8;
-                            let bytes9 = _rt::Vec::from_raw_parts(l7.cast(), len9, len9);
-                            wasvy::ecs::types::Component {
-                                path: _rt::string_lift(bytes6),
-                                value: _rt::string_lift(bytes9),
+                            let len21 = l20;
+                            wasvy::ecs::types::QueryResultEntry {
+                                components: result11,
+                                entity: l12 as u64,
+                                has: result16,
+                                parent: match l17 {
+                                    0 => None,
+                                    1 => {
+                                        let e = {
+                                            let l18 = *base
+                                                .add(16 + 4 * ::core::mem::size_of::<*const u8>())
+                                                .cast::<i64>();
+                                            l18 as u64
+                                        };
+                                        Some(e)
+                                    }
+                                    _ => _rt::invalid_enum_discriminant(),
+                                },
+                                children: _rt::Vec::from_raw_parts(l19.cast(), len21, len21),
                             }
                         };
-                        result10.push(e10);
+                        result22.push(e22);
                     }
                     _rt::cabi_dealloc(
-                        base10,
-                        len10 * (4 * ::core::mem::size_of::<*const u8>()),
-                        ::core::mem::size_of::<*const u8>(),
+                        base22,
+                        len22 * (24 + 6 * ::core::mem::size_of::<*const u8>()),
+                        8,
                     );
-                    let l11 = *base
-                        .add(2 * ::core::mem::size_of::<*const u8>())
-                        .cast::<i64>();
-                    wasvy::ecs::types::QueryResultEntry {
-                        components: result10,
-                        entity: l11 as u64,
-                    }
+                    result22
                 };
-                result12.push(e12);
+                result23.push(e23);
             }
             _rt::cabi_dealloc(
-                base12,
-                len12 * (8 + 2 * ::core::mem::size_of::<*const u8>()),
-                8,
+                base23,
+                len23 * (2 * ::core::mem::size_of::<*const u8>()),
+                ::core::mem::size_of::<*const u8>(),
             );
-            result12
+            T::two_components_in_a_query(result23)
         };
-        result13.push(e13);
-    }
-    _rt::cabi_dealloc(
-        base13,
-        len13 * (2 * ::core::mem::size_of::<*const u8>()),
-        ::core::mem::size_of::<*const u8>(),
-    );
-    T::two_components_in_a_query(result13);
+    }
 }
 #[doc(hidden)]
-#[allow(non_snake_case)]
+#[allow(non_snake_case, unused_unsafe)]
 pub unsafe fn _export_setup_cabi<T: Guest>() {
-    #[cfg(target_arch = "wasm32")] _rt::run_ctors_once();
-    T::setup();
+    unsafe {
+        #[cfg(target_arch = "wasm32")] _rt::run_ctors_once();
+        { T::setup() };
+    }
 }
 pub trait Guest {
     /// This exported function can't be called automatically from Wasvy
     /// because it doesn't comply to the desired signature.
+    #[allow(async_fn_in_trait)]
     fn hello_world() -> _rt::String;
     /// All systems must only have one argument of type `list<query-result>`
+    #[allow(async_fn_in_trait)]
     fn print_first_component_system(params: _rt::Vec<QueryResult>) -> ();
+    #[allow(async_fn_in_trait)]
     fn two_components_in_a_query(params: _rt::Vec<QueryResult>) -> ();
     /// This function is called once on startup for each WASM component (Not Bevy component).
+    #[allow(async_fn_in_trait)]
     fn setup() -> ();
 }
 #[doc(hidden)]
@@ -243,8 +397,112 @@ pub mod wasvy {
             #[doc(hidden)]
             static __FORCE_SECTION_REF: fn() = super::super::super::__link_custom_section_describing_imports;
             use super::super::super::_rt;
+            /// The id of a component, which only depends on the id of the mod that registered it and its type
+            /// path, so it's the same in every run and mods can keep it. The components of the host and the
+            /// exported components only depend on their type path, so every mod gets the same id for them.
             pub type ComponentId = u64;
+            /// Why a mod couldn't register a component.
+            #[derive(Clone)]
+            pub enum RegisterError {
+                /// The path is a type of the host, which mods use without registering it.
+                HostType,
+                /// Another mod exported a component with this path, this is the id of that mod.
+                Exported(_rt::String),
+            }
+            impl ::core::fmt::Debug for RegisterError {
+                fn fmt(
+                    &self,
+                    f: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    match self {
+                        RegisterError::HostType => {
+                            f.debug_tuple("RegisterError::HostType").finish()
+                        }
+                        RegisterError::Exported(e) => {
+                            f.debug_tuple("RegisterError::Exported").field(e).finish()
+                        }
+                    }
+                }
+            }
+            impl ::core::fmt::Display for RegisterError {
+                fn fmt(
+                    &self,
+                    f: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    write!(f, "{:?}", self)
+                }
+            }
+            impl std::error::Error for RegisterError {}
             pub type Entity = u64;
+            /// An asset loaded by the mod with `load-asset`.
+            ///
+            /// Use it in the value of a component wherever the Rust type has a `Handle<T>`, like the `image`
+            /// of a `Sprite` or the scene of a `SceneRoot`: `{"image": 3}`.
+            pub type AssetHandle = u64;
+            /// The time of a frame, like Bevy's `Time`.
+            #[repr(C)]
+            #[derive(Clone, Copy)]
+            pub struct FrameTime {
+                /// The seconds since the previous frame.
+                pub delta: f64,
+                /// The seconds since the app started.
+                pub elapsed: f64,
+                /// The number of frames since the app started, starting at 1 on the first frame.
+                pub frame: u64,
+            }
+            impl ::core::fmt::Debug for FrameTime {
+                fn fmt(
+                    &self,
+                    f: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    f.debug_struct("FrameTime")
+                        .field("delta", &self.delta)
+                        .field("elapsed", &self.elapsed)
+                        .field("frame", &self.frame)
+                        .finish()
+                }
+            }
+            /// Where an asset is in its loading process, like Bevy's `LoadState`.
+            #[repr(u8)]
+            #[derive(Clone, Copy, Eq, Ord, PartialEq, PartialOrd)]
+            pub enum LoadState {
+                NotLoaded,
+                Loading,
+                Loaded,
+                Failed,
+            }
+            impl ::core::fmt::Debug for LoadState {
+                fn fmt(
+                    &self,
+                    f: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    match self {
+                        LoadState::NotLoaded => {
+                            f.debug_tuple("LoadState::NotLoaded").finish()
+                        }
+                        LoadState::Loading => {
+                            f.debug_tuple("LoadState::Loading").finish()
+                        }
+                        LoadState::Loaded => f.debug_tuple("LoadState::Loaded").finish(),
+                        LoadState::Failed => f.debug_tuple("LoadState::Failed").finish(),
+                    }
+                }
+            }
+            impl LoadState {
+                #[doc(hidden)]
+                pub unsafe fn _lift(val: u8) -> LoadState {
+                    if !cfg!(debug_assertions) {
+                        return unsafe { ::core::mem::transmute(val) };
+                    }
+                    match val {
+                        0 => LoadState::NotLoaded,
+                        1 => LoadState::Loading,
+                        2 => LoadState::Loaded,
+                        3 => LoadState::Failed,
+                        _ => panic!("invalid enum discriminant"),
+                    }
+                }
+            }
             /// This is the translation object between bevy Rust `Component` and a bevy `Component` that is registerd in WASM.
             ///
             /// `value` is the JSON serialized version of the actual component that is being passed between WASM and Bevy.
@@ -278,11 +536,37 @@ pub mod wasvy {
             ///
             /// The index for each component-id you put in `components` matters because that will be the order
             /// of retrival from the param argument in your system.
+            ///
+            /// # Optional components
+            ///
+            /// `optional` components work like Bevy's `Option<&T>`: they don't restrict which entities match,
+            /// and are sent after the `components` of each row, as `none` when the entity doesn't have them.
+            /// `has` works like Bevy's `Has<T>`: every row gets a bool per `has` component, telling if the entity has it.
+            ///
+            /// # Hierarchy
+            ///
+            /// When `hierarchy` is true, each row also has the `parent` and `children` of its entity, like Bevy's
+            /// `ChildOf` and `Children` relationship components.
+            ///
+            /// # Change detection
+            ///
+            /// `added` and `changed` work like Bevy's `Added<T>` and `Changed<T>` filters, relative to the
+            /// last time the system ran.
+            ///
+            /// When `removed` isn't empty the query works like Bevy's `RemovedComponents<T>` instead: its
+            /// results are the entities that had one of the `removed` components taken away (or were despawned)
+            /// since the last time the system ran, without any components. Leave the other lists empty in that case.
             #[derive(Clone)]
             pub struct Query {
                 pub components: _rt::Vec<_rt::String>,
+                pub optional: _rt::Vec<_rt::String>,
+                pub has: _rt::Vec<_rt::String>,
                 pub with: _rt::Vec<_rt::String>,
                 pub without: _rt::Vec<_rt::String>,
+                pub added: _rt::Vec<_rt::String>,
+                pub changed: _rt::Vec<_rt::String>,
+                pub removed: _rt::Vec<_rt::String>,
+                pub hierarchy: bool,
             }
             impl ::core::fmt::Debug for Query {
                 fn fmt(
@@ -291,11 +575,192 @@ pub mod wasvy {
                 ) -> ::core::fmt::Result {
                     f.debug_struct("Query")
                         .field("components", &self.components)
+                        .field("optional", &self.optional)
+                        .field("has", &self.has)
                         .field("with", &self.with)
                         .field("without", &self.without)
+                        .field("added", &self.added)
+                        .field("changed", &self.changed)
+                        .field("removed", &self.removed)
+                        .field("hierarchy", &self.hierarchy)
+                        .finish()
+                }
+            }
+            /// The components to add to an existing entity with the `insert` command.
+            #[derive(Clone)]
+            pub struct InsertCommand {
+                pub entity: Entity,
+                pub components: _rt::Vec<Component>,
+            }
+            impl ::core::fmt::Debug for InsertCommand {
+                fn fmt(
+                    &self,
+                    f: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    f.debug_struct("InsertCommand")
+                        .field("entity", &self.entity)
+                        .field("components", &self.components)
+                        .finish()
+                }
+            }
+            /// The parent and components of the entity to spawn with the `spawn-child` command.
+            #[derive(Clone)]
+            pub struct SpawnChildCommand {
+                pub parent: Entity,
+                pub components: _rt::Vec<Component>,
+            }
+            impl ::core::fmt::Debug for SpawnChildCommand {
+                fn fmt(
+                    &self,
+                    f: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    f.debug_struct("SpawnChildCommand")
+                        .field("parent", &self.parent)
+                        .field("components", &self.components)
+                        .finish()
+                }
+            }
+            /// The entity to move under a new parent with the `set-parent` command.
+            #[repr(C)]
+            #[derive(Clone, Copy)]
+            pub struct SetParentCommand {
+                pub entity: Entity,
+                pub parent: Entity,
+            }
+            impl ::core::fmt::Debug for SetParentCommand {
+                fn fmt(
+                    &self,
+                    f: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    f.debug_struct("SetParentCommand")
+                        .field("entity", &self.entity)
+                        .field("parent", &self.parent)
+                        .finish()
+                }
+            }
+            /// The type paths of the components to take away from an entity with the `remove` command.
+            #[derive(Clone)]
+            pub struct RemoveCommand {
+                pub entity: Entity,
+                pub components: _rt::Vec<_rt::String>,
+            }
+            impl ::core::fmt::Debug for RemoveCommand {
+                fn fmt(
+                    &self,
+                    f: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    f.debug_struct("RemoveCommand")
+                        .field("entity", &self.entity)
+                        .field("components", &self.components)
                         .finish()
                 }
             }
+            /// A change to the world that a system wants to make.
+            ///
+            /// Systems can return a `list<command>`, and the host applies all of them in order, in one batch,
+            /// after the system returns. Like Bevy's `Commands`, their effects are only visible to the systems that run afterwards.
+            ///
+            /// For example: `export my-system: func(params: list<query-result>) -> list<command>;`
+            #[derive(Clone)]
+            pub enum Command {
+                Spawn(_rt::Vec<Component>),
+                Insert(InsertCommand),
+                Remove(RemoveCommand),
+                /// Despawn the entity, and like in Bevy, all its descendants.
+                Despawn(Entity),
+                /// Insert (or replace) a resource that is registered in the host's type registry.
+                InsertResource(Component),
+                /// Remove a resource by its type path.
+                RemoveResource(_rt::String),
+                /// Spawn an entity as a child of another one.
+                SpawnChild(SpawnChildCommand),
+                /// Make an entity the child of another one.
+                SetParent(SetParentCommand),
+                /// Remove the parent of an entity, making it a root entity.
+                RemoveParent(Entity),
+            }
+            impl ::core::fmt::Debug for Command {
+                fn fmt(
+                    &self,
+                    f: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    match self {
+                        Command::Spawn(e) => {
+                            f.debug_tuple("Command::Spawn").field(e).finish()
+                        }
+                        Command::Insert(e) => {
+                            f.debug_tuple("Command::Insert").field(e).finish()
+                        }
+                        Command::Remove(e) => {
+                            f.debug_tuple("Command::Remove").field(e).finish()
+                        }
+                        Command::Despawn(e) => {
+                            f.debug_tuple("Command::Despawn").field(e).finish()
+                        }
+                        Command::InsertResource(e) => {
+                            f.debug_tuple("Command::InsertResource").field(e).finish()
+                        }
+                        Command::RemoveResource(e) => {
+                            f.debug_tuple("Command::RemoveResource").field(e).finish()
+                        }
+                        Command::SpawnChild(e) => {
+                            f.debug_tuple("Command::SpawnChild").field(e).finish()
+                        }
+                        Command::SetParent(e) => {
+                            f.debug_tuple("Command::SetParent").field(e).finish()
+                        }
+                        Command::RemoveParent(e) => {
+                            f.debug_tuple("Command::RemoveParent").field(e).finish()
+                        }
+                    }
+                }
+            }
+            /// The rows of a query, which the host serializes as the guest reads them.
+            ///
+            /// Systems that take `list<query-result>` receive all the rows of their queries up front, with every
+            /// component serialized. Systems can take a `list<query-iter>` instead, with an iterator per query,
+            /// so only the rows that the guest reads are serialized:
+            ///
+            /// `export my-system: func(params: list<query-iter>);`
+            #[derive(Debug)]
+            #[repr(transparent)]
+            pub struct QueryIter {
+                handle: _rt::Resource<QueryIter>,
+            }
+            impl QueryIter {
+                #[doc(hidden)]
+                pub unsafe fn from_handle(handle: u32) -> Self {
+                    Self {
+                        handle: unsafe { _rt::Resource::from_handle(handle) },
+                    }
+                }
+                #[doc(hidden)]
+                pub fn take_handle(&self) -> u32 {
+                    _rt::Resource::take_handle(&self.handle)
+                }
+                #[doc(hidden)]
+                pub fn handle(&self) -> u32 {
+                    _rt::Resource::handle(&self.handle)
+                }
+            }
+            unsafe impl _rt::WasmResource for QueryIter {
+                #[inline]
+                unsafe fn drop(_handle: u32) {
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "wasvy:ecs/types")]
+                    unsafe extern "C" {
+                        #[link_name = "[resource-drop]query-iter"]
+                        fn drop(_: i32);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn drop(_: i32) {
+                        unreachable!()
+                    }
+                    unsafe {
+                        drop(_handle as i32);
+                    }
+                }
+            }
             /// This is one row for a query parameter
             ///
             /// For example if we take the following bevy system:
@@ -303,10 +768,18 @@ pub mod wasvy {
             /// fn system(first_query: Query<(&Name, &Transform)>) {}
             ///
             /// query-result-entry is equal to one entry in `first_query`
+            ///
+            /// `components` has a slot for each of the query's `components` (always `some`), followed by a slot
+            /// for each of its `optional` components. `has` has a bool for each of the query's `has` components.
+            ///
+            /// `parent` and `children` are only filled in for queries with `hierarchy` set.
             #[derive(Clone)]
             pub struct QueryResultEntry {
-                pub components: _rt::Vec<Component>,
+                pub components: _rt::Vec<Option<Component>>,
                 pub entity: Entity,
+                pub has: _rt::Vec<bool>,
+                pub parent: Option<Entity>,
+                pub children: _rt::Vec<Entity>,
             }
             impl ::core::fmt::Debug for QueryResultEntry {
                 fn fmt(
@@ -316,6 +789,9 @@ pub mod wasvy {
                     f.debug_struct("QueryResultEntry")
                         .field("components", &self.components)
                         .field("entity", &self.entity)
+                        .field("has", &self.has)
+                        .field("parent", &self.parent)
+                        .field("children", &self.children)
                         .finish()
                 }
             }
@@ -327,6 +803,189 @@ pub mod wasvy {
             ///
             /// query-result is equal to `first_query`
             pub type QueryResult = _rt::Vec<QueryResultEntry>;
+            impl QueryIter {
+                #[allow(unused_unsafe, clippy::all)]
+                /// The next rows of the query, at most `n` of them. Returns an empty list once all the rows were read.
+                #[allow(async_fn_in_trait)]
+                pub fn next_batch(&self, n: u32) -> _rt::Vec<QueryResultEntry> {
+                    unsafe {
+                        #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                        #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                        struct RetArea(
+                            [::core::mem::MaybeUninit<
+                                u8,
+                            >; 2 * ::core::mem::size_of::<*const u8>()],
+                        );
+                        let mut ret_area = RetArea(
+                            [::core::mem::MaybeUninit::uninit(); 2
+                                * ::core::mem::size_of::<*const u8>()],
+                        );
+                        let ptr0 = ret_area.0.as_mut_ptr().cast::<u8>();
+                        #[cfg(target_arch = "wasm32")]
+                        #[link(wasm_import_module = "wasvy:ecs/types")]
+                        unsafe extern "C" {
+                            #[link_name = "[method]query-iter.next-batch"]
+                            fn wit_import1(_: i32, _: i32, _: *mut u8);
+                        }
+                        #[cfg(not(target_arch = "wasm32"))]
+                        unsafe extern "C" fn wit_import1(_: i32, _: i32, _: *mut u8) {
+                            unreachable!()
+                        }
+                        wit_import1((self).handle() as i32, _rt::as_i32(&n), ptr0);
+                        let l2 = *ptr0.add(0).cast::<*mut u8>();
+                        let l3 = *ptr0
+                            .add(::core::mem::size_of::<*const u8>())
+                            .cast::<usize>();
+                        let base24 = l2;
+                        let len24 = l3;
+                        let mut result24 = _rt::Vec::with_capacity(len24);
+                        for i in 0..len24 {
+                            let base = base24
+                                .add(i * (24 + 6 * ::core::mem::size_of::<*const u8>()));
+                            let e24 = {
+                                let l4 = *base.add(0).cast::<*mut u8>();
+                                let l5 = *base
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let base13 = l4;
+                                let len13 = l5;
+                                let mut result13 = _rt::Vec::with_capacity(len13);
+                                for i in 0..len13 {
+                                    let base = base13
+                                        .add(i * (5 * ::core::mem::size_of::<*const u8>()));
+                                    let e13 = {
+                                        let l6 = i32::from(*base.add(0).cast::<u8>());
+                                        match l6 {
+                                            0 => None,
+                                            1 => {
+                                                let e = {
+                                                    let l7 = *base
+                                                        .add(::core::mem::size_of::<*const u8>())
+                                                        .cast::<*mut u8>();
+                                                    let l8 = *base
+                                                        .add(2 * ::core::mem::size_of::<*const u8>())
+                                                        .cast::<usize>();
+                                                    let len9 = l8;
+                                                    let bytes9 = _rt::Vec::from_raw_parts(
+                                                        l7.cast(),
+                                                        len9,
+                                                        len9,
+                                                    );
+                                                    let l10 = *base
+                                                        .add(3 * ::core::mem::size_of::<*const u8>())
+                                                        .cast::<*mut u8>();
+                                                    let l11 = *base
+                                                        .add(4 * ::core::mem::size_of::<*const u8>())
+                                                        .cast::<usize>();
+                                                    let len12 = l11;
+                                                    let bytes12 = _rt::Vec::from_raw_parts(
+                                                        l10.cast(),
+                                                        len12,
+                                                        len12,
+                                                    );
+                                                    Component {
+                                                        path: _rt::string_lift(bytes9),
+                                                        value: _rt::string_lift(bytes12),
+                                                    }
+                                                };
+                                                Some(e)
+                                            }
+                                            _ => _rt::invalid_enum_discriminant(),
+                                        }
+                                    };
+                                    result13.push(e13);
+                                }
+                                _rt::cabi_dealloc(
+                                    base13,
+                                    len13 * (5 * ::core::mem::size_of::<*const u8>()),
+                                    ::core::mem::size_of::<*const u8>(),
+                                );
+                                let l14 = *base
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<i64>();
+                                let l15 = *base
+                                    .add(8 + 2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l16 = *base
+                                    .add(8 + 3 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let base18 = l15;
+                                let len18 = l16;
+                                let mut result18 = _rt::Vec::with_capacity(len18);
+                                for i in 0..len18 {
+                                    let base = base18.add(i * 1);
+                                    let e18 = {
+                                        let l17 = i32::from(*base.add(0).cast::<u8>());
+                                        _rt::bool_lift(l17 as u8)
+                                    };
+                                    result18.push(e18);
+                                }
+                                _rt::cabi_dealloc(base18, len18 * 1, 1);
+                                let l19 = i32::from(
+                                    *base
+                                        .add(8 + 4 * ::core::mem::size_of::<*const u8>())
+                                        .cast::<u8>(),
+                                );
+                                let l21 = *base
+                                    .add(24 + 4 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l22 = *base
+                                    .add(24 + 5 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len23 = l22;
+                                QueryResultEntry {
+                                    components: result13,
+                                    entity: l14 as u64,
+                                    has: result18,
+                                    parent: match l19 {
+                                        0 => None,
+                                        1 => {
+                                            let e = {
+                                                let l20 = *base
+                                                    .add(16 + 4 * ::core::mem::size_of::<*const u8>())
+                                                    .cast::<i64>();
+                                                l20 as u64
+                                            };
+                                            Some(e)
+                                        }
+                                        _ => _rt::invalid_enum_discriminant(),
+                                    },
+                                    children: _rt::Vec::from_raw_parts(l21.cast(), len23, len23),
+                                }
+                            };
+                            result24.push(e24);
+                        }
+                        _rt::cabi_dealloc(
+                            base24,
+                            len24 * (24 + 6 * ::core::mem::size_of::<*const u8>()),
+                            8,
+                        );
+                        let result25 = result24;
+                        result25
+                    }
+                }
+            }
+            impl QueryIter {
+                #[allow(unused_unsafe, clippy::all)]
+                /// The number of rows that weren't read yet.
+                #[allow(async_fn_in_trait)]
+                pub fn remaining(&self) -> u32 {
+                    unsafe {
+                        #[cfg(target_arch = "wasm32")]
+                        #[link(wasm_import_module = "wasvy:ecs/types")]
+                        unsafe extern "C" {
+                            #[link_name = "[method]query-iter.remaining"]
+                            fn wit_import0(_: i32) -> i32;
+                        }
+                        #[cfg(not(target_arch = "wasm32"))]
+                        unsafe extern "C" fn wit_import0(_: i32) -> i32 {
+                            unreachable!()
+                        }
+                        let ret = wit_import0((self).handle() as i32);
+                        ret as u32
+                    }
+                }
+            }
         }
         #[allow(dead_code, async_fn_in_trait, unused_imports, clippy::all)]
         pub mod functions {
@@ -337,55 +996,57 @@ pub mod wasvy {
             pub type Entity = super::super::super::wasvy::ecs::types::Entity;
             pub type Query = super::super::super::wasvy::ecs::types::Query;
             pub type ComponentId = super::super::super::wasvy::ecs::types::ComponentId;
+            pub type RegisterError = super::super::super::wasvy::ecs::types::RegisterError;
             pub type Component = super::super::super::wasvy::ecs::types::Component;
             pub type QueryResult = super::super::super::wasvy::ecs::types::QueryResult;
             pub type QueryResultEntry = super::super::super::wasvy::ecs::types::QueryResultEntry;
+            pub type Command = super::super::super::wasvy::ecs::types::Command;
+            pub type InsertCommand = super::super::super::wasvy::ecs::types::InsertCommand;
+            pub type AssetHandle = super::super::super::wasvy::ecs::types::AssetHandle;
+            pub type LoadState = super::super::super::wasvy::ecs::types::LoadState;
+            pub type FrameTime = super::super::super::wasvy::ecs::types::FrameTime;
             #[allow(unused_unsafe, clippy::all)]
+            #[allow(async_fn_in_trait)]
             pub fn register_system(name: &str, queries: &[Query]) -> () {
                 unsafe {
                     let mut cleanup_list = _rt::Vec::new();
                     let vec0 = name;
                     let ptr0 = vec0.as_ptr().cast::<u8>();
                     let len0 = vec0.len();
-                    let vec8 = queries;
-                    let len8 = vec8.len();
-                    let layout8 = _rt::alloc::Layout::from_size_align_unchecked(
-                        vec8.len() * (6 * ::core::mem::size_of::<*const u8>()),
-                        ::core::mem::size_of::<*const u8>(),
-                    );
-                    let result8 = if layout8.size() != 0 {
-                        let ptr = _rt::alloc::alloc(layout8).cast::<u8>();
-                        if ptr.is_null() {
-                            _rt::alloc::handle_alloc_error(layout8);
-                        }
-                        ptr
-                    } else {
-                        ::core::ptr::null_mut()
-                    };
-                    for (i, e) in vec8.into_iter().enumerate() {
-                        let base = result8
-                            .add(i * (6 * ::core::mem::size_of::<*const u8>()));
+                    let vec18 = queries;
+                    let len18 = vec18.len();
+                    let layout18 = _rt::alloc::Layout::from_size_align(
+                            vec18.len() * (17 * ::core::mem::size_of::<*const u8>()),
+                            ::core::mem::size_of::<*const u8>(),
+                        )
+                        .unwrap();
+                    let (result18, _cleanup18) = wit_bindgen_rt::Cleanup::new(layout18);
+                    for (i, e) in vec18.into_iter().enumerate() {
+                        let base = result18
+                            .add(i * (17 * ::core::mem::size_of::<*const u8>()));
                         {
                             let super::super::super::wasvy::ecs::types::Query {
                                 components: components1,
+                                optional: optional1,
+                                has: has1,
                                 with: with1,
                                 without: without1,
+                                added: added1,
+                                changed: changed1,
+                                removed: removed1,
+                                hierarchy: hierarchy1,
                             } = e;
                             let vec3 = components1;
                             let len3 = vec3.len();
-                            let layout3 = _rt::alloc::Layout::from_size_align_unchecked(
-                                vec3.len() * (2 * ::core::mem::size_of::<*const u8>()),
-                                ::core::mem::size_of::<*const u8>(),
+                            let layout3 = _rt::alloc::Layout::from_size_align(
+                                    vec3.len() * (2 * ::core::mem::size_of::<*const u8>()),
+                                    ::core::mem::size_of::<*const u8>(),
+                                )
+                                .unwrap();
+                            let (result3, _cleanup3) = wit_bindgen_rt::Cleanup::new(
+                                layout3,
                             );
-                            let result3 = if layout3.size() != 0 {
-                                let ptr = _rt::alloc::alloc(layout3).cast::<u8>();
-                                if ptr.is_null() {
-                                    _rt::alloc::handle_alloc_error(layout3);
-                                }
-                                ptr
-                            } else {
-                                ::core::ptr::null_mut()
-                            };
+                            cleanup_list.extend(_cleanup3);
                             for (i, e) in vec3.into_iter().enumerate() {
                                 let base = result3
                                     .add(i * (2 * ::core::mem::size_of::<*const u8>()));
@@ -403,21 +1064,17 @@ pub mod wasvy {
                                 .add(::core::mem::size_of::<*const u8>())
                                 .cast::<usize>() = len3;
                             *base.add(0).cast::<*mut u8>() = result3;
-                            let vec5 = with1;
+                            let vec5 = optional1;
                             let len5 = vec5.len();
-                            let layout5 = _rt::alloc::Layout::from_size_align_unchecked(
-                                vec5.len() * (2 * ::core::mem::size_of::<*const u8>()),
-                                ::core::mem::size_of::<*const u8>(),
+                            let layout5 = _rt::alloc::Layout::from_size_align(
+                                    vec5.len() * (2 * ::core::mem::size_of::<*const u8>()),
+                                    ::core::mem::size_of::<*const u8>(),
+                                )
+                                .unwrap();
+                            let (result5, _cleanup5) = wit_bindgen_rt::Cleanup::new(
+                                layout5,
                             );
-                            let result5 = if layout5.size() != 0 {
-                                let ptr = _rt::alloc::alloc(layout5).cast::<u8>();
-                                if ptr.is_null() {
-                                    _rt::alloc::handle_alloc_error(layout5);
-                                }
-                                ptr
-                            } else {
-                                ::core::ptr::null_mut()
-                            };
+                            cleanup_list.extend(_cleanup5);
                             for (i, e) in vec5.into_iter().enumerate() {
                                 let base = result5
                                     .add(i * (2 * ::core::mem::size_of::<*const u8>()));
@@ -437,21 +1094,17 @@ pub mod wasvy {
                             *base
                                 .add(2 * ::core::mem::size_of::<*const u8>())
                                 .cast::<*mut u8>() = result5;
-                            let vec7 = without1;
+                            let vec7 = has1;
                             let len7 = vec7.len();
-                            let layout7 = _rt::alloc::Layout::from_size_align_unchecked(
-                                vec7.len() * (2 * ::core::mem::size_of::<*const u8>()),
-                                ::core::mem::size_of::<*const u8>(),
+                            let layout7 = _rt::alloc::Layout::from_size_align(
+                                    vec7.len() * (2 * ::core::mem::size_of::<*const u8>()),
+                                    ::core::mem::size_of::<*const u8>(),
+                                )
+                                .unwrap();
+                            let (result7, _cleanup7) = wit_bindgen_rt::Cleanup::new(
+                                layout7,
                             );
-                            let result7 = if layout7.size() != 0 {
-                                let ptr = _rt::alloc::alloc(layout7).cast::<u8>();
-                                if ptr.is_null() {
-                                    _rt::alloc::handle_alloc_error(layout7);
-                                }
-                                ptr
-                            } else {
-                                ::core::ptr::null_mut()
-                            };
+                            cleanup_list.extend(_cleanup7);
                             for (i, e) in vec7.into_iter().enumerate() {
                                 let base = result7
                                     .add(i * (2 * ::core::mem::size_of::<*const u8>()));
@@ -471,83 +1124,355 @@ pub mod wasvy {
                             *base
                                 .add(4 * ::core::mem::size_of::<*const u8>())
                                 .cast::<*mut u8>() = result7;
-                            cleanup_list
-                                .extend_from_slice(
-                                    &[
-                                        (result3, layout3),
-                                        (result5, layout5),
-                                        (result7, layout7),
-                                    ],
-                                );
-                        }
-                    }
-                    #[cfg(target_arch = "wasm32")]
-                    #[link(wasm_import_module = "wasvy:ecs/functions")]
-                    unsafe extern "C" {
-                        #[link_name = "register-system"]
-                        fn wit_import9(_: *mut u8, _: usize, _: *mut u8, _: usize);
-                    }
-                    #[cfg(not(target_arch = "wasm32"))]
-                    unsafe extern "C" fn wit_import9(
-                        _: *mut u8,
-                        _: usize,
-                        _: *mut u8,
-                        _: usize,
-                    ) {
-                        unreachable!()
-                    }
-                    unsafe { wit_import9(ptr0.cast_mut(), len0, result8, len8) };
-                    if layout8.size() != 0 {
-                        _rt::alloc::dealloc(result8.cast(), layout8);
-                    }
-                    for (ptr, layout) in cleanup_list {
-                        if layout.size() != 0 {
-                            _rt::alloc::dealloc(ptr.cast(), layout);
-                        }
-                    }
-                }
-            }
-            #[allow(unused_unsafe, clippy::all)]
-            pub fn register_component(path: &str) -> ComponentId {
-                unsafe {
-                    let vec0 = path;
-                    let ptr0 = vec0.as_ptr().cast::<u8>();
-                    let len0 = vec0.len();
-                    #[cfg(target_arch = "wasm32")]
-                    #[link(wasm_import_module = "wasvy:ecs/functions")]
-                    unsafe extern "C" {
-                        #[link_name = "register-component"]
-                        fn wit_import1(_: *mut u8, _: usize) -> i64;
-                    }
-                    #[cfg(not(target_arch = "wasm32"))]
-                    unsafe extern "C" fn wit_import1(_: *mut u8, _: usize) -> i64 {
-                        unreachable!()
-                    }
-                    let ret = unsafe { wit_import1(ptr0.cast_mut(), len0) };
-                    ret as u64
-                }
-            }
-            #[allow(unused_unsafe, clippy::all)]
-            pub fn get_component_id(path: &str) -> Option<ComponentId> {
-                unsafe {
-                    #[repr(align(8))]
-                    struct RetArea([::core::mem::MaybeUninit<u8>; 16]);
-                    let mut ret_area = RetArea([::core::mem::MaybeUninit::uninit(); 16]);
-                    let vec0 = path;
-                    let ptr0 = vec0.as_ptr().cast::<u8>();
-                    let len0 = vec0.len();
-                    let ptr1 = ret_area.0.as_mut_ptr().cast::<u8>();
-                    #[cfg(target_arch = "wasm32")]
-                    #[link(wasm_import_module = "wasvy:ecs/functions")]
-                    unsafe extern "C" {
-                        #[link_name = "get-component-id"]
-                        fn wit_import2(_: *mut u8, _: usize, _: *mut u8);
+                            let vec9 = with1;
+                            let len9 = vec9.len();
+                            let layout9 = _rt::alloc::Layout::from_size_align(
+                                    vec9.len() * (2 * ::core::mem::size_of::<*const u8>()),
+                                    ::core::mem::size_of::<*const u8>(),
+                                )
+                                .unwrap();
+                            let (result9, _cleanup9) = wit_bindgen_rt::Cleanup::new(
+                                layout9,
+                            );
+                            cleanup_list.extend(_cleanup9);
+                            for (i, e) in vec9.into_iter().enumerate() {
+                                let base = result9
+                                    .add(i * (2 * ::core::mem::size_of::<*const u8>()));
+                                {
+                                    let vec8 = e;
+                                    let ptr8 = vec8.as_ptr().cast::<u8>();
+                                    let len8 = vec8.len();
+                                    *base
+                                        .add(::core::mem::size_of::<*const u8>())
+                                        .cast::<usize>() = len8;
+                                    *base.add(0).cast::<*mut u8>() = ptr8.cast_mut();
+                                }
+                            }
+                            *base
+                                .add(7 * ::core::mem::size_of::<*const u8>())
+                                .cast::<usize>() = len9;
+                            *base
+                                .add(6 * ::core::mem::size_of::<*const u8>())
+                                .cast::<*mut u8>() = result9;
+                            let vec11 = without1;
+                            let len11 = vec11.len();
+                            let layout11 = _rt::alloc::Layout::from_size_align(
+                                    vec11.len() * (2 * ::core::mem::size_of::<*const u8>()),
+                                    ::core::mem::size_of::<*const u8>(),
+                                )
+                                .unwrap();
+                            let (result11, _cleanup11) = wit_bindgen_rt::Cleanup::new(
+                                layout11,
+                            );
+                            cleanup_list.extend(_cleanup11);
+                            for (i, e) in vec11.into_iter().enumerate() {
+                                let base = result11
+                                    .add(i * (2 * ::core::mem::size_of::<*const u8>()));
+                                {
+                                    let vec10 = e;
+                                    let ptr10 = vec10.as_ptr().cast::<u8>();
+                                    let len10 = vec10.len();
+                                    *base
+                                        .add(::core::mem::size_of::<*const u8>())
+                                        .cast::<usize>() = len10;
+                                    *base.add(0).cast::<*mut u8>() = ptr10.cast_mut();
+                                }
+                            }
+                            *base
+                                .add(9 * ::core::mem::size_of::<*const u8>())
+                                .cast::<usize>() = len11;
+                            *base
+                                .add(8 * ::core::mem::size_of::<*const u8>())
+                                .cast::<*mut u8>() = result11;
+                            let vec13 = added1;
+                            let len13 = vec13.len();
+                            let layout13 = _rt::alloc::Layout::from_size_align(
+                                    vec13.len() * (2 * ::core::mem::size_of::<*const u8>()),
+                                    ::core::mem::size_of::<*const u8>(),
+                                )
+                                .unwrap();
+                            let (result13, _cleanup13) = wit_bindgen_rt::Cleanup::new(
+                                layout13,
+                            );
+                            cleanup_list.extend(_cleanup13);
+                            for (i, e) in vec13.into_iter().enumerate() {
+                                let base = result13
+                                    .add(i * (2 * ::core::mem::size_of::<*const u8>()));
+                                {
+                                    let vec12 = e;
+                                    let ptr12 = vec12.as_ptr().cast::<u8>();
+                                    let len12 = vec12.len();
+                                    *base
+                                        .add(::core::mem::size_of::<*const u8>())
+                                        .cast::<usize>() = len12;
+                                    *base.add(0).cast::<*mut u8>() = ptr12.cast_mut();
+                                }
+                            }
+                            *base
+                                .add(11 * ::core::mem::size_of::<*const u8>())
+                                .cast::<usize>() = len13;
+                            *base
+                                .add(10 * ::core::mem::size_of::<*const u8>())
+                                .cast::<*mut u8>() = result13;
+                            let vec15 = changed1;
+                            let len15 = vec15.len();
+                            let layout15 = _rt::alloc::Layout::from_size_align(
+                                    vec15.len() * (2 * ::core::mem::size_of::<*const u8>()),
+                                    ::core::mem::size_of::<*const u8>(),
+                                )
+                                .unwrap();
+                            let (result15, _cleanup15) = wit_bindgen_rt::Cleanup::new(
+                                layout15,
+                            );
+                            cleanup_list.extend(_cleanup15);
+                            for (i, e) in vec15.into_iter().enumerate() {
+                                let base = result15
+                                    .add(i * (2 * ::core::mem::size_of::<*const u8>()));
+                                {
+                                    let vec14 = e;
+                                    let ptr14 = vec14.as_ptr().cast::<u8>();
+                                    let len14 = vec14.len();
+                                    *base
+                                        .add(::core::mem::size_of::<*const u8>())
+                                        .cast::<usize>() = len14;
+                                    *base.add(0).cast::<*mut u8>() = ptr14.cast_mut();
+                                }
+                            }
+                            *base
+                                .add(13 * ::core::mem::size_of::<*const u8>())
+                                .cast::<usize>() = len15;
+                            *base
+                                .add(12 * ::core::mem::size_of::<*const u8>())
+                                .cast::<*mut u8>() = result15;
+                            let vec17 = removed1;
+                            let len17 = vec17.len();
+                            let layout17 = _rt::alloc::Layout::from_size_align(
+                                    vec17.len() * (2 * ::core::mem::size_of::<*const u8>()),
+                                    ::core::mem::size_of::<*const u8>(),
+                                )
+                                .unwrap();
+                            let (result17, _cleanup17) = wit_bindgen_rt::Cleanup::new(
+                                layout17,
+                            );
+                            cleanup_list.extend(_cleanup17);
+                            for (i, e) in vec17.into_iter().enumerate() {
+                                let base = result17
+                                    .add(i * (2 * ::core::mem::size_of::<*const u8>()));
+                                {
+                                    let vec16 = e;
+                                    let ptr16 = vec16.as_ptr().cast::<u8>();
+                                    let len16 = vec16.len();
+                                    *base
+                                        .add(::core::mem::size_of::<*const u8>())
+                                        .cast::<usize>() = len16;
+                                    *base.add(0).cast::<*mut u8>() = ptr16.cast_mut();
+                                }
+                            }
+                            *base
+                                .add(15 * ::core::mem::size_of::<*const u8>())
+                                .cast::<usize>() = len17;
+                            *base
+                                .add(14 * ::core::mem::size_of::<*const u8>())
+                                .cast::<*mut u8>() = result17;
+                            *base
+                                .add(16 * ::core::mem::size_of::<*const u8>())
+                                .cast::<u8>() = (match hierarchy1 {
+                                true => 1,
+                                false => 0,
+                            }) as u8;
+                        }
+                    }
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "wasvy:ecs/functions")]
+                    unsafe extern "C" {
+                        #[link_name = "register-system"]
+                        fn wit_import19(_: *mut u8, _: usize, _: *mut u8, _: usize);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import19(
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                    ) {
+                        unreachable!()
+                    }
+                    wit_import19(ptr0.cast_mut(), len0, result18, len18);
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// Register a component defined by the mod. Registering the same path again returns the same id.
+            ///
+            /// The component is only known by this mod, other mods can have a component with the same path.
+            #[allow(async_fn_in_trait)]
+            pub fn register_component(path: &str) -> Result<ComponentId, RegisterError> {
+                unsafe {
+                    #[repr(align(8))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 16 + 2 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 16
+                            + 2 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = path;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let ptr1 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "wasvy:ecs/functions")]
+                    unsafe extern "C" {
+                        #[link_name = "register-component"]
+                        fn wit_import2(_: *mut u8, _: usize, _: *mut u8);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import2(_: *mut u8, _: usize, _: *mut u8) {
+                        unreachable!()
+                    }
+                    wit_import2(ptr0.cast_mut(), len0, ptr1);
+                    let l3 = i32::from(*ptr1.add(0).cast::<u8>());
+                    let result10 = match l3 {
+                        0 => {
+                            let e = {
+                                let l4 = *ptr1.add(8).cast::<i64>();
+                                l4 as u64
+                            };
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l5 = i32::from(*ptr1.add(8).cast::<u8>());
+                                use super::super::super::wasvy::ecs::types::RegisterError as V9;
+                                let v9 = match l5 {
+                                    0 => V9::HostType,
+                                    n => {
+                                        debug_assert_eq!(n, 1, "invalid enum discriminant");
+                                        let e9 = {
+                                            let l6 = *ptr1
+                                                .add(8 + 1 * ::core::mem::size_of::<*const u8>())
+                                                .cast::<*mut u8>();
+                                            let l7 = *ptr1
+                                                .add(8 + 2 * ::core::mem::size_of::<*const u8>())
+                                                .cast::<usize>();
+                                            let len8 = l7;
+                                            let bytes8 = _rt::Vec::from_raw_parts(
+                                                l6.cast(),
+                                                len8,
+                                                len8,
+                                            );
+                                            _rt::string_lift(bytes8)
+                                        };
+                                        V9::Exported(e9)
+                                    }
+                                };
+                                v9
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result10
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// Register a component defined by the mod that every other mod can use with its path, without
+            /// registering it.
+            #[allow(async_fn_in_trait)]
+            pub fn export_component(path: &str) -> Result<ComponentId, RegisterError> {
+                unsafe {
+                    #[repr(align(8))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 16 + 2 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 16
+                            + 2 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = path;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let ptr1 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "wasvy:ecs/functions")]
+                    unsafe extern "C" {
+                        #[link_name = "export-component"]
+                        fn wit_import2(_: *mut u8, _: usize, _: *mut u8);
                     }
                     #[cfg(not(target_arch = "wasm32"))]
                     unsafe extern "C" fn wit_import2(_: *mut u8, _: usize, _: *mut u8) {
                         unreachable!()
                     }
-                    unsafe { wit_import2(ptr0.cast_mut(), len0, ptr1) };
+                    wit_import2(ptr0.cast_mut(), len0, ptr1);
+                    let l3 = i32::from(*ptr1.add(0).cast::<u8>());
+                    let result10 = match l3 {
+                        0 => {
+                            let e = {
+                                let l4 = *ptr1.add(8).cast::<i64>();
+                                l4 as u64
+                            };
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l5 = i32::from(*ptr1.add(8).cast::<u8>());
+                                use super::super::super::wasvy::ecs::types::RegisterError as V9;
+                                let v9 = match l5 {
+                                    0 => V9::HostType,
+                                    n => {
+                                        debug_assert_eq!(n, 1, "invalid enum discriminant");
+                                        let e9 = {
+                                            let l6 = *ptr1
+                                                .add(8 + 1 * ::core::mem::size_of::<*const u8>())
+                                                .cast::<*mut u8>();
+                                            let l7 = *ptr1
+                                                .add(8 + 2 * ::core::mem::size_of::<*const u8>())
+                                                .cast::<usize>();
+                                            let len8 = l7;
+                                            let bytes8 = _rt::Vec::from_raw_parts(
+                                                l6.cast(),
+                                                len8,
+                                                len8,
+                                            );
+                                            _rt::string_lift(bytes8)
+                                        };
+                                        V9::Exported(e9)
+                                    }
+                                };
+                                v9
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result10
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            #[allow(async_fn_in_trait)]
+            pub fn get_component_id(path: &str) -> Option<ComponentId> {
+                unsafe {
+                    #[repr(align(8))]
+                    struct RetArea([::core::mem::MaybeUninit<u8>; 16]);
+                    let mut ret_area = RetArea([::core::mem::MaybeUninit::uninit(); 16]);
+                    let vec0 = path;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let ptr1 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "wasvy:ecs/functions")]
+                    unsafe extern "C" {
+                        #[link_name = "get-component-id"]
+                        fn wit_import2(_: *mut u8, _: usize, _: *mut u8);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import2(_: *mut u8, _: usize, _: *mut u8) {
+                        unreachable!()
+                    }
+                    wit_import2(ptr0.cast_mut(), len0, ptr1);
                     let l3 = i32::from(*ptr1.add(0).cast::<u8>());
                     let result5 = match l3 {
                         0 => None,
@@ -564,23 +1489,461 @@ pub mod wasvy {
                 }
             }
             #[allow(unused_unsafe, clippy::all)]
+            #[allow(async_fn_in_trait)]
             pub fn spawn(components: &[Component]) -> Entity {
                 unsafe {
                     let vec3 = components;
                     let len3 = vec3.len();
-                    let layout3 = _rt::alloc::Layout::from_size_align_unchecked(
-                        vec3.len() * (4 * ::core::mem::size_of::<*const u8>()),
-                        ::core::mem::size_of::<*const u8>(),
+                    let layout3 = _rt::alloc::Layout::from_size_align(
+                            vec3.len() * (4 * ::core::mem::size_of::<*const u8>()),
+                            ::core::mem::size_of::<*const u8>(),
+                        )
+                        .unwrap();
+                    let (result3, _cleanup3) = wit_bindgen_rt::Cleanup::new(layout3);
+                    for (i, e) in vec3.into_iter().enumerate() {
+                        let base = result3
+                            .add(i * (4 * ::core::mem::size_of::<*const u8>()));
+                        {
+                            let super::super::super::wasvy::ecs::types::Component {
+                                path: path0,
+                                value: value0,
+                            } = e;
+                            let vec1 = path0;
+                            let ptr1 = vec1.as_ptr().cast::<u8>();
+                            let len1 = vec1.len();
+                            *base
+                                .add(::core::mem::size_of::<*const u8>())
+                                .cast::<usize>() = len1;
+                            *base.add(0).cast::<*mut u8>() = ptr1.cast_mut();
+                            let vec2 = value0;
+                            let ptr2 = vec2.as_ptr().cast::<u8>();
+                            let len2 = vec2.len();
+                            *base
+                                .add(3 * ::core::mem::size_of::<*const u8>())
+                                .cast::<usize>() = len2;
+                            *base
+                                .add(2 * ::core::mem::size_of::<*const u8>())
+                                .cast::<*mut u8>() = ptr2.cast_mut();
+                        }
+                    }
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "wasvy:ecs/functions")]
+                    unsafe extern "C" {
+                        #[link_name = "spawn"]
+                        fn wit_import4(_: *mut u8, _: usize) -> i64;
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import4(_: *mut u8, _: usize) -> i64 {
+                        unreachable!()
+                    }
+                    let ret = wit_import4(result3, len3);
+                    ret as u64
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// Spawn an entity as a child of `parent`, like spawning it with Bevy's `ChildOf`.
+            #[allow(async_fn_in_trait)]
+            pub fn spawn_child(parent: Entity, components: &[Component]) -> Entity {
+                unsafe {
+                    let vec3 = components;
+                    let len3 = vec3.len();
+                    let layout3 = _rt::alloc::Layout::from_size_align(
+                            vec3.len() * (4 * ::core::mem::size_of::<*const u8>()),
+                            ::core::mem::size_of::<*const u8>(),
+                        )
+                        .unwrap();
+                    let (result3, _cleanup3) = wit_bindgen_rt::Cleanup::new(layout3);
+                    for (i, e) in vec3.into_iter().enumerate() {
+                        let base = result3
+                            .add(i * (4 * ::core::mem::size_of::<*const u8>()));
+                        {
+                            let super::super::super::wasvy::ecs::types::Component {
+                                path: path0,
+                                value: value0,
+                            } = e;
+                            let vec1 = path0;
+                            let ptr1 = vec1.as_ptr().cast::<u8>();
+                            let len1 = vec1.len();
+                            *base
+                                .add(::core::mem::size_of::<*const u8>())
+                                .cast::<usize>() = len1;
+                            *base.add(0).cast::<*mut u8>() = ptr1.cast_mut();
+                            let vec2 = value0;
+                            let ptr2 = vec2.as_ptr().cast::<u8>();
+                            let len2 = vec2.len();
+                            *base
+                                .add(3 * ::core::mem::size_of::<*const u8>())
+                                .cast::<usize>() = len2;
+                            *base
+                                .add(2 * ::core::mem::size_of::<*const u8>())
+                                .cast::<*mut u8>() = ptr2.cast_mut();
+                        }
+                    }
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "wasvy:ecs/functions")]
+                    unsafe extern "C" {
+                        #[link_name = "spawn-child"]
+                        fn wit_import4(_: i64, _: *mut u8, _: usize) -> i64;
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import4(
+                        _: i64,
+                        _: *mut u8,
+                        _: usize,
+                    ) -> i64 {
+                        unreachable!()
+                    }
+                    let ret = wit_import4(_rt::as_i64(parent), result3, len3);
+                    ret as u64
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// Make `entity` a child of `parent`, moving it out of the children of its current parent.
+            ///
+            /// Nothing happens if the host's permissions don't let the mod change the parents of these
+            /// entities.
+            #[allow(async_fn_in_trait)]
+            pub fn set_parent(entity: Entity, parent: Entity) -> () {
+                unsafe {
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "wasvy:ecs/functions")]
+                    unsafe extern "C" {
+                        #[link_name = "set-parent"]
+                        fn wit_import0(_: i64, _: i64);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import0(_: i64, _: i64) {
+                        unreachable!()
+                    }
+                    wit_import0(_rt::as_i64(entity), _rt::as_i64(parent));
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// Remove the parent of `entity`, making it a root entity.
+            #[allow(async_fn_in_trait)]
+            pub fn remove_parent(entity: Entity) -> () {
+                unsafe {
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "wasvy:ecs/functions")]
+                    unsafe extern "C" {
+                        #[link_name = "remove-parent"]
+                        fn wit_import0(_: i64);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import0(_: i64) {
+                        unreachable!()
+                    }
+                    wit_import0(_rt::as_i64(entity));
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// Despawn `entity` along with all its descendants.
+            ///
+            /// Nothing happens if the host's permissions don't let the mod despawn one of them.
+            #[allow(async_fn_in_trait)]
+            pub fn despawn_recursive(entity: Entity) -> () {
+                unsafe {
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "wasvy:ecs/functions")]
+                    unsafe extern "C" {
+                        #[link_name = "despawn-recursive"]
+                        fn wit_import0(_: i64);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import0(_: i64) {
+                        unreachable!()
+                    }
+                    wit_import0(_rt::as_i64(entity));
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// Look up the components of a single entity, in the order of `components`.
+            ///
+            /// Inside a system, only the components that one of the system's queries can read are accessible,
+            /// and the entity must match that query. Returns `none` if the entity doesn't exist, doesn't have
+            /// one of the components, or the system isn't allowed to read them. In `setup` every component can be read,
+            /// except the ones the host doesn't allow the mod to read.
+            #[allow(async_fn_in_trait)]
+            pub fn get(
+                entity: Entity,
+                components: &[_rt::String],
+            ) -> Option<_rt::Vec<Component>> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 3 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 3
+                            * ::core::mem::size_of::<*const u8>()],
                     );
-                    let result3 = if layout3.size() != 0 {
-                        let ptr = _rt::alloc::alloc(layout3).cast::<u8>();
-                        if ptr.is_null() {
-                            _rt::alloc::handle_alloc_error(layout3);
-                        }
-                        ptr
-                    } else {
-                        ::core::ptr::null_mut()
+                    let vec1 = components;
+                    let len1 = vec1.len();
+                    let layout1 = _rt::alloc::Layout::from_size_align(
+                            vec1.len() * (2 * ::core::mem::size_of::<*const u8>()),
+                            ::core::mem::size_of::<*const u8>(),
+                        )
+                        .unwrap();
+                    let (result1, _cleanup1) = wit_bindgen_rt::Cleanup::new(layout1);
+                    for (i, e) in vec1.into_iter().enumerate() {
+                        let base = result1
+                            .add(i * (2 * ::core::mem::size_of::<*const u8>()));
+                        {
+                            let vec0 = e;
+                            let ptr0 = vec0.as_ptr().cast::<u8>();
+                            let len0 = vec0.len();
+                            *base
+                                .add(::core::mem::size_of::<*const u8>())
+                                .cast::<usize>() = len0;
+                            *base.add(0).cast::<*mut u8>() = ptr0.cast_mut();
+                        }
+                    }
+                    let ptr2 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "wasvy:ecs/functions")]
+                    unsafe extern "C" {
+                        #[link_name = "get"]
+                        fn wit_import3(_: i64, _: *mut u8, _: usize, _: *mut u8);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import3(
+                        _: i64,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                    ) {
+                        unreachable!()
+                    }
+                    wit_import3(_rt::as_i64(entity), result1, len1, ptr2);
+                    let l4 = i32::from(*ptr2.add(0).cast::<u8>());
+                    let result14 = match l4 {
+                        0 => None,
+                        1 => {
+                            let e = {
+                                let l5 = *ptr2
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l6 = *ptr2
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let base13 = l5;
+                                let len13 = l6;
+                                let mut result13 = _rt::Vec::with_capacity(len13);
+                                for i in 0..len13 {
+                                    let base = base13
+                                        .add(i * (4 * ::core::mem::size_of::<*const u8>()));
+                                    let e13 = {
+                                        let l7 = *base.add(0).cast::<*mut u8>();
+                                        let l8 = *base
+                                            .add(::core::mem::size_of::<*const u8>())
+                                            .cast::<usize>();
+                                        let len9 = l8;
+                                        let bytes9 = _rt::Vec::from_raw_parts(
+                                            l7.cast(),
+                                            len9,
+                                            len9,
+                                        );
+                                        let l10 = *base
+                                            .add(2 * ::core::mem::size_of::<*const u8>())
+                                            .cast::<*mut u8>();
+                                        let l11 = *base
+                                            .add(3 * ::core::mem::size_of::<*const u8>())
+                                            .cast::<usize>();
+                                        let len12 = l11;
+                                        let bytes12 = _rt::Vec::from_raw_parts(
+                                            l10.cast(),
+                                            len12,
+                                            len12,
+                                        );
+                                        super::super::super::wasvy::ecs::types::Component {
+                                            path: _rt::string_lift(bytes9),
+                                            value: _rt::string_lift(bytes12),
+                                        }
+                                    };
+                                    result13.push(e13);
+                                }
+                                _rt::cabi_dealloc(
+                                    base13,
+                                    len13 * (4 * ::core::mem::size_of::<*const u8>()),
+                                    ::core::mem::size_of::<*const u8>(),
+                                );
+                                result13
+                            };
+                            Some(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
                     };
+                    result14
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// `get` for many entities at once, with a result for each entity.
+            #[allow(async_fn_in_trait)]
+            pub fn get_many(
+                entities: &[Entity],
+                components: &[_rt::String],
+            ) -> _rt::Vec<Option<_rt::Vec<Component>>> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 2 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 2
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = entities;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let vec2 = components;
+                    let len2 = vec2.len();
+                    let layout2 = _rt::alloc::Layout::from_size_align(
+                            vec2.len() * (2 * ::core::mem::size_of::<*const u8>()),
+                            ::core::mem::size_of::<*const u8>(),
+                        )
+                        .unwrap();
+                    let (result2, _cleanup2) = wit_bindgen_rt::Cleanup::new(layout2);
+                    for (i, e) in vec2.into_iter().enumerate() {
+                        let base = result2
+                            .add(i * (2 * ::core::mem::size_of::<*const u8>()));
+                        {
+                            let vec1 = e;
+                            let ptr1 = vec1.as_ptr().cast::<u8>();
+                            let len1 = vec1.len();
+                            *base
+                                .add(::core::mem::size_of::<*const u8>())
+                                .cast::<usize>() = len1;
+                            *base.add(0).cast::<*mut u8>() = ptr1.cast_mut();
+                        }
+                    }
+                    let ptr3 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "wasvy:ecs/functions")]
+                    unsafe extern "C" {
+                        #[link_name = "get-many"]
+                        fn wit_import4(
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                        );
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import4(
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                    ) {
+                        unreachable!()
+                    }
+                    wit_import4(ptr0.cast_mut(), len0, result2, len2, ptr3);
+                    let l5 = *ptr3.add(0).cast::<*mut u8>();
+                    let l6 = *ptr3
+                        .add(::core::mem::size_of::<*const u8>())
+                        .cast::<usize>();
+                    let base17 = l5;
+                    let len17 = l6;
+                    let mut result17 = _rt::Vec::with_capacity(len17);
+                    for i in 0..len17 {
+                        let base = base17
+                            .add(i * (3 * ::core::mem::size_of::<*const u8>()));
+                        let e17 = {
+                            let l7 = i32::from(*base.add(0).cast::<u8>());
+                            match l7 {
+                                0 => None,
+                                1 => {
+                                    let e = {
+                                        let l8 = *base
+                                            .add(::core::mem::size_of::<*const u8>())
+                                            .cast::<*mut u8>();
+                                        let l9 = *base
+                                            .add(2 * ::core::mem::size_of::<*const u8>())
+                                            .cast::<usize>();
+                                        let base16 = l8;
+                                        let len16 = l9;
+                                        let mut result16 = _rt::Vec::with_capacity(len16);
+                                        for i in 0..len16 {
+                                            let base = base16
+                                                .add(i * (4 * ::core::mem::size_of::<*const u8>()));
+                                            let e16 = {
+                                                let l10 = *base.add(0).cast::<*mut u8>();
+                                                let l11 = *base
+                                                    .add(::core::mem::size_of::<*const u8>())
+                                                    .cast::<usize>();
+                                                let len12 = l11;
+                                                let bytes12 = _rt::Vec::from_raw_parts(
+                                                    l10.cast(),
+                                                    len12,
+                                                    len12,
+                                                );
+                                                let l13 = *base
+                                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                                    .cast::<*mut u8>();
+                                                let l14 = *base
+                                                    .add(3 * ::core::mem::size_of::<*const u8>())
+                                                    .cast::<usize>();
+                                                let len15 = l14;
+                                                let bytes15 = _rt::Vec::from_raw_parts(
+                                                    l13.cast(),
+                                                    len15,
+                                                    len15,
+                                                );
+                                                super::super::super::wasvy::ecs::types::Component {
+                                                    path: _rt::string_lift(bytes12),
+                                                    value: _rt::string_lift(bytes15),
+                                                }
+                                            };
+                                            result16.push(e16);
+                                        }
+                                        _rt::cabi_dealloc(
+                                            base16,
+                                            len16 * (4 * ::core::mem::size_of::<*const u8>()),
+                                            ::core::mem::size_of::<*const u8>(),
+                                        );
+                                        result16
+                                    };
+                                    Some(e)
+                                }
+                                _ => _rt::invalid_enum_discriminant(),
+                            }
+                        };
+                        result17.push(e17);
+                    }
+                    _rt::cabi_dealloc(
+                        base17,
+                        len17 * (3 * ::core::mem::size_of::<*const u8>()),
+                        ::core::mem::size_of::<*const u8>(),
+                    );
+                    let result18 = result17;
+                    result18
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// Replace the components of a single entity, following the same rules as `get`: the entity must
+            /// already have all of them. Like the `insert` command, the change is applied after the system returns.
+            ///
+            /// Returns false (and changes nothing) if `get` wouldn't return the components, the host doesn't
+            /// allow the mod to change one of them, or one of the values doesn't match its type.
+            #[allow(async_fn_in_trait)]
+            pub fn set(entity: Entity, components: &[Component]) -> bool {
+                unsafe {
+                    let vec3 = components;
+                    let len3 = vec3.len();
+                    let layout3 = _rt::alloc::Layout::from_size_align(
+                            vec3.len() * (4 * ::core::mem::size_of::<*const u8>()),
+                            ::core::mem::size_of::<*const u8>(),
+                        )
+                        .unwrap();
+                    let (result3, _cleanup3) = wit_bindgen_rt::Cleanup::new(layout3);
                     for (i, e) in vec3.into_iter().enumerate() {
                         let base = result3
                             .add(i * (4 * ::core::mem::size_of::<*const u8>()));
@@ -610,190 +1973,789 @@ pub mod wasvy {
                     #[cfg(target_arch = "wasm32")]
                     #[link(wasm_import_module = "wasvy:ecs/functions")]
                     unsafe extern "C" {
-                        #[link_name = "spawn"]
-                        fn wit_import4(_: *mut u8, _: usize) -> i64;
+                        #[link_name = "set"]
+                        fn wit_import4(_: i64, _: *mut u8, _: usize) -> i32;
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import4(
+                        _: i64,
+                        _: *mut u8,
+                        _: usize,
+                    ) -> i32 {
+                        unreachable!()
+                    }
+                    let ret = wit_import4(_rt::as_i64(entity), result3, len3);
+                    _rt::bool_lift(ret as u8)
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// `set` for many entities at once, with a result for each entity.
+            #[allow(async_fn_in_trait)]
+            pub fn set_many(entities: &[InsertCommand]) -> _rt::Vec<bool> {
+                unsafe {
+                    let mut cleanup_list = _rt::Vec::new();
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 2 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 2
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec5 = entities;
+                    let len5 = vec5.len();
+                    let layout5 = _rt::alloc::Layout::from_size_align(
+                            vec5.len() * (8 + 2 * ::core::mem::size_of::<*const u8>()),
+                            8,
+                        )
+                        .unwrap();
+                    let (result5, _cleanup5) = wit_bindgen_rt::Cleanup::new(layout5);
+                    for (i, e) in vec5.into_iter().enumerate() {
+                        let base = result5
+                            .add(i * (8 + 2 * ::core::mem::size_of::<*const u8>()));
+                        {
+                            let super::super::super::wasvy::ecs::types::InsertCommand {
+                                entity: entity0,
+                                components: components0,
+                            } = e;
+                            *base.add(0).cast::<i64>() = _rt::as_i64(entity0);
+                            let vec4 = components0;
+                            let len4 = vec4.len();
+                            let layout4 = _rt::alloc::Layout::from_size_align(
+                                    vec4.len() * (4 * ::core::mem::size_of::<*const u8>()),
+                                    ::core::mem::size_of::<*const u8>(),
+                                )
+                                .unwrap();
+                            let (result4, _cleanup4) = wit_bindgen_rt::Cleanup::new(
+                                layout4,
+                            );
+                            cleanup_list.extend(_cleanup4);
+                            for (i, e) in vec4.into_iter().enumerate() {
+                                let base = result4
+                                    .add(i * (4 * ::core::mem::size_of::<*const u8>()));
+                                {
+                                    let super::super::super::wasvy::ecs::types::Component {
+                                        path: path1,
+                                        value: value1,
+                                    } = e;
+                                    let vec2 = path1;
+                                    let ptr2 = vec2.as_ptr().cast::<u8>();
+                                    let len2 = vec2.len();
+                                    *base
+                                        .add(::core::mem::size_of::<*const u8>())
+                                        .cast::<usize>() = len2;
+                                    *base.add(0).cast::<*mut u8>() = ptr2.cast_mut();
+                                    let vec3 = value1;
+                                    let ptr3 = vec3.as_ptr().cast::<u8>();
+                                    let len3 = vec3.len();
+                                    *base
+                                        .add(3 * ::core::mem::size_of::<*const u8>())
+                                        .cast::<usize>() = len3;
+                                    *base
+                                        .add(2 * ::core::mem::size_of::<*const u8>())
+                                        .cast::<*mut u8>() = ptr3.cast_mut();
+                                }
+                            }
+                            *base
+                                .add(8 + 1 * ::core::mem::size_of::<*const u8>())
+                                .cast::<usize>() = len4;
+                            *base.add(8).cast::<*mut u8>() = result4;
+                        }
+                    }
+                    let ptr6 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "wasvy:ecs/functions")]
+                    unsafe extern "C" {
+                        #[link_name = "set-many"]
+                        fn wit_import7(_: *mut u8, _: usize, _: *mut u8);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import7(_: *mut u8, _: usize, _: *mut u8) {
+                        unreachable!()
+                    }
+                    wit_import7(result5, len5, ptr6);
+                    let l8 = *ptr6.add(0).cast::<*mut u8>();
+                    let l9 = *ptr6
+                        .add(::core::mem::size_of::<*const u8>())
+                        .cast::<usize>();
+                    let base11 = l8;
+                    let len11 = l9;
+                    let mut result11 = _rt::Vec::with_capacity(len11);
+                    for i in 0..len11 {
+                        let base = base11.add(i * 1);
+                        let e11 = {
+                            let l10 = i32::from(*base.add(0).cast::<u8>());
+                            _rt::bool_lift(l10 as u8)
+                        };
+                        result11.push(e11);
+                    }
+                    _rt::cabi_dealloc(base11, len11 * 1, 1);
+                    let result12 = result11;
+                    result12
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// Start loading the asset at `path` with Bevy's `AssetServer`.
+            ///
+            /// Paths are relative to the mod's own asset directory by default, the directory next to the mod
+            /// with the name of its file (e.g. `mods/my_mod/` for `mods/my_mod.wasm`). Returns `none` if the
+            /// path leaves that directory. Loading the same path again returns the same handle.
+            #[allow(async_fn_in_trait)]
+            pub fn load_asset(path: &str) -> Option<AssetHandle> {
+                unsafe {
+                    #[repr(align(8))]
+                    struct RetArea([::core::mem::MaybeUninit<u8>; 16]);
+                    let mut ret_area = RetArea([::core::mem::MaybeUninit::uninit(); 16]);
+                    let vec0 = path;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let ptr1 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "wasvy:ecs/functions")]
+                    unsafe extern "C" {
+                        #[link_name = "load-asset"]
+                        fn wit_import2(_: *mut u8, _: usize, _: *mut u8);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import2(_: *mut u8, _: usize, _: *mut u8) {
+                        unreachable!()
+                    }
+                    wit_import2(ptr0.cast_mut(), len0, ptr1);
+                    let l3 = i32::from(*ptr1.add(0).cast::<u8>());
+                    let result5 = match l3 {
+                        0 => None,
+                        1 => {
+                            let e = {
+                                let l4 = *ptr1.add(8).cast::<i64>();
+                                l4 as u64
+                            };
+                            Some(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result5
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// Where an asset loaded with `load-asset` is in its loading process.
+            #[allow(async_fn_in_trait)]
+            pub fn asset_load_state(asset: AssetHandle) -> LoadState {
+                unsafe {
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "wasvy:ecs/functions")]
+                    unsafe extern "C" {
+                        #[link_name = "asset-load-state"]
+                        fn wit_import0(_: i64) -> i32;
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import0(_: i64) -> i32 {
+                        unreachable!()
+                    }
+                    let ret = wit_import0(_rt::as_i64(asset));
+                    super::super::super::wasvy::ecs::types::LoadState::_lift(ret as u8)
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// The time of the current frame.
+            ///
+            /// Mods should use it instead of the clocks of WASI, which are fixed in deterministic mode. In
+            /// that mode the time advances by a fixed step every frame, so it's the same on every machine.
+            #[allow(async_fn_in_trait)]
+            pub fn time() -> FrameTime {
+                unsafe {
+                    #[repr(align(8))]
+                    struct RetArea([::core::mem::MaybeUninit<u8>; 24]);
+                    let mut ret_area = RetArea([::core::mem::MaybeUninit::uninit(); 24]);
+                    let ptr0 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "wasvy:ecs/functions")]
+                    unsafe extern "C" {
+                        #[link_name = "time"]
+                        fn wit_import1(_: *mut u8);
                     }
                     #[cfg(not(target_arch = "wasm32"))]
-                    unsafe extern "C" fn wit_import4(_: *mut u8, _: usize) -> i64 {
+                    unsafe extern "C" fn wit_import1(_: *mut u8) {
                         unreachable!()
                     }
-                    let ret = unsafe { wit_import4(result3, len3) };
-                    if layout3.size() != 0 {
-                        _rt::alloc::dealloc(result3.cast(), layout3);
+                    wit_import1(ptr0);
+                    let l2 = *ptr0.add(0).cast::<f64>();
+                    let l3 = *ptr0.add(8).cast::<f64>();
+                    let l4 = *ptr0.add(16).cast::<i64>();
+                    let result5 = super::super::super::wasvy::ecs::types::FrameTime {
+                        delta: l2,
+                        elapsed: l3,
+                        frame: l4 as u64,
+                    };
+                    result5
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// A random number from the mod's own generator.
+            ///
+            /// In deterministic mode the generator is seeded by the host, so every machine gets the same
+            /// numbers as long as the mod calls it in the same order.
+            #[allow(async_fn_in_trait)]
+            pub fn random() -> u64 {
+                unsafe {
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "wasvy:ecs/functions")]
+                    unsafe extern "C" {
+                        #[link_name = "random"]
+                        fn wit_import0() -> i64;
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import0() -> i64 {
+                        unreachable!()
                     }
+                    let ret = wit_import0();
                     ret as u64
                 }
             }
             #[allow(unused_unsafe, clippy::all)]
             /// For some reason if the type isn't being used by a function, cargo component doesn't generate a binding for it.
             /// so this function is only to accumulate types so they are generated.
+            #[allow(async_fn_in_trait)]
             pub fn this_function_does_nothing(
                 entry: &QueryResultEntry,
                 query_result: &[super::super::super::wasvy::ecs::types::QueryResultEntry],
+                command: &Command,
             ) -> () {
                 unsafe {
                     let mut cleanup_list = _rt::Vec::new();
                     let super::super::super::wasvy::ecs::types::QueryResultEntry {
                         components: components0,
                         entity: entity0,
+                        has: has0,
+                        parent: parent0,
+                        children: children0,
                     } = entry;
                     let vec4 = components0;
                     let len4 = vec4.len();
-                    let layout4 = _rt::alloc::Layout::from_size_align_unchecked(
-                        vec4.len() * (4 * ::core::mem::size_of::<*const u8>()),
-                        ::core::mem::size_of::<*const u8>(),
-                    );
-                    let result4 = if layout4.size() != 0 {
-                        let ptr = _rt::alloc::alloc(layout4).cast::<u8>();
-                        if ptr.is_null() {
-                            _rt::alloc::handle_alloc_error(layout4);
-                        }
-                        ptr
-                    } else {
-                        ::core::ptr::null_mut()
-                    };
+                    let layout4 = _rt::alloc::Layout::from_size_align(
+                            vec4.len() * (5 * ::core::mem::size_of::<*const u8>()),
+                            ::core::mem::size_of::<*const u8>(),
+                        )
+                        .unwrap();
+                    let (result4, _cleanup4) = wit_bindgen_rt::Cleanup::new(layout4);
                     for (i, e) in vec4.into_iter().enumerate() {
                         let base = result4
-                            .add(i * (4 * ::core::mem::size_of::<*const u8>()));
+                            .add(i * (5 * ::core::mem::size_of::<*const u8>()));
                         {
-                            let super::super::super::wasvy::ecs::types::Component {
-                                path: path1,
-                                value: value1,
+                            match e {
+                                Some(e) => {
+                                    *base.add(0).cast::<u8>() = (1i32) as u8;
+                                    let super::super::super::wasvy::ecs::types::Component {
+                                        path: path1,
+                                        value: value1,
+                                    } = e;
+                                    let vec2 = path1;
+                                    let ptr2 = vec2.as_ptr().cast::<u8>();
+                                    let len2 = vec2.len();
+                                    *base
+                                        .add(2 * ::core::mem::size_of::<*const u8>())
+                                        .cast::<usize>() = len2;
+                                    *base
+                                        .add(::core::mem::size_of::<*const u8>())
+                                        .cast::<*mut u8>() = ptr2.cast_mut();
+                                    let vec3 = value1;
+                                    let ptr3 = vec3.as_ptr().cast::<u8>();
+                                    let len3 = vec3.len();
+                                    *base
+                                        .add(4 * ::core::mem::size_of::<*const u8>())
+                                        .cast::<usize>() = len3;
+                                    *base
+                                        .add(3 * ::core::mem::size_of::<*const u8>())
+                                        .cast::<*mut u8>() = ptr3.cast_mut();
+                                }
+                                None => {
+                                    *base.add(0).cast::<u8>() = (0i32) as u8;
+                                }
+                            };
+                        }
+                    }
+                    let vec5 = has0;
+                    let len5 = vec5.len();
+                    let layout5 = _rt::alloc::Layout::from_size_align(vec5.len() * 1, 1)
+                        .unwrap();
+                    let (result5, _cleanup5) = wit_bindgen_rt::Cleanup::new(layout5);
+                    for (i, e) in vec5.into_iter().enumerate() {
+                        let base = result5.add(i * 1);
+                        {
+                            *base.add(0).cast::<u8>() = (match e {
+                                true => 1,
+                                false => 0,
+                            }) as u8;
+                        }
+                    }
+                    let (result6_0, result6_1) = match parent0 {
+                        Some(e) => (1i32, _rt::as_i64(e)),
+                        None => (0i32, 0i64),
+                    };
+                    let vec7 = children0;
+                    let ptr7 = vec7.as_ptr().cast::<u8>();
+                    let len7 = vec7.len();
+                    let vec15 = query_result;
+                    let len15 = vec15.len();
+                    let layout15 = _rt::alloc::Layout::from_size_align(
+                            vec15.len() * (24 + 6 * ::core::mem::size_of::<*const u8>()),
+                            8,
+                        )
+                        .unwrap();
+                    let (result15, _cleanup15) = wit_bindgen_rt::Cleanup::new(layout15);
+                    for (i, e) in vec15.into_iter().enumerate() {
+                        let base = result15
+                            .add(i * (24 + 6 * ::core::mem::size_of::<*const u8>()));
+                        {
+                            let super::super::super::wasvy::ecs::types::QueryResultEntry {
+                                components: components8,
+                                entity: entity8,
+                                has: has8,
+                                parent: parent8,
+                                children: children8,
                             } = e;
-                            let vec2 = path1;
-                            let ptr2 = vec2.as_ptr().cast::<u8>();
-                            let len2 = vec2.len();
+                            let vec12 = components8;
+                            let len12 = vec12.len();
+                            let layout12 = _rt::alloc::Layout::from_size_align(
+                                    vec12.len() * (5 * ::core::mem::size_of::<*const u8>()),
+                                    ::core::mem::size_of::<*const u8>(),
+                                )
+                                .unwrap();
+                            let (result12, _cleanup12) = wit_bindgen_rt::Cleanup::new(
+                                layout12,
+                            );
+                            cleanup_list.extend(_cleanup12);
+                            for (i, e) in vec12.into_iter().enumerate() {
+                                let base = result12
+                                    .add(i * (5 * ::core::mem::size_of::<*const u8>()));
+                                {
+                                    match e {
+                                        Some(e) => {
+                                            *base.add(0).cast::<u8>() = (1i32) as u8;
+                                            let super::super::super::wasvy::ecs::types::Component {
+                                                path: path9,
+                                                value: value9,
+                                            } = e;
+                                            let vec10 = path9;
+                                            let ptr10 = vec10.as_ptr().cast::<u8>();
+                                            let len10 = vec10.len();
+                                            *base
+                                                .add(2 * ::core::mem::size_of::<*const u8>())
+                                                .cast::<usize>() = len10;
+                                            *base
+                                                .add(::core::mem::size_of::<*const u8>())
+                                                .cast::<*mut u8>() = ptr10.cast_mut();
+                                            let vec11 = value9;
+                                            let ptr11 = vec11.as_ptr().cast::<u8>();
+                                            let len11 = vec11.len();
+                                            *base
+                                                .add(4 * ::core::mem::size_of::<*const u8>())
+                                                .cast::<usize>() = len11;
+                                            *base
+                                                .add(3 * ::core::mem::size_of::<*const u8>())
+                                                .cast::<*mut u8>() = ptr11.cast_mut();
+                                        }
+                                        None => {
+                                            *base.add(0).cast::<u8>() = (0i32) as u8;
+                                        }
+                                    };
+                                }
+                            }
                             *base
                                 .add(::core::mem::size_of::<*const u8>())
-                                .cast::<usize>() = len2;
-                            *base.add(0).cast::<*mut u8>() = ptr2.cast_mut();
-                            let vec3 = value1;
-                            let ptr3 = vec3.as_ptr().cast::<u8>();
-                            let len3 = vec3.len();
-                            *base
-                                .add(3 * ::core::mem::size_of::<*const u8>())
-                                .cast::<usize>() = len3;
+                                .cast::<usize>() = len12;
+                            *base.add(0).cast::<*mut u8>() = result12;
                             *base
                                 .add(2 * ::core::mem::size_of::<*const u8>())
-                                .cast::<*mut u8>() = ptr3.cast_mut();
+                                .cast::<i64>() = _rt::as_i64(entity8);
+                            let vec13 = has8;
+                            let len13 = vec13.len();
+                            let layout13 = _rt::alloc::Layout::from_size_align(
+                                    vec13.len() * 1,
+                                    1,
+                                )
+                                .unwrap();
+                            let (result13, _cleanup13) = wit_bindgen_rt::Cleanup::new(
+                                layout13,
+                            );
+                            cleanup_list.extend(_cleanup13);
+                            for (i, e) in vec13.into_iter().enumerate() {
+                                let base = result13.add(i * 1);
+                                {
+                                    *base.add(0).cast::<u8>() = (match e {
+                                        true => 1,
+                                        false => 0,
+                                    }) as u8;
+                                }
+                            }
+                            *base
+                                .add(8 + 3 * ::core::mem::size_of::<*const u8>())
+                                .cast::<usize>() = len13;
+                            *base
+                                .add(8 + 2 * ::core::mem::size_of::<*const u8>())
+                                .cast::<*mut u8>() = result13;
+                            match parent8 {
+                                Some(e) => {
+                                    *base
+                                        .add(8 + 4 * ::core::mem::size_of::<*const u8>())
+                                        .cast::<u8>() = (1i32) as u8;
+                                    *base
+                                        .add(16 + 4 * ::core::mem::size_of::<*const u8>())
+                                        .cast::<i64>() = _rt::as_i64(e);
+                                }
+                                None => {
+                                    *base
+                                        .add(8 + 4 * ::core::mem::size_of::<*const u8>())
+                                        .cast::<u8>() = (0i32) as u8;
+                                }
+                            };
+                            let vec14 = children8;
+                            let ptr14 = vec14.as_ptr().cast::<u8>();
+                            let len14 = vec14.len();
+                            *base
+                                .add(24 + 5 * ::core::mem::size_of::<*const u8>())
+                                .cast::<usize>() = len14;
+                            *base
+                                .add(24 + 4 * ::core::mem::size_of::<*const u8>())
+                                .cast::<*mut u8>() = ptr14.cast_mut();
                         }
                     }
-                    let vec10 = query_result;
-                    let len10 = vec10.len();
-                    let layout10 = _rt::alloc::Layout::from_size_align_unchecked(
-                        vec10.len() * (8 + 2 * ::core::mem::size_of::<*const u8>()),
-                        8,
-                    );
-                    let result10 = if layout10.size() != 0 {
-                        let ptr = _rt::alloc::alloc(layout10).cast::<u8>();
-                        if ptr.is_null() {
-                            _rt::alloc::handle_alloc_error(layout10);
-                        }
-                        ptr
-                    } else {
-                        ::core::ptr::null_mut()
-                    };
-                    for (i, e) in vec10.into_iter().enumerate() {
-                        let base = result10
-                            .add(i * (8 + 2 * ::core::mem::size_of::<*const u8>()));
-                        {
-                            let super::super::super::wasvy::ecs::types::QueryResultEntry {
-                                components: components5,
-                                entity: entity5,
+                    use super::super::super::wasvy::ecs::types::Command as V38;
+                    let (result39_0, result39_1, result39_2, result39_3, result39_4) = match command {
+                        V38::Spawn(e) => {
+                            let vec19 = e;
+                            let len19 = vec19.len();
+                            let layout19 = _rt::alloc::Layout::from_size_align(
+                                    vec19.len() * (4 * ::core::mem::size_of::<*const u8>()),
+                                    ::core::mem::size_of::<*const u8>(),
+                                )
+                                .unwrap();
+                            let (result19, _cleanup19) = wit_bindgen_rt::Cleanup::new(
+                                layout19,
+                            );
+                            cleanup_list.extend(_cleanup19);
+                            for (i, e) in vec19.into_iter().enumerate() {
+                                let base = result19
+                                    .add(i * (4 * ::core::mem::size_of::<*const u8>()));
+                                {
+                                    let super::super::super::wasvy::ecs::types::Component {
+                                        path: path16,
+                                        value: value16,
+                                    } = e;
+                                    let vec17 = path16;
+                                    let ptr17 = vec17.as_ptr().cast::<u8>();
+                                    let len17 = vec17.len();
+                                    *base
+                                        .add(::core::mem::size_of::<*const u8>())
+                                        .cast::<usize>() = len17;
+                                    *base.add(0).cast::<*mut u8>() = ptr17.cast_mut();
+                                    let vec18 = value16;
+                                    let ptr18 = vec18.as_ptr().cast::<u8>();
+                                    let len18 = vec18.len();
+                                    *base
+                                        .add(3 * ::core::mem::size_of::<*const u8>())
+                                        .cast::<usize>() = len18;
+                                    *base
+                                        .add(2 * ::core::mem::size_of::<*const u8>())
+                                        .cast::<*mut u8>() = ptr18.cast_mut();
+                                }
+                            }
+                            (
+                                0i32,
+                                {
+                                    let mut t = ::core::mem::MaybeUninit::<u64>::uninit();
+                                    t.as_mut_ptr().cast::<*mut u8>().write(result19);
+                                    t
+                                },
+                                ::core::mem::MaybeUninit::new(len19 as i64 as u64),
+                                ::core::ptr::null_mut(),
+                                0usize,
+                            )
+                        }
+                        V38::Insert(e) => {
+                            let super::super::super::wasvy::ecs::types::InsertCommand {
+                                entity: entity20,
+                                components: components20,
                             } = e;
-                            let vec9 = components5;
-                            let len9 = vec9.len();
-                            let layout9 = _rt::alloc::Layout::from_size_align_unchecked(
-                                vec9.len() * (4 * ::core::mem::size_of::<*const u8>()),
-                                ::core::mem::size_of::<*const u8>(),
+                            let vec24 = components20;
+                            let len24 = vec24.len();
+                            let layout24 = _rt::alloc::Layout::from_size_align(
+                                    vec24.len() * (4 * ::core::mem::size_of::<*const u8>()),
+                                    ::core::mem::size_of::<*const u8>(),
+                                )
+                                .unwrap();
+                            let (result24, _cleanup24) = wit_bindgen_rt::Cleanup::new(
+                                layout24,
                             );
-                            let result9 = if layout9.size() != 0 {
-                                let ptr = _rt::alloc::alloc(layout9).cast::<u8>();
-                                if ptr.is_null() {
-                                    _rt::alloc::handle_alloc_error(layout9);
+                            cleanup_list.extend(_cleanup24);
+                            for (i, e) in vec24.into_iter().enumerate() {
+                                let base = result24
+                                    .add(i * (4 * ::core::mem::size_of::<*const u8>()));
+                                {
+                                    let super::super::super::wasvy::ecs::types::Component {
+                                        path: path21,
+                                        value: value21,
+                                    } = e;
+                                    let vec22 = path21;
+                                    let ptr22 = vec22.as_ptr().cast::<u8>();
+                                    let len22 = vec22.len();
+                                    *base
+                                        .add(::core::mem::size_of::<*const u8>())
+                                        .cast::<usize>() = len22;
+                                    *base.add(0).cast::<*mut u8>() = ptr22.cast_mut();
+                                    let vec23 = value21;
+                                    let ptr23 = vec23.as_ptr().cast::<u8>();
+                                    let len23 = vec23.len();
+                                    *base
+                                        .add(3 * ::core::mem::size_of::<*const u8>())
+                                        .cast::<usize>() = len23;
+                                    *base
+                                        .add(2 * ::core::mem::size_of::<*const u8>())
+                                        .cast::<*mut u8>() = ptr23.cast_mut();
                                 }
-                                ptr
-                            } else {
-                                ::core::ptr::null_mut()
-                            };
-                            for (i, e) in vec9.into_iter().enumerate() {
-                                let base = result9
+                            }
+                            (
+                                1i32,
+                                ::core::mem::MaybeUninit::new(_rt::as_i64(entity20) as u64),
+                                {
+                                    let mut t = ::core::mem::MaybeUninit::<u64>::uninit();
+                                    t.as_mut_ptr().cast::<*mut u8>().write(result24);
+                                    t
+                                },
+                                len24 as *mut u8,
+                                0usize,
+                            )
+                        }
+                        V38::Remove(e) => {
+                            let super::super::super::wasvy::ecs::types::RemoveCommand {
+                                entity: entity25,
+                                components: components25,
+                            } = e;
+                            let vec27 = components25;
+                            let len27 = vec27.len();
+                            let layout27 = _rt::alloc::Layout::from_size_align(
+                                    vec27.len() * (2 * ::core::mem::size_of::<*const u8>()),
+                                    ::core::mem::size_of::<*const u8>(),
+                                )
+                                .unwrap();
+                            let (result27, _cleanup27) = wit_bindgen_rt::Cleanup::new(
+                                layout27,
+                            );
+                            cleanup_list.extend(_cleanup27);
+                            for (i, e) in vec27.into_iter().enumerate() {
+                                let base = result27
+                                    .add(i * (2 * ::core::mem::size_of::<*const u8>()));
+                                {
+                                    let vec26 = e;
+                                    let ptr26 = vec26.as_ptr().cast::<u8>();
+                                    let len26 = vec26.len();
+                                    *base
+                                        .add(::core::mem::size_of::<*const u8>())
+                                        .cast::<usize>() = len26;
+                                    *base.add(0).cast::<*mut u8>() = ptr26.cast_mut();
+                                }
+                            }
+                            (
+                                2i32,
+                                ::core::mem::MaybeUninit::new(_rt::as_i64(entity25) as u64),
+                                {
+                                    let mut t = ::core::mem::MaybeUninit::<u64>::uninit();
+                                    t.as_mut_ptr().cast::<*mut u8>().write(result27);
+                                    t
+                                },
+                                len27 as *mut u8,
+                                0usize,
+                            )
+                        }
+                        V38::Despawn(e) => {
+                            (
+                                3i32,
+                                ::core::mem::MaybeUninit::new(_rt::as_i64(e) as u64),
+                                ::core::mem::MaybeUninit::<u64>::zeroed(),
+                                ::core::ptr::null_mut(),
+                                0usize,
+                            )
+                        }
+                        V38::InsertResource(e) => {
+                            let super::super::super::wasvy::ecs::types::Component {
+                                path: path28,
+                                value: value28,
+                            } = e;
+                            let vec29 = path28;
+                            let ptr29 = vec29.as_ptr().cast::<u8>();
+                            let len29 = vec29.len();
+                            let vec30 = value28;
+                            let ptr30 = vec30.as_ptr().cast::<u8>();
+                            let len30 = vec30.len();
+                            (
+                                4i32,
+                                {
+                                    let mut t = ::core::mem::MaybeUninit::<u64>::uninit();
+                                    t.as_mut_ptr().cast::<*mut u8>().write(ptr29.cast_mut());
+                                    t
+                                },
+                                ::core::mem::MaybeUninit::new(len29 as i64 as u64),
+                                ptr30.cast_mut(),
+                                len30,
+                            )
+                        }
+                        V38::RemoveResource(e) => {
+                            let vec31 = e;
+                            let ptr31 = vec31.as_ptr().cast::<u8>();
+                            let len31 = vec31.len();
+                            (
+                                5i32,
+                                {
+                                    let mut t = ::core::mem::MaybeUninit::<u64>::uninit();
+                                    t.as_mut_ptr().cast::<*mut u8>().write(ptr31.cast_mut());
+                                    t
+                                },
+                                ::core::mem::MaybeUninit::new(len31 as i64 as u64),
+                                ::core::ptr::null_mut(),
+                                0usize,
+                            )
+                        }
+                        V38::SpawnChild(e) => {
+                            let super::super::super::wasvy::ecs::types::SpawnChildCommand {
+                                parent: parent32,
+                                components: components32,
+                            } = e;
+                            let vec36 = components32;
+                            let len36 = vec36.len();
+                            let layout36 = _rt::alloc::Layout::from_size_align(
+                                    vec36.len() * (4 * ::core::mem::size_of::<*const u8>()),
+                                    ::core::mem::size_of::<*const u8>(),
+                                )
+                                .unwrap();
+                            let (result36, _cleanup36) = wit_bindgen_rt::Cleanup::new(
+                                layout36,
+                            );
+                            cleanup_list.extend(_cleanup36);
+                            for (i, e) in vec36.into_iter().enumerate() {
+                                let base = result36
                                     .add(i * (4 * ::core::mem::size_of::<*const u8>()));
                                 {
                                     let super::super::super::wasvy::ecs::types::Component {
-                                        path: path6,
-                                        value: value6,
+                                        path: path33,
+                                        value: value33,
                                     } = e;
-                                    let vec7 = path6;
-                                    let ptr7 = vec7.as_ptr().cast::<u8>();
-                                    let len7 = vec7.len();
+                                    let vec34 = path33;
+                                    let ptr34 = vec34.as_ptr().cast::<u8>();
+                                    let len34 = vec34.len();
                                     *base
                                         .add(::core::mem::size_of::<*const u8>())
-                                        .cast::<usize>() = len7;
-                                    *base.add(0).cast::<*mut u8>() = ptr7.cast_mut();
-                                    let vec8 = value6;
-                                    let ptr8 = vec8.as_ptr().cast::<u8>();
-                                    let len8 = vec8.len();
+                                        .cast::<usize>() = len34;
+                                    *base.add(0).cast::<*mut u8>() = ptr34.cast_mut();
+                                    let vec35 = value33;
+                                    let ptr35 = vec35.as_ptr().cast::<u8>();
+                                    let len35 = vec35.len();
                                     *base
                                         .add(3 * ::core::mem::size_of::<*const u8>())
-                                        .cast::<usize>() = len8;
+                                        .cast::<usize>() = len35;
                                     *base
                                         .add(2 * ::core::mem::size_of::<*const u8>())
-                                        .cast::<*mut u8>() = ptr8.cast_mut();
+                                        .cast::<*mut u8>() = ptr35.cast_mut();
                                 }
                             }
-                            *base
-                                .add(::core::mem::size_of::<*const u8>())
-                                .cast::<usize>() = len9;
-                            *base.add(0).cast::<*mut u8>() = result9;
-                            *base
-                                .add(2 * ::core::mem::size_of::<*const u8>())
-                                .cast::<i64>() = _rt::as_i64(entity5);
-                            cleanup_list.extend_from_slice(&[(result9, layout9)]);
+                            (
+                                6i32,
+                                ::core::mem::MaybeUninit::new(_rt::as_i64(parent32) as u64),
+                                {
+                                    let mut t = ::core::mem::MaybeUninit::<u64>::uninit();
+                                    t.as_mut_ptr().cast::<*mut u8>().write(result36);
+                                    t
+                                },
+                                len36 as *mut u8,
+                                0usize,
+                            )
                         }
-                    }
+                        V38::SetParent(e) => {
+                            let super::super::super::wasvy::ecs::types::SetParentCommand {
+                                entity: entity37,
+                                parent: parent37,
+                            } = e;
+                            (
+                                7i32,
+                                ::core::mem::MaybeUninit::new(_rt::as_i64(entity37) as u64),
+                                ::core::mem::MaybeUninit::new(_rt::as_i64(parent37) as u64),
+                                ::core::ptr::null_mut(),
+                                0usize,
+                            )
+                        }
+                        V38::RemoveParent(e) => {
+                            (
+                                8i32,
+                                ::core::mem::MaybeUninit::new(_rt::as_i64(e) as u64),
+                                ::core::mem::MaybeUninit::<u64>::zeroed(),
+                                ::core::ptr::null_mut(),
+                                0usize,
+                            )
+                        }
+                    };
                     #[cfg(target_arch = "wasm32")]
                     #[link(wasm_import_module = "wasvy:ecs/functions")]
                     unsafe extern "C" {
                         #[link_name = "this-function-does-nothing"]
-                        fn wit_import11(
+                        fn wit_import40(
+                            _: *mut u8,
+                            _: usize,
+                            _: i64,
                             _: *mut u8,
                             _: usize,
+                            _: i32,
                             _: i64,
                             _: *mut u8,
                             _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: i32,
+                            _: ::core::mem::MaybeUninit<u64>,
+                            _: ::core::mem::MaybeUninit<u64>,
+                            _: *mut u8,
+                            _: usize,
                         );
                     }
                     #[cfg(not(target_arch = "wasm32"))]
-                    unsafe extern "C" fn wit_import11(
+                    unsafe extern "C" fn wit_import40(
+                        _: *mut u8,
+                        _: usize,
+                        _: i64,
                         _: *mut u8,
                         _: usize,
+                        _: i32,
                         _: i64,
                         _: *mut u8,
                         _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: i32,
+                        _: ::core::mem::MaybeUninit<u64>,
+                        _: ::core::mem::MaybeUninit<u64>,
+                        _: *mut u8,
+                        _: usize,
                     ) {
                         unreachable!()
                     }
-                    unsafe {
-                        wit_import11(
-                            result4,
-                            len4,
-                            _rt::as_i64(entity0),
-                            result10,
-                            len10,
-                        )
-                    };
-                    if layout4.size() != 0 {
-                        _rt::alloc::dealloc(result4.cast(), layout4);
-                    }
-                    if layout10.size() != 0 {
-                        _rt::alloc::dealloc(result10.cast(), layout10);
-                    }
-                    for (ptr, layout) in cleanup_list {
-                        if layout.size() != 0 {
-                            _rt::alloc::dealloc(ptr.cast(), layout);
-                        }
-                    }
+                    wit_import40(
+                        result4,
+                        len4,
+                        _rt::as_i64(entity0),
+                        result5,
+                        len5,
+                        result6_0,
+                        result6_1,
+                        ptr7.cast_mut(),
+                        len7,
+                        result15,
+                        len15,
+                        result39_0,
+                        result39_1,
+                        result39_2,
+                        result39_3,
+                        result39_4,
+                    );
                 }
             }
         }
@@ -804,7 +2766,146 @@ mod _rt {
     #![allow(dead_code, clippy::all)]
     pub use alloc_crate::string::String;
     pub use alloc_crate::vec::Vec;
-    pub use alloc_crate::alloc;
+    use core::fmt;
+    use core::marker;
+    use core::sync::atomic::{AtomicU32, Ordering::Relaxed};
+    /// A type which represents a component model resource, either imported or
+    /// exported into this component.
+    ///
+    /// This is a low-level wrapper which handles the lifetime of the resource
+    /// (namely this has a destructor). The `T` provided defines the component model
+    /// intrinsics that this wrapper uses.
+    ///
+    /// One of the chief purposes of this type is to provide `Deref` implementations
+    /// to access the underlying data when it is owned.
+    ///
+    /// This type is primarily used in generated code for exported and imported
+    /// resources.
+    #[repr(transparent)]
+    pub struct Resource<T: WasmResource> {
+        handle: AtomicU32,
+        _marker: marker::PhantomData<T>,
+    }
+    /// A trait which all wasm resources implement, namely providing the ability to
+    /// drop a resource.
+    ///
+    /// This generally is implemented by generated code, not user-facing code.
+    #[allow(clippy::missing_safety_doc)]
+    pub unsafe trait WasmResource {
+        /// Invokes the `[resource-drop]...` intrinsic.
+        unsafe fn drop(handle: u32);
+    }
+    impl<T: WasmResource> Resource<T> {
+        #[doc(hidden)]
+        pub unsafe fn from_handle(handle: u32) -> Self {
+            debug_assert!(handle != 0 && handle != u32::MAX);
+            Self {
+                handle: AtomicU32::new(handle),
+                _marker: marker::PhantomData,
+            }
+        }
+        /// Takes ownership of the handle owned by `resource`.
+        ///
+        /// Note that this ideally would be `into_handle` taking `Resource<T>` by
+        /// ownership. The code generator does not enable that in all situations,
+        /// unfortunately, so this is provided instead.
+        ///
+        /// Also note that `take_handle` is in theory only ever called on values
+        /// owned by a generated function. For example a generated function might
+        /// take `Resource<T>` as an argument but then call `take_handle` on a
+        /// reference to that argument. In that sense the dynamic nature of
+        /// `take_handle` should only be exposed internally to generated code, not
+        /// to user code.
+        #[doc(hidden)]
+        pub fn take_handle(resource: &Resource<T>) -> u32 {
+            resource.handle.swap(u32::MAX, Relaxed)
+        }
+        #[doc(hidden)]
+        pub fn handle(resource: &Resource<T>) -> u32 {
+            resource.handle.load(Relaxed)
+        }
+    }
+    impl<T: WasmResource> fmt::Debug for Resource<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Resource").field("handle", &self.handle).finish()
+        }
+    }
+    impl<T: WasmResource> Drop for Resource<T> {
+        fn drop(&mut self) {
+            unsafe {
+                match self.handle.load(Relaxed) {
+                    u32::MAX => {}
+                    other => T::drop(other),
+                }
+            }
+        }
+    }
+    pub fn as_i32<T: AsI32>(t: T) -> i32 {
+        t.as_i32()
+    }
+    pub trait AsI32 {
+        fn as_i32(self) -> i32;
+    }
+    impl<'a, T: Copy + AsI32> AsI32 for &'a T {
+        fn as_i32(self) -> i32 {
+            (*self).as_i32()
+        }
+    }
+    impl AsI32 for i32 {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
+    impl AsI32 for u32 {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
+    impl AsI32 for i16 {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
+    impl AsI32 for u16 {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
+    impl AsI32 for i8 {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
+    impl AsI32 for u8 {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
+    impl AsI32 for char {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
+    impl AsI32 for usize {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
+    pub unsafe fn string_lift(bytes: Vec<u8>) -> String {
+        if cfg!(debug_assertions) {
+            String::from_utf8(bytes).unwrap()
+        } else {
+            unsafe { String::from_utf8_unchecked(bytes) }
+        }
+    }
     pub unsafe fn invalid_enum_discriminant<T>() -> T {
         if cfg!(debug_assertions) {
             panic!("invalid enum discriminant")
@@ -812,6 +2913,27 @@ mod _rt {
             unsafe { core::hint::unreachable_unchecked() }
         }
     }
+    pub unsafe fn cabi_dealloc(ptr: *mut u8, size: usize, align: usize) {
+        if size == 0 {
+            return;
+        }
+        unsafe {
+            let layout = alloc::Layout::from_size_align_unchecked(size, align);
+            alloc::dealloc(ptr, layout);
+        }
+    }
+    pub unsafe fn bool_lift(val: u8) -> bool {
+        if cfg!(debug_assertions) {
+            match val {
+                0 => false,
+                1 => true,
+                _ => panic!("invalid bool discriminant"),
+            }
+        } else {
+            val != 0
+        }
+    }
+    pub use alloc_crate::alloc;
     pub fn as_i64<T: AsI64>(t: T) -> i64 {
         t.as_i64()
     }
@@ -839,20 +2961,6 @@ mod _rt {
     pub fn run_ctors_once() {
         wit_bindgen_rt::run_ctors_once();
     }
-    pub unsafe fn cabi_dealloc(ptr: *mut u8, size: usize, align: usize) {
-        if size == 0 {
-            return;
-        }
-        let layout = alloc::Layout::from_size_align_unchecked(size, align);
-        alloc::dealloc(ptr, layout);
-    }
-    pub unsafe fn string_lift(bytes: Vec<u8>) -> String {
-        if cfg!(debug_assertions) {
-            String::from_utf8(bytes).unwrap()
-        } else {
-            String::from_utf8_unchecked(bytes)
-        }
-    }
     extern crate alloc as alloc_crate;
 }
 /// Generates `#[unsafe(no_mangle)]` functions to export the specified type as
@@ -884,35 +2992,66 @@ macro_rules! __export_example_impl {
 }
 #[doc(inline)]
 pub(crate) use __export_example_impl as export;
+#[rustfmt::skip]
 #[cfg(target_arch = "wasm32")]
 #[unsafe(
-    link_section = "component-type:wit-bindgen:0.41.0:component:simple:example:encoded world"
+    link_section = "component-type:wit-bindgen:0.44.0:component:simple:example:encoded world"
 )]
 #[doc(hidden)]
 #[allow(clippy::octal_escapes)]
-pub static __WIT_BINDGEN_COMPONENT_TYPE: [u8; 943] = *b"\
-\0asm\x0d\0\x01\0\0\x19\x16wit-component-encoding\x04\0\x07\xb1\x06\x01A\x02\x01\
-A\x13\x01B\x0e\x01w\x04\0\x0ccomponent-id\x03\0\0\x01w\x04\0\x06entity\x03\0\x02\
-\x01r\x02\x04paths\x05values\x04\0\x09component\x03\0\x04\x01ps\x01r\x03\x0acomp\
-onents\x06\x04with\x06\x07without\x06\x04\0\x05query\x03\0\x07\x01p\x05\x01r\x02\
-\x0acomponents\x09\x06entity\x03\x04\0\x12query-result-entry\x03\0\x0a\x01p\x0b\x04\
-\0\x0cquery-result\x03\0\x0c\x03\0\x0fwasvy:ecs/types\x05\0\x02\x03\0\0\x0cquery\
--result\x03\0\x0cquery-result\x03\0\x01\x02\x03\0\0\x06entity\x02\x03\0\0\x05que\
-ry\x02\x03\0\0\x0ccomponent-id\x02\x03\0\0\x09component\x02\x03\0\0\x12query-res\
-ult-entry\x01B\x19\x02\x03\x02\x01\x03\x04\0\x06entity\x03\0\0\x02\x03\x02\x01\x04\
-\x04\0\x05query\x03\0\x02\x02\x03\x02\x01\x05\x04\0\x0ccomponent-id\x03\0\x04\x02\
-\x03\x02\x01\x06\x04\0\x09component\x03\0\x06\x02\x03\x02\x01\x01\x04\0\x0cquery\
--result\x03\0\x08\x02\x03\x02\x01\x07\x04\0\x12query-result-entry\x03\0\x0a\x01p\
-\x03\x01@\x02\x04names\x07queries\x0c\x01\0\x04\0\x0fregister-system\x01\x0d\x01\
-@\x01\x04paths\0\x05\x04\0\x12register-component\x01\x0e\x01k\x05\x01@\x01\x04pa\
-ths\0\x0f\x04\0\x10get-component-id\x01\x10\x01p\x07\x01@\x01\x0acomponents\x11\0\
-\x01\x04\0\x05spawn\x01\x12\x01@\x02\x05entry\x0b\x0cquery-result\x09\x01\0\x04\0\
-\x1athis-function-does-nothing\x01\x13\x03\0\x13wasvy:ecs/functions\x05\x08\x01@\
-\0\0s\x04\0\x0bhello-world\x01\x09\x01p\x02\x01@\x01\x06params\x0a\x01\0\x04\0\x1c\
-print-first-component-system\x01\x0b\x04\0\x19two-components-in-a-query\x01\x0b\x01\
-@\0\x01\0\x04\0\x05setup\x01\x0c\x04\0\x18component:simple/example\x04\0\x0b\x0d\
-\x01\0\x07example\x03\0\0\0G\x09producers\x01\x0cprocessed-by\x02\x0dwit-compone\
-nt\x070.227.1\x10wit-bindgen-rust\x060.41.0";
+pub static __WIT_BINDGEN_COMPONENT_TYPE: [u8; 2263] = *b"\
+\0asm\x0d\0\x01\0\0\x19\x16wit-component-encoding\x04\0\x07\xd9\x10\x01A\x02\x01\
+A\x19\x01B,\x01w\x04\0\x0ccomponent-id\x03\0\0\x01q\x02\x09host-type\0\0\x08expo\
+rted\x01s\0\x04\0\x0eregister-error\x03\0\x02\x01w\x04\0\x06entity\x03\0\x04\x01\
+w\x04\0\x0casset-handle\x03\0\x06\x01r\x03\x05deltau\x07elapsedu\x05framew\x04\0\
+\x0aframe-time\x03\0\x08\x01m\x04\x0anot-loaded\x07loading\x06loaded\x06failed\x04\
+\0\x0aload-state\x03\0\x0a\x01r\x02\x04paths\x05values\x04\0\x09component\x03\0\x0c\
+\x01ps\x01r\x09\x0acomponents\x0e\x08optional\x0e\x03has\x0e\x04with\x0e\x07with\
+out\x0e\x05added\x0e\x07changed\x0e\x07removed\x0e\x09hierarchy\x7f\x04\0\x05que\
+ry\x03\0\x0f\x01p\x0d\x01r\x02\x06entity\x05\x0acomponents\x11\x04\0\x0einsert-c\
+ommand\x03\0\x12\x01r\x02\x06parent\x05\x0acomponents\x11\x04\0\x13spawn-child-c\
+ommand\x03\0\x14\x01r\x02\x06entity\x05\x06parent\x05\x04\0\x12set-parent-comman\
+d\x03\0\x16\x01r\x02\x06entity\x05\x0acomponents\x0e\x04\0\x0eremove-command\x03\
+\0\x18\x01q\x09\x05spawn\x01\x11\0\x06insert\x01\x13\0\x06remove\x01\x19\0\x07de\
+spawn\x01\x05\0\x0finsert-resource\x01\x0d\0\x0fremove-resource\x01s\0\x0bspawn-\
+child\x01\x15\0\x0aset-parent\x01\x17\0\x0dremove-parent\x01\x05\0\x04\0\x07comm\
+and\x03\0\x1a\x04\0\x0aquery-iter\x03\x01\x01k\x0d\x01p\x1d\x01p\x7f\x01k\x05\x01\
+p\x05\x01r\x05\x0acomponents\x1e\x06entity\x05\x03has\x1f\x06parent\x20\x08child\
+ren!\x04\0\x12query-result-entry\x03\0\"\x01p#\x04\0\x0cquery-result\x03\0$\x01h\
+\x1c\x01p#\x01@\x02\x04self&\x01ny\0'\x04\0\x1d[method]query-iter.next-batch\x01\
+(\x01@\x01\x04self&\0y\x04\0\x1c[method]query-iter.remaining\x01)\x03\0\x0fwasvy\
+:ecs/types\x05\0\x02\x03\0\0\x06entity\x02\x03\0\0\x05query\x02\x03\0\0\x0ccompo\
+nent-id\x02\x03\0\0\x0eregister-error\x02\x03\0\0\x09component\x02\x03\0\0\x0cqu\
+ery-result\x02\x03\0\0\x12query-result-entry\x02\x03\0\0\x07command\x02\x03\0\0\x0e\
+insert-command\x02\x03\0\0\x0casset-handle\x02\x03\0\0\x0aload-state\x02\x03\0\0\
+\x0aframe-time\x01BE\x02\x03\x02\x01\x01\x04\0\x06entity\x03\0\0\x02\x03\x02\x01\
+\x02\x04\0\x05query\x03\0\x02\x02\x03\x02\x01\x03\x04\0\x0ccomponent-id\x03\0\x04\
+\x02\x03\x02\x01\x04\x04\0\x0eregister-error\x03\0\x06\x02\x03\x02\x01\x05\x04\0\
+\x09component\x03\0\x08\x02\x03\x02\x01\x06\x04\0\x0cquery-result\x03\0\x0a\x02\x03\
+\x02\x01\x07\x04\0\x12query-result-entry\x03\0\x0c\x02\x03\x02\x01\x08\x04\0\x07\
+command\x03\0\x0e\x02\x03\x02\x01\x09\x04\0\x0einsert-command\x03\0\x10\x02\x03\x02\
+\x01\x0a\x04\0\x0casset-handle\x03\0\x12\x02\x03\x02\x01\x0b\x04\0\x0aload-state\
+\x03\0\x14\x02\x03\x02\x01\x0c\x04\0\x0aframe-time\x03\0\x16\x01p\x03\x01@\x02\x04\
+names\x07queries\x18\x01\0\x04\0\x0fregister-system\x01\x19\x01j\x01\x05\x01\x07\
+\x01@\x01\x04paths\0\x1a\x04\0\x12register-component\x01\x1b\x04\0\x10export-com\
+ponent\x01\x1b\x01k\x05\x01@\x01\x04paths\0\x1c\x04\0\x10get-component-id\x01\x1d\
+\x01p\x09\x01@\x01\x0acomponents\x1e\0\x01\x04\0\x05spawn\x01\x1f\x01@\x02\x06pa\
+rent\x01\x0acomponents\x1e\0\x01\x04\0\x0bspawn-child\x01\x20\x01@\x02\x06entity\
+\x01\x06parent\x01\x01\0\x04\0\x0aset-parent\x01!\x01@\x01\x06entity\x01\x01\0\x04\
+\0\x0dremove-parent\x01\"\x04\0\x11despawn-recursive\x01\"\x01ps\x01k\x1e\x01@\x02\
+\x06entity\x01\x0acomponents#\0$\x04\0\x03get\x01%\x01p\x01\x01p$\x01@\x02\x08en\
+tities&\x0acomponents#\0'\x04\0\x08get-many\x01(\x01@\x02\x06entity\x01\x0acompo\
+nents\x1e\0\x7f\x04\0\x03set\x01)\x01p\x11\x01p\x7f\x01@\x01\x08entities*\0+\x04\
+\0\x08set-many\x01,\x01k\x13\x01@\x01\x04paths\0-\x04\0\x0aload-asset\x01.\x01@\x01\
+\x05asset\x13\0\x15\x04\0\x10asset-load-state\x01/\x01@\0\0\x17\x04\0\x04time\x01\
+0\x01@\0\0w\x04\0\x06random\x011\x01@\x03\x05entry\x0d\x0cquery-result\x0b\x07co\
+mmand\x0f\x01\0\x04\0\x1athis-function-does-nothing\x012\x03\0\x13wasvy:ecs/func\
+tions\x05\x0d\x03\0\x0cquery-result\x03\0\x06\x01@\0\0s\x04\0\x0bhello-world\x01\
+\x0f\x01p\x0e\x01@\x01\x06params\x10\x01\0\x04\0\x1cprint-first-component-system\
+\x01\x11\x04\0\x19two-components-in-a-query\x01\x11\x01@\0\x01\0\x04\0\x05setup\x01\
+\x12\x04\0\x18component:simple/example\x04\0\x0b\x0d\x01\0\x07example\x03\0\0\0G\
+\x09producers\x01\x0cprocessed-by\x02\x0dwit-component\x070.236.1\x10wit-bindgen\
+-rust\x060.44.0";
 #[inline(never)]
 #[doc(hidden)]
 pub fn __link_custom_section_describing_imports() {
@@ -1,19 +1,17 @@
 #[allow(warnings)]
 mod bindings;
 
-use bindings::{
-    Guest,
-    wasvy::{
-        self,
-        ecs::types::{Component, Query},
-    },
-};
+use bindings::Guest;
 use serde::{Deserialize, Serialize};
+use wasvy_guest::prelude::*;
 
-use bevy::{prelude::*, reflect::Type};
+// Bevy's prelude has its own `Query`, `Entity`, `With`, ... so only import the types that are used
+use bevy::prelude::{Reflect, Transform, Vec3};
 
 struct GuestComponent;
 
+wasvy_guest::query_row!(bindings::wasvy::ecs::types::QueryResultEntry);
+
 #[derive(Debug, Reflect, Serialize, Deserialize)]
 pub struct FirstComponent {
     pub first: usize,
@@ -24,97 +22,54 @@ pub struct SecondComponent {
     pub second: usize,
 }
 
+/// The `#[wasvy_guest::system]` attribute generates the `query` records for the parameters,
+/// so registering the system is as easy as `print_first_component_system::register()`.
+///
+/// If for example the parameter was `Query<(&FirstComponent, &SecondComponent)>`
+/// then the host would only send the entities that have both components.
+#[wasvy_guest::system]
+fn print_first_component_system(query: Query<&FirstComponent>) {
+    for (entity, first_component) in &query {
+        println!("Entity: {:?}", entity);
+        println!("Component: {:?}", first_component);
+    }
+}
+
+#[wasvy_guest::system]
+fn two_components_in_a_query(query: Query<(&SecondComponent, &Transform)>) {
+    for (_, (second_component, transform_component)) in &query {
+        println!(
+            "Second Component: {:?}, Transform: {:?}",
+            second_component, transform_component
+        );
+    }
+}
+
 impl Guest for GuestComponent {
     fn hello_world() -> String {
         "Hello, World!".to_string()
     }
 
-    /// The params in this instance will be equal to: `[Query<&FirstComponent>]`
-    /// due to how the system was registered in `setup`.
-    ///
-    /// If for example the system was registered with
-    /// `components: [simple::FirstComponent, simple::SecondComponent]`
-    /// then params would be equal to `[Query<(&FirstComponent, &SecondComponent)>]`
     fn print_first_component_system(params: Vec<bindings::QueryResult>) {
-        let first_component_query = params.first().unwrap();
-        for row in first_component_query {
-            let entity = row.entity;
-            println!("Entity: {:?}", entity);
-            let component = row.components.first().unwrap();
-            let first_component: FirstComponent = serde_json::from_str(&component.value).unwrap();
-            println!("Component: {:?}", first_component);
-        }
+        print_first_component_system::run(params);
     }
 
     fn two_components_in_a_query(params: Vec<bindings::QueryResult>) {
-        let query = params.first().unwrap();
-        for row in query {
-            let second_component_serialized = row.components.first().unwrap();
-            let second_component: SecondComponent =
-                serde_json::from_str(&second_component_serialized.value).unwrap();
-
-            let transform_component_serialized = &row.components[1];
-            let transform_component: Transform =
-                serde_json::from_str(&transform_component_serialized.value).unwrap();
-
-            println!(
-                "Second Component: {:?}, Transform: {:?}",
-                second_component, transform_component
-            );
-        }
+        two_components_in_a_query::run(params);
     }
 
     fn setup() {
-        let first_component_type_path = Type::of::<FirstComponent>().path();
-        let second_component_type_path = Type::of::<SecondComponent>().path();
-        let transform_type_path = Type::of::<Transform>().path();
-
-        let _id1 = wasvy::ecs::functions::register_component(first_component_type_path);
-        let _id2 = wasvy::ecs::functions::register_component(second_component_type_path);
-
-        wasvy::ecs::functions::register_system(
-            "print-first-component-system",
-            &[Query {
-                components: vec![first_component_type_path.to_string()],
-                with: vec![],
-                without: vec![],
-            }],
-        );
-
-        wasvy::ecs::functions::register_system(
-            "two-components-in-a-query",
-            &[Query {
-                components: vec![
-                    second_component_type_path.to_string(),
-                    transform_type_path.to_string(),
-                ],
-                with: vec![],
-                without: vec![],
-            }],
-        );
-
-        let first_serialized = serde_json::to_string(&FirstComponent { first: 18 }).unwrap();
-        let second_serialized = serde_json::to_string(&SecondComponent { second: 18 }).unwrap();
-        let transform_serialized = serde_json::to_string(
-            &Transform::default().with_translation(Vec3::new(10.0, 20.0, 30.0)),
-        )
-        .unwrap();
+        register_component::<FirstComponent>().unwrap();
+        register_component::<SecondComponent>().unwrap();
 
-        wasvy::ecs::functions::spawn(&[Component {
-            path: first_component_type_path.to_string(),
-            value: first_serialized,
-        }]);
+        print_first_component_system::register();
+        two_components_in_a_query::register();
 
-        wasvy::ecs::functions::spawn(&[
-            Component {
-                path: second_component_type_path.to_string(),
-                value: second_serialized,
-            },
-            Component {
-                path: transform_type_path.to_string(),
-                value: transform_serialized,
-            },
-        ]);
+        spawn((FirstComponent { first: 18 },));
+        spawn((
+            SecondComponent { second: 18 },
+            Transform::default().with_translation(Vec3::new(10.0, 20.0, 30.0)),
+        ));
     }
 }
 
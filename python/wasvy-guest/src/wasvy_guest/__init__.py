@@ -0,0 +1,73 @@
+"""
+Typed helpers for writing Wasvy mods in Python.
+
+Instead of building `query` records out of type paths and deserializing every component with
+`json`, describe your components with dataclasses and your systems with type annotations:
+
+    import guest
+    from dataclasses import dataclass
+    from wasvy_guest import Mod, Query, Without
+
+    wasvy = Mod(guest)
+
+    @wasvy.component
+    @dataclass
+    class Player:
+        speed: float
+
+    @wasvy.system
+    def print_players(players: Query[Player, Without[Enemy]]):
+        for entity, player in players:
+            print(entity, player)
+
+    class Guest(guest.Guest):
+        def print_players(self, params):
+            print_players.run(params)
+
+        def setup(self):
+            wasvy.setup()
+            wasvy.spawn(Player(speed=5.0))
+"""
+
+from .commands import Commands
+from .component import deserialize, serialize, type_path
+from .query import (
+    Added,
+    Changed,
+    Children,
+    Entity,
+    Has,
+    LazyQuery,
+    Parent,
+    Query,
+    RemovedComponents,
+    With,
+    Without,
+)
+from .storage import Storage
+from .system import Mod, System
+
+# The version of the `wasvy:ecs` WIT package these helpers are written against
+WIT_PACKAGE = "wasvy:ecs@0.0.3"
+
+__all__ = [
+    "Added",
+    "Changed",
+    "Children",
+    "Commands",
+    "Entity",
+    "Has",
+    "LazyQuery",
+    "Mod",
+    "Parent",
+    "Query",
+    "RemovedComponents",
+    "Storage",
+    "System",
+    "WIT_PACKAGE",
+    "With",
+    "Without",
+    "deserialize",
+    "serialize",
+    "type_path",
+]
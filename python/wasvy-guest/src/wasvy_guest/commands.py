@@ -0,0 +1,83 @@
+"""Queued changes to the world that systems return to the host."""
+
+from typing import Any
+
+from .component import serialize, type_path
+from .query import Entity
+
+
+class Commands:
+    """
+    Queues changes to the world, like Bevy's `Commands`.
+
+    The commands are returned by `System.run` and applied by the host in order once the system
+    returns, so the exported function of the system must return a `list<command>`.
+    """
+
+    def __init__(self, types: Any):
+        self._types = types
+        self.queue: list[Any] = []
+
+    def spawn(self, *components: Any) -> None:
+        """
+        Spawn an entity with the given components.
+
+        The entity is only spawned once the system returns, use `Mod.spawn` if you need its id.
+        """
+        self.queue.append(self._types.Command_Spawn(self._components(components)))
+
+    def insert(self, entity: Entity, *components: Any) -> None:
+        """Add the components to the entity, replacing the ones it already has."""
+        self.queue.append(
+            self._types.Command_Insert(
+                self._types.InsertCommand(int(entity), self._components(components))
+            )
+        )
+
+    def remove(self, entity: Entity, *component_types: type) -> None:
+        self.queue.append(
+            self._types.Command_Remove(
+                self._types.RemoveCommand(
+                    int(entity), [type_path(cls) for cls in component_types]
+                )
+            )
+        )
+
+    def spawn_child(self, parent: Entity, *components: Any) -> None:
+        """
+        Spawn an entity with the given components as a child of `parent`.
+
+        Like `spawn`, use `Mod.spawn_child` if you need its id.
+        """
+        self.queue.append(
+            self._types.Command_SpawnChild(
+                self._types.SpawnChildCommand(int(parent), self._components(components))
+            )
+        )
+
+    def set_parent(self, entity: Entity, parent: Entity) -> None:
+        """Make `entity` a child of `parent`."""
+        self.queue.append(
+            self._types.Command_SetParent(self._types.SetParentCommand(int(entity), int(parent)))
+        )
+
+    def remove_parent(self, entity: Entity) -> None:
+        """Remove the parent of `entity`, making it a root entity."""
+        self.queue.append(self._types.Command_RemoveParent(int(entity)))
+
+    def despawn(self, entity: Entity) -> None:
+        """Despawn the entity along with all its descendants."""
+        self.queue.append(self._types.Command_Despawn(int(entity)))
+
+    def insert_resource(self, resource: Any) -> None:
+        """Insert (or replace) a resource that is registered in the host's type registry."""
+        self.queue.append(self._types.Command_InsertResource(self._component(resource)))
+
+    def remove_resource(self, resource_type: type) -> None:
+        self.queue.append(self._types.Command_RemoveResource(type_path(resource_type)))
+
+    def _component(self, component: Any) -> Any:
+        return self._types.Component(type_path(type(component)), serialize(component))
+
+    def _components(self, components: tuple[Any, ...]) -> list[Any]:
+        return [self._component(component) for component in components]
@@ -0,0 +1,68 @@
+"""Components that are sent to and from the host as JSON."""
+
+import dataclasses
+import json
+import types
+import typing
+from typing import Any, TypeVar
+
+T = TypeVar("T")
+
+_TYPE_PATH = "__wasvy_type_path__"
+
+
+def set_type_path(cls: type, path: str | None) -> None:
+    if not dataclasses.is_dataclass(cls):
+        raise TypeError(f"{cls.__name__} must be a dataclass to be used as a component")
+    setattr(cls, _TYPE_PATH, path or cls.__name__)
+
+
+def type_path(cls: type) -> str:
+    """
+    The key the component is registered under on the host.
+
+    It's the class name unless another path was given to `Mod.component`.
+    """
+    path = cls.__dict__.get(_TYPE_PATH)
+    if path is None:
+        raise TypeError(
+            f"{cls.__name__} isn't a component, decorate it with `@wasvy.component`"
+        )
+    return path
+
+
+def serialize(component: Any) -> str:
+    return json.dumps(dataclasses.asdict(component))
+
+
+def deserialize(cls: type[T], value: str) -> T:
+    return _from_json(cls, json.loads(value))
+
+
+def _from_json(tp: Any, data: Any) -> Any:
+    """Rebuild the nested dataclasses of `tp` from the decoded JSON."""
+    if data is None:
+        return None
+
+    if dataclasses.is_dataclass(tp):
+        hints = typing.get_type_hints(tp)
+        return tp(
+            **{
+                field.name: _from_json(hints[field.name], data[field.name])
+                for field in dataclasses.fields(tp)
+                if field.init and field.name in data
+            }
+        )
+
+    origin = typing.get_origin(tp)
+    args = typing.get_args(tp)
+    if origin is list and args:
+        return [_from_json(args[0], item) for item in data]
+    if origin is dict and len(args) == 2:
+        return {key: _from_json(args[1], item) for key, item in data.items()}
+    if origin in (typing.Union, types.UnionType):
+        options = [arg for arg in args if arg is not type(None)]
+        if len(options) == 1:
+            return _from_json(options[0], data)
+
+    return data
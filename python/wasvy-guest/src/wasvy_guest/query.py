@@ -0,0 +1,280 @@
+"""Typed queries that are passed as parameters to guest systems."""
+
+import typing
+from collections import deque
+from types import UnionType
+from typing import Any, Callable, Generic, Iterator, TypeVar, TypeVarTuple, Union, Unpack
+
+from .component import deserialize, type_path
+
+D = TypeVar("D")
+C = TypeVar("C")
+F = TypeVarTuple("F")
+
+
+class Entity(int):
+    """An entity on the host."""
+
+    def __repr__(self) -> str:
+        return f"Entity({int(self)})"
+
+
+class Has(Generic[C]):
+    """Fetch whether the entity has the component `C`, as a `bool`."""
+
+
+class Parent:
+    """Fetch the parent of the entity, like Bevy's `ChildOf`, as an `Entity` or `None` for root entities."""
+
+
+class Children:
+    """Fetch the children of the entity, like Bevy's `Children`, as a `list[Entity]`."""
+
+
+class With(Generic[C]):
+    """Only match entities that have the component `C`, without fetching it."""
+
+
+class Without(Generic[C]):
+    """Only match entities that don't have the component `C`."""
+
+
+class Added(Generic[C]):
+    """Only match entities whose component `C` was added since the system last ran."""
+
+
+class Changed(Generic[C]):
+    """Only match entities whose component `C` was added or changed since the system last ran."""
+
+
+class Query(Generic[D, Unpack[F]]):
+    """
+    The typed equivalent of Bevy's `Query`, holding the rows the host sent for this system.
+
+    The first argument is the fetched component (or a `tuple` of them), followed by any number
+    of `With`, `Without`, `Added` and `Changed` filters:
+
+        def system(players: Query[tuple[Player, Health], Without[Dead]]):
+            for entity, (player, health) in players:
+                ...
+
+    Optional components (`Health | None`) are `None` for the entities that don't have them,
+    `Has[Dead]` fetches a `bool` instead of the component, and `Parent` and `Children` fetch the
+    entity's place in the hierarchy.
+    """
+
+    def __init__(self, rows: list[tuple[Entity, D]]):
+        self._rows = rows
+
+    def __iter__(self) -> Iterator[tuple[Entity, D]]:
+        return iter(self._rows)
+
+    def __len__(self) -> int:
+        return len(self._rows)
+
+    def get(self, entity: Entity) -> D | None:
+        """Returns the components of the given entity, if it's matched by this query."""
+        for row_entity, item in self._rows:
+            if row_entity == entity:
+                return item
+        return None
+
+
+class LazyQuery(Generic[D, Unpack[F]]):
+    """
+    A `Query` that reads its rows from the host while it's iterated, instead of receiving all of
+    them before the system runs.
+
+    The host only serializes the rows that are read, so systems that don't read every row of a
+    large query (e.g. stopping at the first match) don't pay for the others. A system that takes a
+    `LazyQuery` gets a `list<query-iter>` instead of a `list<query-result>`, and its other
+    parameters read all of their rows up front:
+
+        @wasvy.system
+        def find_boss(enemies: LazyQuery[Enemy]):
+            for entity, enemy in enemies:
+                if enemy.boss:
+                    break
+
+    Rows are read one at a time, set `batch_size` to read more of them at once in systems that
+    read most of their rows.
+    """
+
+    def __init__(self, query_iter: Any, item: Callable[[Any], tuple[Entity, D]]):
+        self._iter = query_iter
+        self._item = item
+        # The rows that were read from the host but weren't returned yet
+        self._read: deque[tuple[Entity, D]] = deque()
+        self.batch_size = 1
+
+    def __iter__(self) -> Iterator[tuple[Entity, D]]:
+        return self
+
+    def __next__(self) -> tuple[Entity, D]:
+        if not self._read:
+            self._read.extend(self._item(row) for row in self._iter.next_batch(self.batch_size))
+        if not self._read:
+            raise StopIteration
+        return self._read.popleft()
+
+    def __len__(self) -> int:
+        """The number of rows that weren't returned yet."""
+        return len(self._read) + self._iter.remaining()
+
+
+def _read_all(query_iter: Any) -> list[Any]:
+    """All the rows of a `query-iter`."""
+    return query_iter.next_batch(2**32 - 1)
+
+
+class QuerySpec:
+    """What a `Query[...]` or `LazyQuery[...]` annotation asks the host for, and how to read its rows."""
+
+    def __init__(self, annotation: Any):
+        data, *filters = typing.get_args(annotation)
+
+        self.lazy = typing.get_origin(annotation) is LazyQuery
+        self.is_tuple = typing.get_origin(data) is tuple
+        # Each fetched item, with how the host sends it: "component", "optional", "has", "parent"
+        # or "children"
+        self.data: list[tuple[str, type]] = [
+            _slot(item) for item in (typing.get_args(data) if self.is_tuple else [data])
+        ]
+        self.with_: list[str] = []
+        self.without: list[str] = []
+        self.added: list[str] = []
+        self.changed: list[str] = []
+
+        for query_filter in filters:
+            origin = typing.get_origin(query_filter)
+            (component,) = typing.get_args(query_filter)
+            if origin is With:
+                self.with_.append(type_path(component))
+            elif origin is Without:
+                self.without.append(type_path(component))
+            elif origin is Added:
+                self.added.append(type_path(component))
+            elif origin is Changed:
+                self.changed.append(type_path(component))
+            else:
+                raise TypeError(
+                    f"{query_filter} isn't a query filter, use `With`, `Without`, `Added` or `Changed`"
+                )
+
+    def descriptor(self, types: Any) -> Any:
+        """The `query` record that makes the host send the rows of this query."""
+        def paths(kind: str) -> list[str]:
+            return [type_path(cls) for slot, cls in self.data if slot == kind]
+
+        return types.Query(
+            paths("component"),
+            paths("optional"),
+            paths("has"),
+            self.with_,
+            self.without,
+            self.added,
+            self.changed,
+            [],
+            any(slot in ("parent", "children") for slot, _ in self.data),
+        )
+
+    def from_rows(self, rows: list[Any]) -> Query | LazyQuery:
+        """Deserialize the `query-result-entry` rows the host sent for this query."""
+        if self.lazy:
+            return LazyQuery(_Rows(rows), self._item)
+        return Query([self._item(row) for row in rows])
+
+    def from_iter(self, query_iter: Any) -> Query | LazyQuery:
+        """Read the rows of this query from the `query-iter` the host sent for it."""
+        if self.lazy:
+            return LazyQuery(query_iter, self._item)
+        return self.from_rows(_read_all(query_iter))
+
+    def _item(self, row: Any) -> tuple[Entity, Any]:
+        """Deserialize one `query-result-entry`."""
+        # Optional components are sent after the required ones
+        required = sum(1 for slot, _ in self.data if slot == "component")
+        components = iter(row.components[:required])
+        optional = iter(row.components[required:])
+        has = iter(row.has)
+
+        values = []
+        for slot, cls in self.data:
+            if slot == "has":
+                values.append(next(has))
+            elif slot == "parent":
+                values.append(None if row.parent is None else Entity(row.parent))
+            elif slot == "children":
+                values.append([Entity(child) for child in row.children])
+            else:
+                component = next(components if slot == "component" else optional)
+                values.append(None if component is None else deserialize(cls, component.value))
+        return Entity(row.entity), tuple(values) if self.is_tuple else values[0]
+
+
+class _Rows:
+    """A `query-iter` over rows that were all sent up front."""
+
+    def __init__(self, rows: list[Any]):
+        self._rows = rows
+        self._position = 0
+
+    def next_batch(self, n: int) -> list[Any]:
+        batch = self._rows[self._position : self._position + n]
+        self._position += len(batch)
+        return batch
+
+    def remaining(self) -> int:
+        return len(self._rows) - self._position
+
+
+def _slot(item: Any) -> tuple[str, type]:
+    if item is Parent:
+        return "parent", item
+    if item is Children:
+        return "children", item
+    if typing.get_origin(item) is Has:
+        return "has", typing.get_args(item)[0]
+    if typing.get_origin(item) in (Union, UnionType):
+        args = [arg for arg in typing.get_args(item) if arg is not type(None)]
+        if len(args) == 1 and len(typing.get_args(item)) == 2:
+            return "optional", args[0]
+        raise TypeError(f"{item} isn't a component, only `Component | None` unions are supported")
+    return "component", item
+
+
+class RemovedComponents(Generic[C]):
+    """
+    The entities that had the component `C` removed (or were despawned) since the system last
+    ran, like Bevy's `RemovedComponents`:
+
+        def system(removed: RemovedComponents[Health]):
+            for entity in removed:
+                ...
+    """
+
+    def __init__(self, entities: list[Entity]):
+        self._entities = entities
+
+    def __iter__(self) -> Iterator[Entity]:
+        return iter(self._entities)
+
+    def __len__(self) -> int:
+        return len(self._entities)
+
+
+class RemovedSpec:
+    """What a `RemovedComponents[...]` annotation asks the host for."""
+
+    def __init__(self, annotation: Any):
+        (component,) = typing.get_args(annotation)
+        self.path = type_path(component)
+
+    def descriptor(self, types: Any) -> Any:
+        return types.Query([], [], [], [], [], [], [], [self.path], False)
+
+    def from_rows(self, rows: list[Any]) -> RemovedComponents:
+        return RemovedComponents([Entity(row.entity) for row in rows])
+
+    def from_iter(self, query_iter: Any) -> RemovedComponents:
+        return self.from_rows(_read_all(query_iter))
@@ -0,0 +1,39 @@
+"""Data that the mod keeps between sessions, saved by the host in a file per mod."""
+
+import importlib
+import json
+from typing import Any
+
+
+class Storage:
+    """
+    The key-value store of the mod, with values stored as JSON:
+
+        progress = wasvy.storage.load("progress") or {"level": 0}
+        progress["level"] += 1
+        wasvy.storage.save("progress", progress)
+
+    The world of the mod must `import wasvy:ecs/storage;`. Saving raises the `Err` exception of the
+    bindings with a `StorageError` if the mod would store more than its quota.
+    """
+
+    def __init__(self, bindings: Any):
+        # Only worlds that import `wasvy:ecs/storage` have the module
+        self._storage = importlib.import_module(f"{bindings.__name__}.imports.storage")
+
+    def load(self, key: str) -> Any | None:
+        """The value saved under `key`, or `None` if there is none."""
+        value = self._storage.get(key)
+        return None if value is None else json.loads(value)
+
+    def save(self, key: str, value: Any) -> None:
+        """Save `value` under `key`, replacing the previous value."""
+        self._storage.set(key, json.dumps(value))
+
+    def remove(self, key: str) -> None:
+        """Remove the value saved under `key`, if there is one."""
+        self._storage.remove(key)
+
+    def keys(self) -> list[str]:
+        """The keys that have a value saved."""
+        return self._storage.keys()
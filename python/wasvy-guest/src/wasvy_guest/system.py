@@ -0,0 +1,255 @@
+"""Registering components and systems with the host."""
+
+import importlib
+import typing
+from typing import Any, Callable, TypeVar
+
+from .commands import Commands
+from .component import deserialize, serialize, set_type_path, type_path
+from .query import Entity, LazyQuery, Query, QuerySpec, RemovedComponents, RemovedSpec
+from .storage import Storage
+
+T = TypeVar("T", bound=type)
+
+
+class System:
+    """A function turned into a Wasvy system by `Mod.system`."""
+
+    def __init__(self, mod: "Mod", function: Callable[..., None]):
+        self._mod = mod
+        self.function = function
+        # The name of the WIT export, e.g. `move_players` is exported as `move-players`
+        self.name = function.__name__.replace("_", "-")
+
+        hints = typing.get_type_hints(function)
+        self._params: list[QuerySpec | RemovedSpec | None] = []
+        for name in function.__code__.co_varnames[: function.__code__.co_argcount]:
+            annotation = hints.get(name)
+            if annotation is Commands:
+                self._params.append(None)
+            elif typing.get_origin(annotation) in (Query, LazyQuery):
+                self._params.append(QuerySpec(annotation))
+            elif typing.get_origin(annotation) is RemovedComponents:
+                self._params.append(RemovedSpec(annotation))
+            else:
+                raise TypeError(
+                    f"the parameter `{name}` of the system `{function.__name__}` must be "
+                    "annotated with `Query[...]`, `LazyQuery[...]`, `RemovedComponents[...]` or "
+                    "`Commands`"
+                )
+        # Systems with a `LazyQuery` are exported with `list<query-iter>`
+        self.lazy = any(isinstance(spec, QuerySpec) and spec.lazy for spec in self._params)
+
+    def queries(self) -> list[Any]:
+        """The `query` records that are registered for this system, one per query parameter."""
+        return [
+            spec.descriptor(self._mod.types) for spec in self._params if spec is not None
+        ]
+
+    def register(self) -> None:
+        self._mod.functions.register_system(self.name, self.queries())
+
+    def run(self, params: list[Any]) -> list[Any]:
+        """
+        Deserialize the query results, call the function and return the commands it queued.
+
+        Call it from the exported function of the system, returning the result if the export
+        returns a `list<command>`. The params are a `list<query-iter>` if the system takes a
+        `LazyQuery`, and a `list<query-result>` otherwise.
+        """
+        results = iter(params)
+        commands = Commands(self._mod.types)
+
+        args = []
+        for spec in self._params:
+            if spec is None:
+                args.append(commands)
+            else:
+                rows = next(results, None)
+                if rows is None:
+                    raise ValueError(
+                        f"the host sent less query results than `{self.name}` has queries"
+                    )
+                args.append(spec.from_iter(rows) if self.lazy else spec.from_rows(rows))
+
+        self.function(*args)
+        return commands.queue
+
+    def __call__(self, *args: Any, **kwargs: Any) -> None:
+        self.function(*args, **kwargs)
+
+
+class Mod:
+    """
+    The entry point of the SDK, bound to the bindings componentize-py generated for your mod.
+
+        import guest
+        from wasvy_guest import Mod
+
+        wasvy = Mod(guest)
+    """
+
+    def __init__(self, bindings: Any):
+        # The generated package doesn't import `imports.functions` by itself
+        self.functions = importlib.import_module(f"{bindings.__name__}.imports.functions")
+        self.types = importlib.import_module(f"{bindings.__name__}.imports.types")
+        self._err = importlib.import_module(f"{bindings.__name__}.types").Err
+        self._bindings = bindings
+        self._storage: Storage | None = None
+        self._components: list[tuple[type, bool]] = []
+        self._systems: list[System] = []
+
+    @property
+    def storage(self) -> Storage:
+        """The data the mod keeps between sessions, see `Storage`."""
+        if self._storage is None:
+            self._storage = Storage(self._bindings)
+        return self._storage
+
+    def component(
+        self, cls: T | None = None, *, path: str | None = None, export: bool = False
+    ) -> Any:
+        """
+        Turn a dataclass into a component that is registered with the host in `setup`.
+
+        The type path defaults to the class name. For host components like `Transform` it must
+        be the path of the Rust type, e.g. `@wasvy.component(path="bevy_transform::components::transform::Transform")`.
+
+        Components are only known by this mod unless `export` is true, which shares them with the
+        other mods.
+        """
+
+        def wrap(cls: T) -> T:
+            set_type_path(cls, path)
+            self._components.append((cls, export))
+            return cls
+
+        return wrap if cls is None else wrap(cls)
+
+    def system(self, function: Callable[..., None]) -> System:
+        """Turn a function with `Query` (and `Commands`) parameters into a system."""
+        system = System(self, function)
+        self._systems.append(system)
+        return system
+
+    def setup(self) -> None:
+        """Register all the components and systems, call it in the `setup` export."""
+        for cls, export in self._components:
+            register = (
+                self.functions.export_component if export else self.functions.register_component
+            )
+            try:
+                register(type_path(cls))
+            except self._err as err:
+                # Host components like `Transform` are declared with `@wasvy.component` too
+                if not isinstance(err.value, self.types.RegisterError_HostType):
+                    raise
+        for system in self._systems:
+            system.register()
+
+    def spawn(self, *components: Any) -> Entity:
+        """Spawn an entity with the given components right away."""
+        return Entity(self.functions.spawn(self._components_of(components)))
+
+    def spawn_child(self, parent: int, *components: Any) -> Entity:
+        """Spawn an entity with the given components as a child of `parent` right away."""
+        return Entity(self.functions.spawn_child(parent, self._components_of(components)))
+
+    def set_parent(self, entity: int, parent: int) -> None:
+        """Make `entity` a child of `parent`, moving it out of the children of its current parent."""
+        self.functions.set_parent(entity, parent)
+
+    def remove_parent(self, entity: int) -> None:
+        """Remove the parent of `entity`, making it a root entity."""
+        self.functions.remove_parent(entity)
+
+    def despawn_recursive(self, entity: int) -> None:
+        """Despawn `entity` along with all its descendants."""
+        self.functions.despawn_recursive(entity)
+
+    def get(self, entity: int, *components: type) -> Any | None:
+        """
+        Look up the components of an entity, e.g. `wasvy.get(follow.target, Transform)`.
+
+        A single component is returned as is, several of them as a tuple.
+
+        Inside a system, only the components that one of its queries reads can be looked up, and
+        the entity must match that query. Returns `None` if the entity doesn't have all the
+        components, or the system isn't allowed to read them.
+        """
+        values = self.functions.get(entity, [type_path(cls) for cls in components])
+        return None if values is None else self._deserialize(components, values)
+
+    def get_many(self, entities: list[int], *components: type) -> list[Any | None]:
+        """`get` for many entities at once, with a result for each entity."""
+        results = self.functions.get_many(list(entities), [type_path(cls) for cls in components])
+        return [
+            None if values is None else self._deserialize(components, values)
+            for values in results
+        ]
+
+    def set(self, entity: int, *components: Any) -> bool:
+        """
+        Replace the components of an entity, following the same access rules as `get`.
+
+        Like `Commands.insert`, the change is applied after the system returns. Returns `False`
+        (and changes nothing) if `get` wouldn't return the components, the host doesn't allow the
+        mod to change one of them, or one of the values doesn't match its type.
+        """
+        return self.functions.set(entity, self._components_of(components))
+
+    def set_many(self, entities: dict[int, tuple[Any, ...]]) -> list[bool]:
+        """`set` for many entities at once, with a result for each entity."""
+        return self.functions.set_many(
+            [
+                self.types.InsertCommand(entity, self._components_of(components))
+                for entity, components in entities.items()
+            ]
+        )
+
+    def load_asset(self, path: str) -> int | None:
+        """
+        Start loading the asset at `path`, relative to the mod's asset directory on the host (e.g.
+        `mods/my_mod/` for `mods/my_mod.wasm`).
+
+        Put the returned handle in a component wherever the Rust type has a `Handle<T>`, e.g.
+        `SceneRoot(wasvy.load_asset("ship.glb#Scene0"))`. Returns `None` if the path leaves the
+        mod's asset directory.
+        """
+        return self.functions.load_asset(path)
+
+    def asset_load_state(self, asset: int) -> Any:
+        """Where an asset loaded with `load_asset` is in its loading process, a `LoadState`."""
+        return self.functions.asset_load_state(asset)
+
+    def time(self) -> Any:
+        """
+        The time of the current frame, a `FrameTime` with the `delta` and `elapsed` seconds and
+        the number of the `frame`.
+
+        Use it instead of the `time` module, whose clocks are fixed when the host runs mods in
+        deterministic mode. In that mode it advances by a fixed step every frame.
+        """
+        return self.functions.time()
+
+    def random(self) -> int:
+        """
+        A random 64-bit number from the mod's own generator on the host.
+
+        Use it instead of the `random` module when the host runs mods in deterministic mode: the
+        generator is seeded by the host, so every machine gets the same numbers as long as the mod
+        asks for them in the same order.
+        """
+        return self.functions.random()
+
+    def _components_of(self, components: tuple[Any, ...]) -> list[Any]:
+        return [
+            self.types.Component(type_path(type(component)), serialize(component))
+            for component in components
+        ]
+
+    @staticmethod
+    def _deserialize(components: tuple[type, ...], values: list[Any]) -> Any:
+        # Like a query, a single component isn't wrapped in a tuple
+        items = tuple(deserialize(cls, value.value) for cls, value in zip(components, values))
+        return items[0] if len(items) == 1 else items
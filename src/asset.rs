@@ -1,17 +1,35 @@
+use std::path::PathBuf;
+
 use bevy::{
-    asset::{AssetLoader, LoadContext, io::Reader},
+    asset::{
+        AssetLoader, AssetPath, LoadContext, ReadAssetBytesError,
+        io::{AssetReaderError, Reader},
+    },
     prelude::*,
 };
 use thiserror::Error;
 use wasmtime::Engine;
 
+use crate::{
+    mod_package::ModManifest,
+    mod_trust::{ModSignature, ModTrust},
+};
+
 #[derive(Asset, TypePath, Clone)]
 pub struct WasmComponentAsset {
     pub component: wasmtime::component::Component,
+    /// The manifest of the mod, if it was loaded from a [mod package](crate::mod_package).
+    pub manifest: Option<ModManifest>,
+    /// Whether the mod is signed by a trusted key, see [`mod_trust`](crate::mod_trust).
+    pub signature: ModSignature,
+    /// Whether the mod runs in the stricter sandbox of
+    /// [`UnsignedModPolicy::Restrict`](crate::mod_trust::UnsignedModPolicy::Restrict).
+    pub restricted: bool,
 }
 
 pub struct WasmComponentAssetLoader {
     pub engine: Engine,
+    pub trust: ModTrust,
 }
 
 /// Possible errors that can be produced by [`WasmComponentAssetLoader`]
@@ -21,6 +39,42 @@ pub enum WasmComponentAssetLoaderError {
     /// An [IO](std::io) Error
     #[error("Could not load asset: {0}")]
     Io(#[from] std::io::Error),
+    /// The bytes aren't a valid WASM component
+    #[error("Could not compile the WASM component: {0:?}")]
+    Compile(wasmtime::Error),
+    /// The signature file next to the mod couldn't be read
+    #[error("Could not read the signature of the mod: {0}")]
+    Signature(#[from] ReadAssetBytesError),
+    /// The mod isn't signed by a trusted key, and the game refuses those mods
+    #[error("The mod isn't signed by a trusted key ({0:?})")]
+    NotTrusted(ModSignature),
+}
+
+impl WasmComponentAssetLoader {
+    /// Compile the bytes of a component, unless the game refuses the mod because of its signature.
+    ///
+    /// `signature` is the content of the signature file of the mod, which signs `signed`.
+    pub(crate) fn compile(
+        &self,
+        bytes: &[u8],
+        signed: &[u8],
+        signature: Option<&[u8]>,
+    ) -> Result<WasmComponentAsset, WasmComponentAssetLoaderError> {
+        let signature = self.trust.verify(signed, signature);
+        if !self.trust.allows(signature) {
+            return Err(WasmComponentAssetLoaderError::NotTrusted(signature));
+        }
+
+        let component = wasmtime::component::Component::from_binary(&self.engine, bytes)
+            .map_err(WasmComponentAssetLoaderError::Compile)?;
+
+        Ok(WasmComponentAsset {
+            component,
+            manifest: None,
+            signature,
+            restricted: self.trust.restricts(signature),
+        })
+    }
 }
 
 impl AssetLoader for WasmComponentAssetLoader {
@@ -32,16 +86,33 @@ impl AssetLoader for WasmComponentAssetLoader {
         &self,
         reader: &mut dyn Reader,
         _settings: &Self::Settings,
-        _load_context: &mut LoadContext<'_>,
+        load_context: &mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = vec![];
-        reader.read_to_end(&mut bytes).await.unwrap();
-        let component = wasmtime::component::Component::from_binary(&self.engine, &bytes).unwrap();
+        reader.read_to_end(&mut bytes).await?;
+        let signature = read_signature(load_context).await?;
 
-        Ok(WasmComponentAsset { component })
+        self.compile(&bytes, &bytes, signature.as_deref())
     }
 
     fn extensions(&self) -> &[&str] {
         &["wasm"]
     }
 }
+
+/// The signature file next to the mod (e.g. `space_ship.wasm.sig`), if it has one.
+async fn read_signature(
+    load_context: &mut LoadContext<'_>,
+) -> Result<Option<Vec<u8>>, ReadAssetBytesError> {
+    let path = load_context.asset_path();
+    let mut signature_path = path.path().as_os_str().to_owned();
+    signature_path.push(".sig");
+    let signature_path =
+        AssetPath::from(PathBuf::from(signature_path)).with_source(path.source().clone_owned());
+
+    match load_context.read_asset_bytes(signature_path).await {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(ReadAssetBytesError::AssetReaderError(AssetReaderError::NotFound(_))) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
@@ -0,0 +1,309 @@
+//! The `mod://` asset source, which keeps the assets bundled with mods apart from the game's.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+use bevy::{
+    asset::{
+        AssetPath,
+        io::{
+            AssetReader, AssetReaderError, AssetSource, AssetSourceBuilder, AssetSourceId,
+            ErasedAssetReader, PathStream, Reader, VecReader,
+        },
+    },
+    prelude::*,
+    tasks::futures_lite::{StreamExt, stream},
+};
+
+use thiserror::Error;
+
+use crate::{mod_package::PACKAGE_EXTENSION, mods::Mod};
+
+/// The name of the asset source of mods, e.g. `mod://space_ship/ship.glb` is the `ship.glb` of the
+/// mod `space_ship.wasm`.
+pub const MOD_ASSET_SOURCE: &str = "mod";
+
+/// Adds the `mod://` asset source, where every mod gets a directory named after its id.
+///
+/// The assets of a mod are read from the directory next to it with the name of its file, e.g.
+/// `mod://space_ship/ship.glb` is read from `mods/space_ship/ship.glb` for `mods/space_ship.wasm`,
+/// or from the mod's package (see [`mod_package`](crate::mod_package)) for `mods/space_ship.wasvy`.
+/// Mods are added to the source when they are loaded with [`Mods`](crate::mods::Mods), and the
+/// `load-asset` host function loads from it.
+///
+/// Bevy only accepts asset sources before the [`AssetPlugin`] is built, so this plugin must be
+/// added before the `DefaultPlugins`:
+///
+/// ```rust,ignore
+/// App::new()
+///     .add_plugins(ModAssetSourcePlugin::default())
+///     .add_plugins(DefaultPlugins)
+///     .add_plugins(ModloaderPlugin::default())
+/// ```
+pub struct ModAssetSourcePlugin {
+    /// The directory of the game's assets that mods are loaded from, like [`AssetPlugin::file_path`].
+    pub file_path: String,
+}
+
+impl Default for ModAssetSourcePlugin {
+    fn default() -> Self {
+        Self {
+            file_path: AssetPlugin::default().file_path,
+        }
+    }
+}
+
+impl Plugin for ModAssetSourcePlugin {
+    fn build(&self, app: &mut App) {
+        let roots = app
+            .world_mut()
+            .get_resource_or_init::<ModAssetRoots>()
+            .clone();
+        let mut game_reader = AssetSource::get_default_reader(self.file_path.clone());
+
+        app.register_asset_source(
+            MOD_ASSET_SOURCE,
+            AssetSourceBuilder::default().with_reader(move || {
+                Box::new(ModAssetReader {
+                    roots: roots.clone(),
+                    game_reader: game_reader(),
+                })
+            }),
+        );
+    }
+}
+
+/// Where the assets of each loaded mod are, keyed by the id of the mod.
+///
+/// Mods are identified by their id everywhere (their components, storage and permissions), so
+/// only one mod with a given id can be loaded at a time.
+#[derive(Resource, Clone, Default)]
+pub(crate) struct ModAssetRoots(Arc<RwLock<HashMap<String, RegisteredMod>>>);
+
+#[derive(Clone)]
+struct RegisteredMod {
+    /// The path of the mod, which tells apart mods with the same id.
+    path: PathBuf,
+    root: ModAssetRoot,
+}
+
+/// Where the assets of a mod are.
+#[derive(Clone)]
+enum ModAssetRoot {
+    /// A directory in the game's assets.
+    Directory(PathBuf),
+    /// The files of a [mod package](crate::mod_package), which were checked against its manifest.
+    Package(Arc<HashMap<PathBuf, Vec<u8>>>),
+}
+
+/// A mod has the same id as another mod that is already loaded.
+#[derive(Debug, Error)]
+#[error("The mod {path} has the id {id} of {existing}, which is already loaded")]
+pub struct ModIdConflict {
+    pub id: String,
+    pub path: PathBuf,
+    pub existing: PathBuf,
+}
+
+impl ModAssetRoots {
+    /// Add the mod at `path` to the `mod://` asset source.
+    pub(crate) fn register(&self, path: &AssetPath) -> Result<(), ModIdConflict> {
+        // Packages are added by their loader once their files are read
+        if path.get_full_extension().as_deref() == Some(PACKAGE_EXTENSION) {
+            return Ok(());
+        }
+        if *path.source() != AssetSourceId::Default {
+            debug!(
+                "The mod {path} isn't in the default asset source, it can't have bundled assets"
+            );
+            return Ok(());
+        }
+
+        self.insert(
+            path,
+            ModAssetRoot::Directory(path.path().with_extension("")),
+        )
+    }
+
+    /// Add the mod package at `path` to the `mod://` asset source, with the given files.
+    pub(crate) fn register_package(
+        &self,
+        path: &AssetPath,
+        files: HashMap<PathBuf, Vec<u8>>,
+    ) -> Result<(), ModIdConflict> {
+        self.insert(path, ModAssetRoot::Package(Arc::new(files)))
+    }
+
+    fn insert(&self, path: &AssetPath, root: ModAssetRoot) -> Result<(), ModIdConflict> {
+        let Some(id) = mod_id(path) else {
+            return Ok(());
+        };
+
+        let mut roots = self.0.write().unwrap();
+        if let Some(existing) = roots
+            .get(&id)
+            .filter(|existing| existing.path != path.path())
+        {
+            return Err(ModIdConflict {
+                id,
+                path: path.path().to_path_buf(),
+                existing: existing.path.clone(),
+            });
+        }
+
+        // A mod that is loaded again (e.g. a package that changed) replaces its files
+        let path = path.path().to_path_buf();
+        roots.insert(id, RegisteredMod { path, root });
+        Ok(())
+    }
+
+    /// Remove the mod at `path` from the `mod://` asset source.
+    pub(crate) fn unregister(&self, path: &AssetPath) {
+        let Some(id) = mod_id(path) else {
+            return;
+        };
+
+        let mut roots = self.0.write().unwrap();
+        if roots
+            .get(&id)
+            .is_some_and(|existing| existing.path == path.path())
+        {
+            roots.remove(&id);
+        }
+    }
+
+    /// The root of the mod of a `mod://` asset, and the path of the asset in it.
+    fn resolve(&self, path: &Path) -> Result<(ModAssetRoot, PathBuf), AssetReaderError> {
+        let mut components = path.components();
+        let id = components.next().and_then(|id| id.as_os_str().to_str());
+        let roots = self.0.read().unwrap();
+        let Some(registered) = id.and_then(|id| roots.get(id)) else {
+            return Err(AssetReaderError::NotFound(path.to_path_buf()));
+        };
+
+        Ok((registered.root.clone(), components.as_path().to_path_buf()))
+    }
+}
+
+/// Removes a mod from the `mod://` asset source once no entity uses the mod anymore.
+pub(crate) fn unregister_mod_asset_root(
+    remove: On<Remove, Mod>,
+    roots: Res<ModAssetRoots>,
+    mods: Query<&Mod>,
+) {
+    let Ok(removed) = mods.get(remove.entity) else {
+        return;
+    };
+    let Some(path) = removed.asset.path() else {
+        return;
+    };
+    let mod_id = removed.asset.id();
+    if mods.iter().filter(|m| m.asset.id() == mod_id).count() == 1 {
+        roots.unregister(path);
+    }
+}
+
+/// The id of the mod at `path` in the `mod://` asset source, which is the name of its file
+/// without the extension.
+pub(crate) fn mod_id(path: &AssetPath) -> Option<String> {
+    path.path()
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .map(|stem| stem.to_string())
+}
+
+/// Reads the assets of mods from the game's assets.
+struct ModAssetReader {
+    roots: ModAssetRoots,
+    game_reader: Box<dyn ErasedAssetReader>,
+}
+
+impl AssetReader for ModAssetReader {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        let bytes = match self.roots.resolve(path)? {
+            (ModAssetRoot::Directory(directory), asset) => {
+                let game_path = directory.join(asset);
+                let mut reader = self.game_reader.read(&game_path).await?;
+                let mut bytes = Vec::new();
+                reader.read_to_end(&mut bytes).await?;
+                bytes
+            }
+            (ModAssetRoot::Package(files), asset) => files
+                .get(&asset)
+                .cloned()
+                .ok_or_else(|| AssetReaderError::NotFound(path.to_path_buf()))?,
+        };
+        Ok(VecReader::new(bytes))
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        let bytes = match self.roots.resolve(path)? {
+            (ModAssetRoot::Directory(directory), asset) => {
+                self.game_reader
+                    .read_meta_bytes(&directory.join(asset))
+                    .await?
+            }
+            (ModAssetRoot::Package(files), asset) => {
+                let mut meta = asset.into_os_string();
+                meta.push(".meta");
+                files
+                    .get(Path::new(&meta))
+                    .cloned()
+                    .ok_or_else(|| AssetReaderError::NotFound(path.to_path_buf()))?
+            }
+        };
+        Ok(VecReader::new(bytes))
+    }
+
+    async fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> Result<Box<PathStream>, AssetReaderError> {
+        let id = path
+            .components()
+            .next()
+            .map(|id| PathBuf::from(id.as_os_str()))
+            .unwrap_or_default();
+
+        match self.roots.resolve(path)? {
+            (ModAssetRoot::Directory(directory), asset) => {
+                let paths = self
+                    .game_reader
+                    .read_directory(&directory.join(asset))
+                    .await?;
+
+                // The game's reader returns paths in the game's assets
+                Ok(Box::new(paths.map(move |path| {
+                    let relative = path.strip_prefix(&directory).unwrap_or(&path);
+                    id.join(relative)
+                })))
+            }
+            (ModAssetRoot::Package(files), directory) => {
+                let entries: BTreeSet<PathBuf> = files
+                    .keys()
+                    .filter_map(|file| file.strip_prefix(&directory).ok()?.components().next())
+                    .map(|entry| id.join(&directory).join(entry))
+                    .collect();
+                if entries.is_empty() {
+                    return Err(AssetReaderError::NotFound(path.to_path_buf()));
+                }
+
+                Ok(Box::new(stream::iter(entries)))
+            }
+        }
+    }
+
+    async fn is_directory<'a>(&'a self, path: &'a Path) -> Result<bool, AssetReaderError> {
+        match self.roots.resolve(path)? {
+            (ModAssetRoot::Directory(directory), asset) => {
+                self.game_reader.is_directory(&directory.join(asset)).await
+            }
+            (ModAssetRoot::Package(files), asset) => Ok(files
+                .keys()
+                .any(|file| file.starts_with(&asset) && *file != asset)),
+        }
+    }
+}
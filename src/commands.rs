@@ -0,0 +1,395 @@
+//! The commands that guest systems return to make changes to the world.
+
+use bevy::{
+    ecs::{component::ComponentId, entity::Entities, reflect::ReflectResource},
+    prelude::*,
+    reflect::{TypeRegistration, TypeRegistry, serde::TypedReflectDeserializer},
+};
+use serde::de::DeserializeSeed;
+use serde_json::Deserializer as JsonDeserializer;
+use wasmtime::component::Val;
+
+use crate::{
+    asset::WasmComponentAsset,
+    bindings::wasvy::ecs::types,
+    component_registry::WasmComponentRegistry,
+    host::{component_access, component_id, component_value, despawn, set_parent},
+    mod_assets::{AssetHandleProcessor, ModAssets},
+    mod_permissions::{Access, ModPermissions, SpawnedBy, can_reparent},
+};
+
+/// Apply the `list<command>` that a guest system returned, in order.
+///
+/// Each command is applied before the next one is read, so a command that can't be applied
+/// (e.g. inserting into an entity that was despawned) is skipped with a warning instead of
+/// affecting the rest of the batch.
+pub(crate) fn apply_guest_commands(
+    world: &mut World,
+    commands: Vec<Val>,
+    wasm_asset_id: AssetId<WasmComponentAsset>,
+) {
+    let type_registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = world.resource::<WasmComponentRegistry>().clone();
+    let permissions = world.resource::<ModPermissions>().clone();
+    let mod_assets = world.resource::<ModAssets>().clone();
+    let namespace = mod_assets.namespace(wasm_asset_id);
+    let mut assets = AssetHandleProcessor {
+        assets: &mod_assets,
+        mod_id: wasm_asset_id,
+    };
+
+    for command in commands {
+        let Some(command) = command_from_val(command) else {
+            warn!("Ignoring a command returned by a guest system that doesn't match the WIT");
+            continue;
+        };
+        apply_command(
+            world,
+            command,
+            &namespace,
+            &type_registry.read(),
+            &registry,
+            &permissions,
+            &mut assets,
+        );
+        world.flush();
+    }
+}
+
+fn apply_command(
+    world: &mut World,
+    command: types::Command,
+    namespace: &str,
+    type_registry: &TypeRegistry,
+    registry: &WasmComponentRegistry,
+    permissions: &ModPermissions,
+    assets: &mut AssetHandleProcessor,
+) {
+    match command {
+        types::Command::Spawn(components) => {
+            let mut commands = world.commands();
+            let mut entity = commands.spawn(SpawnedBy(namespace.to_string()));
+            insert_components(
+                &mut entity,
+                components,
+                namespace,
+                type_registry,
+                registry,
+                permissions,
+                assets,
+            );
+        }
+        types::Command::Insert(insert) => {
+            let Some(entity) = resolve_entity(world.entities(), insert.entity) else {
+                warn!(
+                    "Can't insert components into {}: it doesn't exist",
+                    insert.entity
+                );
+                return;
+            };
+            let mut commands = world.commands();
+            let mut entity = commands.entity(entity);
+            insert_components(
+                &mut entity,
+                insert.components,
+                namespace,
+                type_registry,
+                registry,
+                permissions,
+                assets,
+            );
+        }
+        types::Command::Remove(remove) => {
+            let Some(entity) = resolve_entity(world.entities(), remove.entity) else {
+                warn!(
+                    "Can't remove components from {}: it doesn't exist",
+                    remove.entity
+                );
+                return;
+            };
+            let component_ids: Vec<ComponentId> = remove
+                .components
+                .iter()
+                .filter_map(|path| {
+                    let id =
+                        component_id(world.components(), namespace, path, type_registry, registry);
+                    if id.is_none() {
+                        warn!("Can't remove {path}: it isn't a registered component");
+                    } else if component_access(namespace, path, registry, permissions)
+                        < Access::Write
+                    {
+                        warn!(
+                            "Can't remove {path}: the mod {namespace} isn't allowed to change it"
+                        );
+                        return None;
+                    }
+                    id
+                })
+                .collect();
+
+            let mut entity = world.entity_mut(entity);
+            for component_id in component_ids {
+                entity.remove_by_id(component_id);
+            }
+        }
+        types::Command::Despawn(entity) => {
+            let Some(entity) = resolve_entity(world.entities(), entity) else {
+                warn!("Can't despawn {entity}: it doesn't exist");
+                return;
+            };
+            despawn(world, entity, namespace);
+        }
+        types::Command::SpawnChild(spawn) => {
+            let Some(parent) = resolve_entity(world.entities(), spawn.parent) else {
+                warn!("Can't spawn a child of {}: it doesn't exist", spawn.parent);
+                return;
+            };
+            if !can_reparent(world, parent, namespace) {
+                warn!(
+                    "Can't spawn a child of {}: the mod {namespace} isn't allowed to change it",
+                    spawn.parent
+                );
+                return;
+            }
+            let mut commands = world.commands();
+            let mut entity = commands.spawn((SpawnedBy(namespace.to_string()), ChildOf(parent)));
+            insert_components(
+                &mut entity,
+                spawn.components,
+                namespace,
+                type_registry,
+                registry,
+                permissions,
+                assets,
+            );
+        }
+        types::Command::SetParent(reparent) => {
+            let entities = world.entities();
+            let (Some(entity), Some(parent)) = (
+                resolve_entity(entities, reparent.entity),
+                resolve_entity(entities, reparent.parent),
+            ) else {
+                warn!(
+                    "Can't make {} a child of {}: one of them doesn't exist",
+                    reparent.entity, reparent.parent
+                );
+                return;
+            };
+            set_parent(world, entity, Some(parent), namespace);
+        }
+        types::Command::RemoveParent(entity) => {
+            let Some(entity) = resolve_entity(world.entities(), entity) else {
+                warn!("Can't remove the parent of {entity}: it doesn't exist");
+                return;
+            };
+            set_parent(world, entity, None, namespace);
+        }
+        types::Command::InsertResource(resource) => {
+            let Some((registration, reflect_resource)) =
+                reflect_resource(type_registry, &resource.path)
+            else {
+                return;
+            };
+            if !permissions.can_write(namespace, &resource.path) {
+                warn!(
+                    "Can't insert the resource {}: the mod {namespace} isn't allowed to change it",
+                    resource.path
+                );
+                return;
+            }
+
+            let mut de = JsonDeserializer::from_str(&resource.value);
+            let value = match TypedReflectDeserializer::new(registration, type_registry)
+                .deserialize(&mut de)
+            {
+                Ok(value) => value,
+                Err(err) => {
+                    warn!(
+                        "Could not deserialize the resource {}: {err}",
+                        resource.path
+                    );
+                    return;
+                }
+            };
+
+            reflect_resource.insert(world, value.as_partial_reflect(), type_registry);
+        }
+        types::Command::RemoveResource(path) => {
+            if !permissions.can_write(namespace, &path) {
+                warn!(
+                    "Can't remove the resource {path}: the mod {namespace} isn't allowed to change it"
+                );
+                return;
+            }
+            if let Some((_, reflect_resource)) = reflect_resource(type_registry, &path) {
+                reflect_resource.remove(world);
+            }
+        }
+    }
+}
+
+/// Insert the components of a command, skipping the ones that are invalid with a warning.
+fn insert_components(
+    entity: &mut EntityCommands,
+    components: Vec<types::Component>,
+    namespace: &str,
+    type_registry: &TypeRegistry,
+    registry: &WasmComponentRegistry,
+    permissions: &ModPermissions,
+    assets: &mut AssetHandleProcessor,
+) {
+    for component in components {
+        let path = component.path.clone();
+        match component_value(
+            component,
+            namespace,
+            type_registry,
+            registry,
+            permissions,
+            assets,
+        ) {
+            Ok(value) => value.insert(entity),
+            Err(err) => warn!("Can't insert {path} into {}: {err}", entity.id()),
+        }
+    }
+}
+
+/// Guests only know the index of an entity, so the generation is the one that is currently alive.
+pub(crate) fn resolve_entity(entities: &Entities, entity: types::Entity) -> Option<Entity> {
+    let row = Entity::from_raw_u32(entity as u32)?.row();
+    entities
+        .resolve_from_id(row)
+        .filter(|entity| entities.contains(*entity))
+}
+
+fn reflect_resource<'a>(
+    type_registry: &'a TypeRegistry,
+    path: &str,
+) -> Option<(&'a TypeRegistration, &'a ReflectResource)> {
+    let Some(registration) = type_registry.get_with_type_path(path) else {
+        warn!("The resource {path} isn't registered in the type registry");
+        return None;
+    };
+    let Some(reflect_resource) = registration.data::<ReflectResource>() else {
+        warn!("The type {path} isn't a resource, make sure it has `#[reflect(Resource)]`");
+        return None;
+    };
+    Some((registration, reflect_resource))
+}
+
+fn command_from_val(val: Val) -> Option<types::Command> {
+    let Val::Variant(case, payload) = val else {
+        return None;
+    };
+    let payload = payload.map(|payload| *payload);
+
+    let command = match (case.as_str(), payload) {
+        ("spawn", Some(components)) => types::Command::Spawn(components_from_val(components)?),
+        ("insert", Some(Val::Record(fields))) => {
+            let mut entity = None;
+            let mut components = None;
+            for (name, value) in fields {
+                match (name.as_str(), value) {
+                    ("entity", Val::U64(value)) => entity = Some(value),
+                    ("components", value) => components = components_from_val(value),
+                    _ => {}
+                }
+            }
+            types::Command::Insert(types::InsertCommand {
+                entity: entity?,
+                components: components?,
+            })
+        }
+        ("remove", Some(Val::Record(fields))) => {
+            let mut entity = None;
+            let mut components = None;
+            for (name, value) in fields {
+                match (name.as_str(), value) {
+                    ("entity", Val::U64(value)) => entity = Some(value),
+                    ("components", Val::List(paths)) => {
+                        components = paths.into_iter().map(string_from_val).collect()
+                    }
+                    _ => {}
+                }
+            }
+            types::Command::Remove(types::RemoveCommand {
+                entity: entity?,
+                components: components?,
+            })
+        }
+        ("despawn", Some(Val::U64(entity))) => types::Command::Despawn(entity),
+        ("insert-resource", Some(resource)) => {
+            types::Command::InsertResource(component_from_val(resource)?)
+        }
+        ("remove-resource", Some(path)) => types::Command::RemoveResource(string_from_val(path)?),
+        ("spawn-child", Some(Val::Record(fields))) => {
+            let mut parent = None;
+            let mut components = None;
+            for (name, value) in fields {
+                match (name.as_str(), value) {
+                    ("parent", Val::U64(value)) => parent = Some(value),
+                    ("components", value) => components = components_from_val(value),
+                    _ => {}
+                }
+            }
+            types::Command::SpawnChild(types::SpawnChildCommand {
+                parent: parent?,
+                components: components?,
+            })
+        }
+        ("set-parent", Some(Val::Record(fields))) => {
+            let mut entity = None;
+            let mut parent = None;
+            for (name, value) in fields {
+                match (name.as_str(), value) {
+                    ("entity", Val::U64(value)) => entity = Some(value),
+                    ("parent", Val::U64(value)) => parent = Some(value),
+                    _ => {}
+                }
+            }
+            types::Command::SetParent(types::SetParentCommand {
+                entity: entity?,
+                parent: parent?,
+            })
+        }
+        ("remove-parent", Some(Val::U64(entity))) => types::Command::RemoveParent(entity),
+        _ => return None,
+    };
+
+    Some(command)
+}
+
+fn components_from_val(val: Val) -> Option<Vec<types::Component>> {
+    let Val::List(components) = val else {
+        return None;
+    };
+    components.into_iter().map(component_from_val).collect()
+}
+
+fn component_from_val(val: Val) -> Option<types::Component> {
+    let Val::Record(fields) = val else {
+        return None;
+    };
+
+    let mut path = None;
+    let mut value = None;
+    for (name, field) in fields {
+        match name.as_str() {
+            "path" => path = string_from_val(field),
+            "value" => value = string_from_val(field),
+            _ => {}
+        }
+    }
+
+    Some(types::Component {
+        path: path?,
+        value: value?,
+    })
+}
+
+fn string_from_val(val: Val) -> Option<String> {
+    match val {
+        Val::String(value) => Some(value),
+        _ => None,
+    }
+}
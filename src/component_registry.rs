@@ -1,7 +1,152 @@
-use bevy::{ecs::component::ComponentId, platform::collections::HashMap, prelude::*};
+use bevy::{
+    ecs::component::ComponentId, platform::collections::HashMap, prelude::*, reflect::TypeRegistry,
+};
+use thiserror::Error;
+
+use crate::bindings::wasvy::ecs::types;
 
 pub type TypePath = String;
 
 /// Registry for storing the components that are registered from WASM assets.
-#[derive(Default, Clone, Debug, Resource, Deref, DerefMut)]
-pub struct WasmComponentRegistry(pub HashMap<TypePath, ComponentId>);
+///
+/// The components of a mod are registered in the namespace of the mod, under the key
+/// `<mod id>/<type path>` (see [`component_key`]), so two mods can both have a
+/// `my_mod::Health` without sharing it. A mod can export a component instead, which registers it
+/// under its type path for every mod to use.
+#[derive(Default, Clone, Debug, Resource)]
+pub struct WasmComponentRegistry {
+    /// The registered components, keyed by [`component_key`] or by type path if they're exported.
+    components: HashMap<String, ComponentId>,
+    /// The id of the mod that exported each exported component, keyed by type path.
+    exporters: HashMap<TypePath, String>,
+}
+
+/// Possible errors when a mod registers a component.
+#[non_exhaustive]
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ComponentRegistrationError {
+    /// The path is a type of the host, which mods use without registering it
+    #[error("{0} is a type of the host, it can't be registered by a mod")]
+    HostType(TypePath),
+    /// Another mod exported a component with this path
+    #[error("{path} is already exported by the mod {exporter}")]
+    Exported { path: TypePath, exporter: String },
+}
+
+impl From<ComponentRegistrationError> for types::RegisterError {
+    fn from(err: ComponentRegistrationError) -> Self {
+        match err {
+            ComponentRegistrationError::HostType(_) => types::RegisterError::HostType,
+            ComponentRegistrationError::Exported { exporter, .. } => {
+                types::RegisterError::Exported(exporter)
+            }
+        }
+    }
+}
+
+/// The key of a component that the mod `mod_id` registered without exporting it.
+pub fn component_key(mod_id: &str, path: &str) -> String {
+    format!("{mod_id}/{path}")
+}
+
+impl WasmComponentRegistry {
+    /// The component that the mod `mod_id` refers to by `path`: its own component, or else the one
+    /// that was exported under that path.
+    pub fn get(&self, mod_id: &str, path: &str) -> Option<ComponentId> {
+        self.components
+            .get(&component_key(mod_id, path))
+            .or_else(|| self.components.get(path))
+            .copied()
+    }
+
+    /// The key of the component that the mod `mod_id` refers to by `path`, see [`Self::get`].
+    pub fn key_of(&self, mod_id: &str, path: &str) -> Option<String> {
+        let key = component_key(mod_id, path);
+        if self.components.contains_key(&key) {
+            return Some(key);
+        }
+        self.components.contains_key(path).then(|| path.to_string())
+    }
+
+    /// The component registered under `key`, see [`component_key`].
+    pub fn get_by_key(&self, key: &str) -> Option<ComponentId> {
+        self.components.get(key).copied()
+    }
+
+    /// The id of the mod that exported the component with `path`.
+    pub fn exporter(&self, path: &str) -> Option<&str> {
+        self.exporters.get(path).map(String::as_str)
+    }
+
+    /// The keys and ids of all the registered components.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, ComponentId)> {
+        self.components.iter().map(|(key, id)| (key.as_str(), *id))
+    }
+
+    /// Whether `key` can be the key of a mod component that was saved in a scene: the key of the
+    /// component of a mod (see [`component_key`]), or the type path of an exported component.
+    ///
+    /// Any other key, or one whose path is a type of the host, would register a component that
+    /// shadows the types of the host or the components of other mods.
+    pub(crate) fn is_restorable_key(&self, key: &str, type_registry: &TypeRegistry) -> bool {
+        let path = match key.split_once('/') {
+            Some((mod_id, path)) if !mod_id.is_empty() && !path.is_empty() => path,
+            Some(_) => return false,
+            None if self.exporter(key).is_some() => key,
+            None => return false,
+        };
+        type_registry.get_with_type_path(path).is_none()
+    }
+
+    /// The key that the mod `mod_id` registers the component with `path` under, or why it can't.
+    pub(crate) fn key_for(
+        &self,
+        mod_id: &str,
+        path: &str,
+        export: bool,
+        type_registry: &TypeRegistry,
+    ) -> Result<String, ComponentRegistrationError> {
+        // Mods could change how host types are stored otherwise
+        if type_registry.get_with_type_path(path).is_some() {
+            return Err(ComponentRegistrationError::HostType(path.to_string()));
+        }
+        match self.exporter(path) {
+            Some(exporter) if exporter != mod_id => {
+                return Err(ComponentRegistrationError::Exported {
+                    path: path.to_string(),
+                    exporter: exporter.to_string(),
+                });
+            }
+            _ => {}
+        }
+
+        Ok(if export {
+            path.to_string()
+        } else {
+            component_key(mod_id, path)
+        })
+    }
+
+    pub(crate) fn insert(&mut self, key: String, id: ComponentId) {
+        self.components.insert(key, id);
+    }
+
+    pub(crate) fn set_exporter(&mut self, path: TypePath, mod_id: String) {
+        self.exporters.insert(path, mod_id);
+    }
+}
+
+/// The id that mods get for a component, which is the same in every run and on every machine.
+///
+/// The [`ComponentId`]s of Bevy depend on the order the components are registered in, so mods get
+/// the 64-bit FNV-1a hash of the component's key in the [`WasmComponentRegistry`] instead (the
+/// type path for the components of the host and exported components). The components that two
+/// mods register with the same path have different keys, so they get different ids.
+pub fn guest_component_id(key: &str) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    key.bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
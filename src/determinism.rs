@@ -0,0 +1,205 @@
+//! Running mods the same way on every machine, for lockstep multiplayer and replays.
+//!
+//! Enable it with [`ModloaderPlugin::deterministic`](crate::plugin::ModloaderPlugin::deterministic).
+//! In deterministic mode:
+//!
+//! - The NaNs that float operations produce are canonicalized, their bits depend on the CPU
+//!   otherwise.
+//! - The clocks of WASI always tell the Unix epoch, and its random numbers come from the
+//!   generator of the mod. Mods get no stdin and no network, and their environment is empty.
+//! - The `time` host function advances by [`Determinism::timestep`] every frame, and the `random`
+//!   host function of every mod is seeded from [`Determinism::seed`].
+//! - Guest systems run one after the other, ordered by the id of their mod and their name, and
+//!   the rows of their queries are sorted by entity.
+//!
+//! Mods only behave the same if the game gives them the same world on every machine, so the rest
+//! of the game has to be deterministic too.
+
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, RandomState},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use wasmtime_wasi::{HostMonotonicClock, HostWallClock, RngCore};
+
+use crate::{
+    asset::WasmComponentAsset, bindings::wasvy::ecs::types, component_registry::guest_component_id,
+    mod_assets::ModAssets,
+};
+
+/// The settings of deterministic mode, which is enabled when this resource exists.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Determinism {
+    /// The seed of the random numbers of mods, which must be the same on every machine.
+    pub seed: u64,
+    /// How much the time of mods advances every frame.
+    pub timestep: Duration,
+}
+
+impl Determinism {
+    /// The timestep if it isn't set with [`Determinism::with_timestep`], 60 frames per second.
+    pub const DEFAULT_TIMESTEP: Duration = Duration::from_nanos(16_666_667);
+
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            timestep: Self::DEFAULT_TIMESTEP,
+        }
+    }
+
+    /// Advance the time of mods by `timestep` every frame.
+    pub fn with_timestep(mut self, timestep: Duration) -> Self {
+        self.timestep = timestep;
+        self
+    }
+}
+
+/// The time of the current frame for mods, which they get with the `time` host function.
+///
+/// It follows Bevy's [`Time`], or advances by [`Determinism::timestep`] in deterministic mode.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ModTime {
+    pub delta: Duration,
+    pub elapsed: Duration,
+    /// The number of frames since the app started, starting at 1 on the first frame.
+    pub frame: u64,
+}
+
+impl From<ModTime> for types::FrameTime {
+    fn from(time: ModTime) -> Self {
+        Self {
+            delta: time.delta.as_secs_f64(),
+            elapsed: time.elapsed.as_secs_f64(),
+            frame: time.frame,
+        }
+    }
+}
+
+pub(crate) fn update_mod_time(
+    time: Res<Time>,
+    determinism: Option<Res<Determinism>>,
+    mut mod_time: ResMut<ModTime>,
+) {
+    let (delta, elapsed) = match determinism {
+        Some(determinism) => (
+            determinism.timestep,
+            mod_time.elapsed + determinism.timestep,
+        ),
+        None => (time.delta(), time.elapsed()),
+    };
+    *mod_time = ModTime {
+        delta,
+        elapsed,
+        frame: mod_time.frame + 1,
+    };
+}
+
+/// The random number generators of mods, which they use with the `random` host function.
+///
+/// Each mod has its own generator, keyed by its id (the name of its file without the extension),
+/// so the numbers a mod gets don't depend on the other mods. Without [`Determinism`], the seed is
+/// different in every run.
+#[derive(Resource, Clone, Debug)]
+pub struct ModRng {
+    seed: u64,
+    /// The state of the generator of each mod, keyed by mod id.
+    states: Arc<Mutex<HashMap<String, u64>>>,
+}
+
+impl Default for ModRng {
+    fn default() -> Self {
+        Self::new(RandomState::new().hash_one(0))
+    }
+}
+
+impl ModRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            states: Arc::default(),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The next number of the generator of the mod with the given id.
+    pub fn next(&self, mod_id: &str) -> u64 {
+        let mut states = self.states.lock().unwrap();
+        // Any hash of the mod id works, as long as it's the same on every machine
+        let state = states
+            .entry(mod_id.to_string())
+            .or_insert_with(|| self.seed ^ guest_component_id(mod_id));
+        split_mix(state)
+    }
+}
+
+/// The seed of the random numbers of WASI for a run of a mod, if it runs in deterministic mode.
+pub(crate) fn wasi_seed(world: &World, mod_id: AssetId<WasmComponentAsset>) -> Option<u64> {
+    world.contains_resource::<Determinism>().then(|| {
+        let mod_id = world.resource::<ModAssets>().namespace(mod_id);
+        world.resource::<ModRng>().next(&mod_id)
+    })
+}
+
+/// The SplitMix64 generator, which is tiny and good enough for games.
+fn split_mix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// The wall and monotonic clocks of WASI in deterministic mode, which never move.
+pub(crate) struct FixedClock;
+
+impl HostWallClock for FixedClock {
+    fn resolution(&self) -> Duration {
+        Duration::from_nanos(1)
+    }
+
+    fn now(&self) -> Duration {
+        Duration::ZERO
+    }
+}
+
+impl HostMonotonicClock for FixedClock {
+    fn resolution(&self) -> u64 {
+        1
+    }
+
+    fn now(&self) -> u64 {
+        0
+    }
+}
+
+/// The random numbers of WASI in deterministic mode, seeded with a number from the generator of
+/// the mod.
+pub(crate) struct SeededRng(pub u64);
+
+impl RngCore for SeededRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        split_mix(&mut self.0)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
@@ -1,63 +1,447 @@
 use std::alloc::Layout;
 use std::any::TypeId;
 use std::borrow::Cow;
+use std::collections::HashMap;
 
 use bevy::{
     asset::AssetId,
     ecs::{
-        component::{Component, ComponentDescriptor as BevyComponentDescriptor, ComponentId},
+        component::{
+            Component, ComponentDescriptor as BevyComponentDescriptor, ComponentId, Components,
+        },
+        entity::{Entities, Entity},
+        hierarchy::ChildOf,
         name::Name,
-        reflect::{AppTypeRegistry, ReflectCommandExt},
-        system::EntityCommands,
+        reflect::{AppTypeRegistry, ReflectCommandExt, ReflectComponent},
+        system::{Commands, EntityCommands},
         world::World,
     },
+    log::warn,
     reflect::{
         PartialReflect, TypeRegistration, TypeRegistry, prelude::*, serde::TypedReflectDeserializer,
     },
 };
 use serde::de::DeserializeSeed;
 use serde_json::Deserializer as JsonDeserializer;
+use thiserror::Error;
 
-use crate::bindings::wasvy::ecs::types;
-use crate::component_registry::WasmComponentRegistry;
+use crate::asset_source::mod_id;
+use crate::bindings::wasvy::ecs::{storage, types};
+use crate::commands::resolve_entity;
+use crate::component_registry::{
+    ComponentRegistrationError, WasmComponentRegistry, guest_component_id,
+};
+use crate::determinism::{ModRng, ModTime};
+use crate::mod_assets::{AssetHandleProcessor, ModAssets};
+use crate::mod_permissions::{
+    Access, ModPermissions, SpawnedBy, can_despawn, can_reparent, is_protected,
+};
+use crate::mod_storage::ModStorage;
+use crate::query_iter::QueryIter;
+use crate::systems::{GuestQuery, query_entry, serialize_component};
 use crate::{asset::WasmComponentAsset, plugin::WasmComponent, systems::WasmGuestSystem};
 
 /// The implemenation of the ECS host functions that the WASM components use for interacting with
 /// Bevy.
 pub struct WasmHost<'a> {
-    pub world: &'a mut World,
+    access: HostAccess<'a>,
     /// The WASM component this host is going to be used on.
     pub wasm_asset_id: AssetId<WasmComponentAsset>,
 }
 
-impl crate::bindings::wasvy::ecs::functions::Host for WasmHost<'_> {
-    fn register_component(
+/// How a [`WasmHost`] accesses the world.
+// There is only one host per running mod, so its size doesn't matter
+#[allow(clippy::large_enum_variant)]
+enum HostAccess<'a> {
+    /// `setup` runs in an exclusive system, with full access to the world.
+    World(&'a mut World),
+    /// Guest systems run in parallel with other systems, so they can only queue changes.
+    Deferred {
+        commands: Commands<'a, 'a>,
+        /// The queries of the system, which limit what `get` and `set` can access.
+        queries: &'a [GuestQuery<'a, 'a>],
+        entities: &'a Entities,
+        component_ids: &'a ComponentIds,
+        type_registry: AppTypeRegistry,
+        registry: &'a WasmComponentRegistry,
+        mod_assets: ModAssets,
+        mod_storage: ModStorage,
+        permissions: &'a ModPermissions,
+        mod_rng: ModRng,
+        time: ModTime,
+    },
+}
+
+/// The ids of the registered components, for hosts that can't borrow [`Components`], which can't
+/// be sent to the wasmtime store.
+///
+/// Each guest system keeps its own, which is only rebuilt when new components are registered.
+#[derive(Default)]
+pub(crate) struct ComponentIds {
+    by_type: HashMap<TypeId, ComponentId>,
+    by_name: HashMap<String, ComponentId>,
+    /// The number of registered components when the ids were collected.
+    registered: usize,
+}
+
+impl ComponentIds {
+    /// Collect the ids again if components were registered since the last update.
+    ///
+    /// Components are never unregistered, so their number only changes when new ones are added.
+    pub(crate) fn update(&mut self, components: &Components) {
+        if components.num_registered() == self.registered {
+            return;
+        }
+
+        self.by_type.clear();
+        self.by_name.clear();
+        for component_info in components.iter_registered() {
+            if let Some(type_id) = component_info.type_id() {
+                self.by_type.insert(type_id, component_info.id());
+            }
+            self.by_name
+                .insert(component_info.name().to_string(), component_info.id());
+        }
+        self.registered = components.num_registered();
+    }
+}
+
+impl<'a> WasmHost<'a> {
+    /// A host with exclusive access to the world.
+    pub fn new(world: &'a mut World, wasm_asset_id: AssetId<WasmComponentAsset>) -> Self {
+        Self {
+            access: HostAccess::World(world),
+            wasm_asset_id,
+        }
+    }
+
+    /// A host for guest systems, which queues its changes with `commands`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn deferred(
+        commands: Commands<'a, 'a>,
+        queries: &'a [GuestQuery<'a, 'a>],
+        entities: &'a Entities,
+        component_ids: &'a ComponentIds,
+        type_registry: AppTypeRegistry,
+        registry: &'a WasmComponentRegistry,
+        mod_assets: ModAssets,
+        mod_storage: ModStorage,
+        permissions: &'a ModPermissions,
+        mod_rng: ModRng,
+        time: ModTime,
+        wasm_asset_id: AssetId<WasmComponentAsset>,
+    ) -> Self {
+        Self {
+            access: HostAccess::Deferred {
+                commands,
+                queries,
+                entities,
+                component_ids,
+                type_registry,
+                registry,
+                mod_assets,
+                mod_storage,
+                permissions,
+                mod_rng,
+                time,
+            },
+            wasm_asset_id,
+        }
+    }
+
+    /// Exclusive access to the world.
+    ///
+    /// This is only available while the `setup` of a mod runs. Guest systems run in parallel with
+    /// other systems, so they must use [`WasmHost::commands`] instead.
+    pub fn world(&mut self) -> Option<&mut World> {
+        match &mut self.access {
+            HostAccess::World(world) => Some(world),
+            HostAccess::Deferred { .. } => None,
+        }
+    }
+
+    /// Queue changes to the world, which are applied after the mod's `setup` or system returns.
+    pub fn commands(&mut self) -> Commands<'_, '_> {
+        match &mut self.access {
+            HostAccess::World(world) => world.commands(),
+            HostAccess::Deferred { commands, .. } => commands.reborrow(),
+        }
+    }
+
+    fn component_id_for_type(&self, type_id: TypeId) -> Option<ComponentId> {
+        match &self.access {
+            HostAccess::World(world) => world.components().get_id(type_id),
+            HostAccess::Deferred { component_ids, .. } => {
+                component_ids.by_type.get(&type_id).copied()
+            }
+        }
+    }
+
+    fn component_id_for_name(&self, name: &str) -> Option<ComponentId> {
+        match &self.access {
+            HostAccess::World(world) => world
+                .components()
+                .iter_registered()
+                .find(|component_info| *component_info.name() == *name)
+                .map(|component_info| component_info.id()),
+            HostAccess::Deferred { component_ids, .. } => component_ids.by_name.get(name).copied(),
+        }
+    }
+
+    /// The id of a component the mod can read.
+    fn component_id_for_path(
+        &self,
+        path: &str,
+        type_registry: &TypeRegistry,
+        registry: &WasmComponentRegistry,
+        permissions: &ModPermissions,
+    ) -> Option<ComponentId> {
+        let namespace = self.namespace();
+        if let Some(component_id) = registry.get(&namespace, path) {
+            return Some(component_id);
+        }
+        if !permissions.can_read(&namespace, path) {
+            return None;
+        }
+
+        self.component_id_for_type(type_id_for_path(type_registry, path)?)
+    }
+
+    fn resolve_entity(&self, entity: types::Entity) -> Option<Entity> {
+        match &self.access {
+            HostAccess::World(world) => resolve_entity(world.entities(), entity),
+            HostAccess::Deferred { entities, .. } => resolve_entity(entities, entity),
+        }
+    }
+
+    /// The components of an entity, if the host is allowed to read all of them.
+    fn read_components(
+        &self,
+        entity: Entity,
+        paths: &[String],
+        type_registry: &TypeRegistry,
+        registry: &WasmComponentRegistry,
+        permissions: &ModPermissions,
+    ) -> Option<Vec<types::Component>> {
+        let ids = paths
+            .iter()
+            .map(|path| self.component_id_for_path(path, type_registry, registry, permissions))
+            .collect::<Option<Vec<_>>>()?;
+        let serialize =
+            |path: &String, component| Some(serialize_component(component?, path, type_registry));
+
+        match &self.access {
+            HostAccess::World(world) => {
+                let entity = world.get_entity(entity).ok()?;
+                ids.iter()
+                    .zip(paths)
+                    .map(|(id, path)| serialize(path, entity.get_by_id(*id).ok()))
+                    .collect()
+            }
+            // Like a registered query, a system can only read the components its queries read
+            HostAccess::Deferred { queries, .. } => queries.iter().find_map(|query| {
+                let row = query.get(entity).ok()?;
+                ids.iter()
+                    .zip(paths)
+                    .map(|(id, path)| serialize(path, row.get_by_id(*id)))
+                    .collect()
+            }),
+        }
+    }
+
+    fn spawn_with_parent(
         &mut self,
-        path: wasmtime::component::__internal::String,
-    ) -> types::ComponentId {
-        let type_registry = self
-            .world
-            .get_resource::<AppTypeRegistry>()
-            .unwrap()
-            .clone();
+        parent: Option<Entity>,
+        components: Vec<types::Component>,
+    ) -> types::Entity {
+        let type_registry = self.get_type_registry();
+        let type_registry = type_registry.read();
+        let registry = self.get_component_registry();
+        let permissions = self.get_permissions();
+        let namespace = self.namespace();
+        let mod_assets = self.get_mod_assets();
+        let mut assets = AssetHandleProcessor {
+            assets: &mod_assets,
+            mod_id: self.wasm_asset_id,
+        };
+
+        let mut commands = self.commands();
+        let mut entity = commands.spawn(SpawnedBy(namespace.clone()));
+        for component in components {
+            let path = component.path.clone();
+            match component_value(
+                component,
+                &namespace,
+                &type_registry,
+                &registry,
+                &permissions,
+                &mut assets,
+            ) {
+                Ok(value) => value.insert(&mut entity),
+                Err(err) => warn!("Can't spawn {path}: {err}"),
+            }
+        }
+
+        if let Some(parent) = parent {
+            let child = entity.id();
+            entity
+                .commands()
+                .queue(move |world: &mut World| set_parent(world, child, Some(parent), &namespace));
+        }
+
+        entity.id().index() as u64
+    }
+
+    /// Serialize the next rows of a query of the running system.
+    pub(crate) fn next_query_batch(
+        &self,
+        iter: &mut QueryIter,
+        n: u32,
+    ) -> Vec<types::QueryResultEntry> {
+        // Query iterators are only created for guest systems
+        let HostAccess::Deferred {
+            queries,
+            type_registry,
+            ..
+        } = &self.access
+        else {
+            return vec![];
+        };
+        let type_registry = type_registry.read();
+        let query = &queries[iter.query];
+        let slots = iter.slots.clone();
+
+        iter.next_entities(n)
+            .iter()
+            .map(|entity| query_entry(query, *entity, &slots, &type_registry))
+            .collect()
+    }
+
+    fn get_type_registry(&self) -> AppTypeRegistry {
+        match &self.access {
+            HostAccess::World(world) => world.get_resource::<AppTypeRegistry>().unwrap().clone(),
+            HostAccess::Deferred { type_registry, .. } => type_registry.clone(),
+        }
+    }
+
+    /// Guest systems borrow the registry, `setup` gets a copy since it can change the world.
+    fn get_component_registry(&self) -> Cow<'a, WasmComponentRegistry> {
+        match &self.access {
+            HostAccess::World(world) => Cow::Owned(
+                world
+                    .get_resource::<WasmComponentRegistry>()
+                    .unwrap()
+                    .clone(),
+            ),
+            HostAccess::Deferred { registry, .. } => Cow::Borrowed(*registry),
+        }
+    }
+
+    fn get_mod_assets(&self) -> ModAssets {
+        match &self.access {
+            HostAccess::World(world) => world.get_resource::<ModAssets>().unwrap().clone(),
+            HostAccess::Deferred { mod_assets, .. } => mod_assets.clone(),
+        }
+    }
+
+    fn get_mod_storage(&self) -> ModStorage {
+        match &self.access {
+            HostAccess::World(world) => world.get_resource::<ModStorage>().unwrap().clone(),
+            HostAccess::Deferred { mod_storage, .. } => mod_storage.clone(),
+        }
+    }
+
+    fn get_mod_rng(&self) -> ModRng {
+        match &self.access {
+            HostAccess::World(world) => world.get_resource::<ModRng>().unwrap().clone(),
+            HostAccess::Deferred { mod_rng, .. } => mod_rng.clone(),
+        }
+    }
+
+    fn get_time(&self) -> ModTime {
+        match &self.access {
+            HostAccess::World(world) => *world.get_resource::<ModTime>().unwrap(),
+            HostAccess::Deferred { time, .. } => *time,
+        }
+    }
+
+    fn get_permissions(&self) -> Cow<'a, ModPermissions> {
+        match &self.access {
+            HostAccess::World(world) => {
+                Cow::Owned(world.get_resource::<ModPermissions>().unwrap().clone())
+            }
+            HostAccess::Deferred { permissions, .. } => Cow::Borrowed(*permissions),
+        }
+    }
+
+    /// Register a component of the mod in its namespace, or under its type path if it's exported.
+    fn register_mod_component(
+        &mut self,
+        path: String,
+        export: bool,
+    ) -> Result<types::ComponentId, ComponentRegistrationError> {
+        let namespace = self.namespace();
+        let type_registry = self.get_type_registry();
+        let key = self.get_component_registry().key_for(
+            &namespace,
+            &path,
+            export,
+            &type_registry.read(),
+        )?;
+        let id = guest_component_id(&key);
 
-        // This is a known type by the hosto no need to register it.
-        if let Some(id) = type_id_for_path(&type_registry.read(), &path) {
-            return self.world.components().get_id(id).expect("if the value exists in the type registry it should also exist in the world components.").index() as u64;
+        let registered = self.get_component_registry().get_by_key(&key).is_some();
+        let exported_path = path.clone();
+        let register = move |world: &mut World| {
+            register_wasm_component(world, key);
+            if export {
+                world
+                    .resource_mut::<WasmComponentRegistry>()
+                    .set_exporter(exported_path, namespace);
+            }
+        };
+        match &mut self.access {
+            HostAccess::World(world) => register(world),
+            HostAccess::Deferred { commands, .. } => {
+                if !registered {
+                    // Registering a component needs exclusive access to the world, so it's only
+                    // registered after the system returns.
+                    warn!("The component {path} should be registered in `setup`, not in a system");
+                    commands.queue(register);
+                }
+            }
         }
 
-        let id = self
-            .world
-            .register_component_with_descriptor(create_component_descriptor(Cow::from(
-                path.clone(),
-            )));
+        Ok(id)
+    }
 
-        self.world
-            .get_resource_mut::<WasmComponentRegistry>()
-            .unwrap()
-            .insert(path, id);
+    /// The namespace of the components the mod registers, see [`WasmComponentRegistry`].
+    fn namespace(&self) -> String {
+        self.get_mod_assets().namespace(self.wasm_asset_id)
+    }
+
+    /// The id of the mod in the [`ModStorage`].
+    fn storage_id(&self) -> Result<String, storage::StorageError> {
+        self.get_mod_assets()
+            .mod_path(self.wasm_asset_id)
+            .and_then(|path| mod_id(&path))
+            .ok_or_else(|| storage::StorageError::Io("The mod has no file name".to_string()))
+    }
+}
+
+impl crate::bindings::wasvy::ecs::functions::Host for WasmHost<'_> {
+    fn register_component(
+        &mut self,
+        path: wasmtime::component::__internal::String,
+    ) -> Result<types::ComponentId, types::RegisterError> {
+        Ok(self.register_mod_component(path, false)?)
+    }
 
-        id.index() as u64
+    fn export_component(
+        &mut self,
+        path: wasmtime::component::__internal::String,
+    ) -> Result<types::ComponentId, types::RegisterError> {
+        Ok(self.register_mod_component(path, true)?)
     }
 
     fn register_system(
@@ -65,12 +449,14 @@ impl crate::bindings::wasvy::ecs::functions::Host for WasmHost<'_> {
         name: wasmtime::component::__internal::String,
         query: wasmtime::component::__internal::Vec<types::Query>,
     ) {
-        self.world.spawn((
+        let wasm_asset_id = self.wasm_asset_id;
+        self.commands().spawn((
             Name::new("WasvySystem"),
             WasmGuestSystem {
                 name,
                 queries: query,
-                wasm_asset_id: self.wasm_asset_id,
+                wasm_asset_id,
+                enabled: true,
             },
         ));
     }
@@ -79,41 +465,285 @@ impl crate::bindings::wasvy::ecs::functions::Host for WasmHost<'_> {
         &mut self,
         path: wasmtime::component::__internal::String,
     ) -> Option<types::ComponentId> {
-        for component_info in self.world.components().iter_registered() {
-            if *component_info.name() == path {
-                return Some(component_info.id().index() as u64);
-            }
+        let namespace = self.namespace();
+        if let Some(key) = self.get_component_registry().key_of(&namespace, &path) {
+            return Some(guest_component_id(&key));
         }
-
-        None
+        // Components the mod can't read are hidden from it
+        let host = self.get_permissions().can_read(&namespace, &path)
+            && self.component_id_for_name(&path).is_some();
+        host.then(|| guest_component_id(&path))
     }
 
     fn spawn(
         &mut self,
         components: wasmtime::component::__internal::Vec<types::Component>,
     ) -> types::Entity {
+        self.spawn_with_parent(None, components)
+    }
+
+    fn spawn_child(
+        &mut self,
+        parent: types::Entity,
+        components: wasmtime::component::__internal::Vec<types::Component>,
+    ) -> types::Entity {
+        let Some(parent) = self.resolve_entity(parent) else {
+            warn!(
+                "Can't spawn a child of {parent}: it doesn't exist, spawning it without a parent"
+            );
+            return self.spawn_with_parent(None, components);
+        };
+        self.spawn_with_parent(Some(parent), components)
+    }
+
+    fn set_parent(&mut self, entity: types::Entity, parent: types::Entity) {
+        let (Some(entity), Some(parent)) =
+            (self.resolve_entity(entity), self.resolve_entity(parent))
+        else {
+            warn!("Can't make {entity} a child of {parent}: one of them doesn't exist");
+            return;
+        };
+        let mod_id = self.namespace();
+        self.commands()
+            .queue(move |world: &mut World| set_parent(world, entity, Some(parent), &mod_id));
+    }
+
+    fn remove_parent(&mut self, entity: types::Entity) {
+        let Some(entity) = self.resolve_entity(entity) else {
+            warn!("Can't remove the parent of {entity}: it doesn't exist");
+            return;
+        };
+        let mod_id = self.namespace();
+        self.commands()
+            .queue(move |world: &mut World| set_parent(world, entity, None, &mod_id));
+    }
+
+    fn despawn_recursive(&mut self, entity: types::Entity) {
+        let Some(entity) = self.resolve_entity(entity) else {
+            warn!("Can't despawn {entity}: it doesn't exist");
+            return;
+        };
+        let mod_id = self.namespace();
+        self.commands()
+            .queue(move |world: &mut World| despawn(world, entity, &mod_id));
+    }
+
+    fn get(
+        &mut self,
+        entity: types::Entity,
+        components: wasmtime::component::__internal::Vec<wasmtime::component::__internal::String>,
+    ) -> Option<wasmtime::component::__internal::Vec<types::Component>> {
+        let type_registry = self.get_type_registry();
+        let registry = self.get_component_registry();
+        let permissions = self.get_permissions();
+
+        let entity = self.resolve_entity(entity)?;
+        self.read_components(
+            entity,
+            &components,
+            &type_registry.read(),
+            &registry,
+            &permissions,
+        )
+    }
+
+    fn get_many(
+        &mut self,
+        entities: wasmtime::component::__internal::Vec<types::Entity>,
+        components: wasmtime::component::__internal::Vec<wasmtime::component::__internal::String>,
+    ) -> wasmtime::component::__internal::Vec<
+        Option<wasmtime::component::__internal::Vec<types::Component>>,
+    > {
+        entities
+            .into_iter()
+            .map(|entity| self.get(entity, components.clone()))
+            .collect()
+    }
+
+    fn set(
+        &mut self,
+        entity: types::Entity,
+        components: wasmtime::component::__internal::Vec<types::Component>,
+    ) -> bool {
         let type_registry = self.get_type_registry();
         let type_registry = type_registry.read();
         let registry = self.get_component_registry();
+        let permissions = self.get_permissions();
 
-        let mut commands = self.world.commands();
-        let mut entity = commands.spawn_empty();
+        let Some(entity) = self.resolve_entity(entity) else {
+            return false;
+        };
+        let paths: Vec<_> = components
+            .iter()
+            .map(|component| component.path.clone())
+            .collect();
+        if self
+            .read_components(entity, &paths, &type_registry, &registry, &permissions)
+            .is_none()
+        {
+            return false;
+        }
+        let namespace = self.namespace();
+        let writable = paths.iter().all(|path| {
+            component_access(&namespace, path, &registry, &permissions) == Access::Write
+        });
+        if !writable {
+            return false;
+        }
 
-        for component in components {
-            insert_component(&mut entity, component, &type_registry, &registry);
+        let mod_assets = self.get_mod_assets();
+        let mut assets = AssetHandleProcessor {
+            assets: &mod_assets,
+            mod_id: self.wasm_asset_id,
+        };
+
+        // Every value is checked before anything is queued, so nothing changes if one is invalid
+        let values = components
+            .into_iter()
+            .map(|component| {
+                let path = component.path.clone();
+                component_value(
+                    component,
+                    &namespace,
+                    &type_registry,
+                    &registry,
+                    &permissions,
+                    &mut assets,
+                )
+                .inspect_err(|err| warn!("Can't set {path}: {err}"))
+            })
+            .collect::<Result<Vec<_>, _>>();
+        let Ok(values) = values else {
+            return false;
+        };
+
+        let mut commands = self.commands();
+        let mut entity = commands.entity(entity);
+        for value in values {
+            value.insert(&mut entity);
         }
+        true
+    }
 
-        entity.id().index() as u64
+    fn set_many(
+        &mut self,
+        entities: wasmtime::component::__internal::Vec<types::InsertCommand>,
+    ) -> wasmtime::component::__internal::Vec<bool> {
+        entities
+            .into_iter()
+            .map(|insert| self.set(insert.entity, insert.components))
+            .collect()
+    }
+
+    fn load_asset(
+        &mut self,
+        path: wasmtime::component::__internal::String,
+    ) -> Option<types::AssetHandle> {
+        self.get_mod_assets().load(self.wasm_asset_id, &path)
+    }
+
+    fn asset_load_state(&mut self, asset: types::AssetHandle) -> types::LoadState {
+        self.get_mod_assets().load_state(self.wasm_asset_id, asset)
+    }
+
+    fn time(&mut self) -> types::FrameTime {
+        self.get_time().into()
+    }
+
+    fn random(&mut self) -> u64 {
+        self.get_mod_rng().next(&self.namespace())
     }
 
     fn this_function_does_nothing(
         &mut self,
         _entry: crate::bindings::wasvy::ecs::types::QueryResultEntry,
         _query_result: crate::bindings::wasvy::ecs::types::QueryResult,
+        _command: crate::bindings::wasvy::ecs::types::Command,
     ) {
     }
 }
 
+impl storage::Host for WasmHost<'_> {
+    fn get(&mut self, key: wasmtime::component::__internal::String) -> Option<String> {
+        let id = self.storage_id().ok()?;
+        self.get_mod_storage().get(&id, &key)
+    }
+
+    fn set(
+        &mut self,
+        key: wasmtime::component::__internal::String,
+        value: wasmtime::component::__internal::String,
+    ) -> Result<(), storage::StorageError> {
+        let id = self.storage_id()?;
+        Ok(self.get_mod_storage().set(&id, &key, &value)?)
+    }
+
+    fn remove(
+        &mut self,
+        key: wasmtime::component::__internal::String,
+    ) -> Result<(), storage::StorageError> {
+        let id = self.storage_id()?;
+        Ok(self.get_mod_storage().remove(&id, &key)?)
+    }
+
+    fn keys(&mut self) -> Vec<String> {
+        let Ok(id) = self.storage_id() else {
+            return Vec::new();
+        };
+        self.get_mod_storage().data(&id).into_keys().collect()
+    }
+}
+
+/// Change or remove the parent of an entity, if the mod is allowed to.
+///
+/// The permissions of the mod can only be checked with access to the world, so the host functions
+/// queue this.
+pub(crate) fn set_parent(world: &mut World, entity: Entity, parent: Option<Entity>, mod_id: &str) {
+    let allowed = can_reparent(world, entity, mod_id)
+        && parent.is_none_or(|parent| can_reparent(world, parent, mod_id));
+    if !allowed {
+        warn!("Can't change the parent of {entity}: the mod {mod_id} isn't allowed to");
+        return;
+    }
+    let Ok(mut entity) = world.get_entity_mut(entity) else {
+        return;
+    };
+    match parent {
+        Some(parent) => entity.insert(ChildOf(parent)),
+        None => entity.remove::<ChildOf>(),
+    };
+}
+
+/// Despawn an entity along with its descendants, like in Bevy, if the mod is allowed to.
+pub(crate) fn despawn(world: &mut World, entity: Entity, mod_id: &str) {
+    if !world.entities().contains(entity) {
+        return;
+    }
+    if !can_despawn(world, entity, mod_id) {
+        warn!("Can't despawn {entity}: the mod {mod_id} isn't allowed to");
+        return;
+    }
+    world.despawn(entity);
+}
+
+/// Register a mod component under its key in the [`WasmComponentRegistry`], unless it's already
+/// registered.
+pub(crate) fn register_wasm_component(world: &mut World, key: String) -> ComponentId {
+    if let Some(id) = world.resource::<WasmComponentRegistry>().get_by_key(&key) {
+        return id;
+    }
+
+    let id = world
+        .register_component_with_descriptor(create_component_descriptor(Cow::from(key.clone())));
+
+    world
+        .get_resource_mut::<WasmComponentRegistry>()
+        .unwrap()
+        .insert(key, id);
+
+    id
+}
+
 fn create_component_descriptor(name: impl Into<Cow<'static, str>>) -> BevyComponentDescriptor {
     unsafe {
         BevyComponentDescriptor::new_with_layout(
@@ -133,17 +763,111 @@ pub fn type_id_for_path(registry: &TypeRegistry, path: &str) -> Option<TypeId> {
         .map(|registration: &TypeRegistration| registration.type_id())
 }
 
-fn insert_component(
-    entity: &mut EntityCommands,
+/// The access of the mod with the given namespace to a component, which is full access to the
+/// components of mods and at most read access to the ones only the host changes.
+pub(crate) fn component_access(
+    namespace: &str,
+    path: &str,
+    registry: &WasmComponentRegistry,
+    permissions: &ModPermissions,
+) -> Access {
+    if registry.get(namespace, path).is_some() {
+        return Access::Write;
+    }
+    let access = permissions.access(namespace, path);
+    if is_protected(path) {
+        return access.min(Access::Read);
+    }
+    access
+}
+
+/// The id of a component that the mod with the given namespace registered, or known by the
+/// host's type registry.
+pub(crate) fn component_id(
+    components: &Components,
+    namespace: &str,
+    path: &str,
+    type_registry: &TypeRegistry,
+    registry: &WasmComponentRegistry,
+) -> Option<ComponentId> {
+    if let Some(component_id) = registry.get(namespace, path) {
+        return Some(component_id);
+    }
+
+    components.get_id(type_id_for_path(type_registry, path)?)
+}
+
+/// Why a component that a mod sent can't be inserted.
+#[derive(Debug, Error)]
+pub(crate) enum InsertComponentError {
+    /// The path is neither a component of the mod nor a type of the host
+    #[error("{0} isn't a registered component")]
+    Unregistered(String),
+    /// The permissions of the mod don't let it change the component
+    #[error("the mod {mod_id} isn't allowed to change {path}")]
+    NotAllowed { path: String, mod_id: String },
+    /// Only the host changes the component, see [`is_protected`]
+    #[error("{0} is only changed by the host, use the hierarchy functions to change parents")]
+    Protected(String),
+    /// The type is registered, but not as a component
+    #[error("{0} isn't a component, make sure it has `#[reflect(Component)]`")]
+    NotAComponent(String),
+    /// The type can't be created from its reflected value
+    #[error("{0} can't be created with `FromReflect`")]
+    NoFromReflect(String),
+    /// The value doesn't match the type
+    #[error("invalid value for {path}: {message}")]
+    InvalidValue { path: String, message: String },
+}
+
+/// A component from a mod that was checked, so inserting it can't fail.
+pub(crate) enum ComponentValue {
+    /// A component registered by a mod, with its serialized value
+    Wasm(ComponentId, String),
+    /// A component of the host
+    Host(Box<dyn Reflect>),
+}
+
+impl ComponentValue {
+    pub(crate) fn insert(self, entity: &mut EntityCommands) {
+        match self {
+            ComponentValue::Wasm(component_id, value) => {
+                insert_wasm_component(entity, &component_id, value)
+            }
+            ComponentValue::Host(value) => {
+                entity.insert_reflect(value.into_partial_reflect());
+            }
+        }
+    }
+}
+
+/// Check a component that the mod with the given namespace wants to insert, and deserialize it
+/// if it's a component of the host.
+pub(crate) fn component_value(
     component: types::Component,
+    namespace: &str,
     type_registry: &TypeRegistry,
     registry: &WasmComponentRegistry,
-) {
-    if let Some(component_id) = registry.get(&component.path) {
-        insert_wasm_component(entity, component_id, component.value);
-    } else {
-        insert_host_component(entity, component, type_registry);
+    permissions: &ModPermissions,
+    assets: &mut AssetHandleProcessor,
+) -> Result<ComponentValue, InsertComponentError> {
+    if let Some(component_id) = registry.get(namespace, &component.path) {
+        return Ok(ComponentValue::Wasm(component_id, component.value));
+    }
+    let Some(type_registration) = type_registry.get_with_type_path(&component.path) else {
+        return Err(InsertComponentError::Unregistered(component.path));
+    };
+    if is_protected(&component.path) {
+        return Err(InsertComponentError::Protected(component.path));
     }
+    if !permissions.can_write(namespace, &component.path) {
+        return Err(InsertComponentError::NotAllowed {
+            path: component.path,
+            mod_id: namespace.to_string(),
+        });
+    }
+    host_component_value(component, type_registration, type_registry, assets)
+        .map(ComponentValue::Host)
 }
 
 fn insert_wasm_component(entity: &mut EntityCommands, component_id: &ComponentId, value: String) {
@@ -157,40 +881,40 @@ fn insert_wasm_component(entity: &mut EntityCommands, component_id: &ComponentId
     }
 }
 
-fn insert_host_component(
-    entity: &mut EntityCommands,
+/// Deserialize a component of the host from the JSON that a mod sent.
+///
+/// Everything that would make inserting it panic is checked here, since the insert itself only
+/// happens when the commands are applied.
+fn host_component_value(
     component: types::Component,
+    type_registration: &TypeRegistration,
     type_registry: &TypeRegistry,
-) {
-    let type_registration = type_registry.get_with_type_path(&component.path).unwrap();
+    assets: &mut AssetHandleProcessor,
+) -> Result<Box<dyn Reflect>, InsertComponentError> {
+    let path = component.path;
+    if type_registration.data::<ReflectComponent>().is_none() {
+        return Err(InsertComponentError::NotAComponent(path));
+    }
+    let Some(reflect_from_reflect) = type_registration.data::<ReflectFromReflect>() else {
+        return Err(InsertComponentError::NoFromReflect(path));
+    };
 
+    // The asset handles of the mod are replaced with the actual handles of its assets
     let mut de = JsonDeserializer::from_str(&component.value);
-    let reflect_deserializer = TypedReflectDeserializer::new(type_registration, type_registry);
-    let output: Box<dyn PartialReflect> = reflect_deserializer.deserialize(&mut de).unwrap();
-
-    let type_id = output.get_represented_type_info().unwrap().type_id();
-    let reflect_from_reflect = type_registry
-        .get_type_data::<ReflectFromReflect>(type_id)
-        .unwrap();
-    let value: Box<dyn Reflect> = reflect_from_reflect
-        .from_reflect(output.as_partial_reflect())
-        .unwrap();
-
-    entity.insert_reflect(value);
-}
-
-impl WasmHost<'_> {
-    fn get_type_registry(&self) -> AppTypeRegistry {
-        self.world
-            .get_resource::<AppTypeRegistry>()
-            .unwrap()
-            .clone()
-    }
+    let reflect_deserializer =
+        TypedReflectDeserializer::with_processor(type_registration, type_registry, assets);
+    let output: Box<dyn PartialReflect> =
+        reflect_deserializer.deserialize(&mut de).map_err(|err| {
+            InsertComponentError::InvalidValue {
+                path: path.clone(),
+                message: err.to_string(),
+            }
+        })?;
 
-    fn get_component_registry(&self) -> WasmComponentRegistry {
-        self.world
-            .get_resource::<WasmComponentRegistry>()
-            .unwrap()
-            .clone()
-    }
+    reflect_from_reflect
+        .from_reflect(output.as_partial_reflect())
+        .ok_or_else(|| InsertComponentError::InvalidValue {
+            path,
+            message: "the value doesn't match the type".to_string(),
+        })
 }
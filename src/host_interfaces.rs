@@ -0,0 +1,89 @@
+//! Game specific WIT interfaces that the host exposes to mods, next to `wasvy:ecs`.
+
+use std::sync::Arc;
+
+use bevy::{asset::ReflectHandle, prelude::*};
+use wasmtime::component::Linker;
+
+use crate::{mod_assets::ModAssets, state::States};
+
+type AddToLinker = dyn for<'a> Fn(&mut Linker<States<'a>>) -> wasmtime::Result<()> + Send + Sync;
+
+/// The custom interfaces added with [`ModloaderAppExt::add_host_interface`].
+#[derive(Resource, Default, Clone)]
+pub struct HostInterfaces(Vec<Arc<AddToLinker>>);
+
+impl HostInterfaces {
+    pub(crate) fn add_to_linker(&self, linker: &mut Linker<States<'_>>) {
+        for add_to_linker in &self.0 {
+            if let Err(err) = add_to_linker(linker) {
+                error!("Could not add a host interface to the linker: {err:?}");
+            }
+        }
+    }
+}
+
+/// Extends [`App`] with the ability to expose your own WIT interfaces to mods.
+pub trait ModloaderAppExt {
+    /// Add a host interface generated with [`wasmtime::component::bindgen!`] to the linker that
+    /// instantiates mods.
+    ///
+    /// Implement the generated `Host` trait for [`WasmHost`](crate::host::WasmHost), which
+    /// gives you access to the [`World`] (through [`Commands`] while guest systems run) and the
+    /// mod that is calling the function.
+    ///
+    /// ```rust,ignore
+    /// mod bindings {
+    ///     wasmtime::component::bindgen!("game" in "wit/game.wit");
+    /// }
+    ///
+    /// impl bindings::my_game::api::inventory::Host for WasmHost<'_> {
+    ///     fn give_item(&mut self, item: String) {
+    ///         self.commands().spawn(Item(item));
+    ///     }
+    /// }
+    ///
+    /// app.add_host_interface(|linker| {
+    ///     bindings::my_game::api::inventory::add_to_linker(linker, |state: &mut States| {
+    ///         &mut state.host_ecs
+    ///     })
+    /// });
+    /// ```
+    fn add_host_interface<F>(&mut self, add_to_linker: F) -> &mut Self
+    where
+        F: for<'a> Fn(&mut Linker<States<'a>>) -> wasmtime::Result<()> + Send + Sync + 'static;
+
+    /// Let mods use handles of the asset type `A` in their components, like the default
+    /// [`Image`], [`Scene`], [`AudioSource`] and [`Font`].
+    ///
+    /// Must be called after adding the [`ModloaderPlugin`](crate::plugin::ModloaderPlugin).
+    fn register_mod_asset<A: Asset>(&mut self) -> &mut Self;
+}
+
+impl ModloaderAppExt for App {
+    fn add_host_interface<F>(&mut self, add_to_linker: F) -> &mut Self
+    where
+        F: for<'a> Fn(&mut Linker<States<'a>>) -> wasmtime::Result<()> + Send + Sync + 'static,
+    {
+        self.world_mut()
+            .get_resource_or_init::<HostInterfaces>()
+            .0
+            .push(Arc::new(add_to_linker));
+        self
+    }
+
+    fn register_mod_asset<A: Asset>(&mut self) -> &mut Self {
+        // Mods put the index of an asset where the component has a handle, which is recognized
+        // by the `ReflectHandle` of the handle type
+        let type_registry = self.world().resource::<AppTypeRegistry>().clone();
+        let mut type_registry = type_registry.write();
+        type_registry.register::<Handle<A>>();
+        type_registry.register_type_data::<Handle<A>, ReflectHandle>();
+
+        self.world_mut()
+            .get_resource_mut::<ModAssets>()
+            .expect("register_mod_asset must be called after adding the ModloaderPlugin")
+            .register::<A>();
+        self
+    }
+}
@@ -1,13 +1,33 @@
 pub mod asset;
+pub mod asset_source;
+mod commands;
 pub mod component_registry;
+pub mod determinism;
 pub mod host;
+pub mod host_interfaces;
+pub mod linking;
+pub mod mod_assets;
+pub mod mod_list;
+pub mod mod_package;
+pub mod mod_permissions;
+pub mod mod_scene;
+pub mod mod_state;
+pub mod mod_storage;
+pub mod mod_trust;
 pub mod mods;
 pub mod plugin;
 pub mod prelude;
+mod query_iter;
 pub mod runner;
 pub mod state;
 pub mod systems;
 
 mod bindings {
-    wasmtime::component::bindgen!("host" in "wit/ecs/ecs.wit");
+    wasmtime::component::bindgen!({
+        world: "host",
+        path: "wit/ecs/ecs.wit",
+        with: {
+            "wasvy:ecs/types/query-iter": crate::query_iter::QueryIter,
+        },
+    });
 }
@@ -0,0 +1,212 @@
+//! Linking between mods, so one mod can import a WIT interface that another mod exports.
+//!
+//! Any interface a mod exports (e.g. `my-lib:math/vectors`) is registered in [`ModInterfaces`].
+//! When a mod that imports the interface runs, the exporting mod is instantiated in the same
+//! [`Store`], and the imported functions forward their calls to it. Both mods share the same
+//! host state, so the library mod can use the ECS functions as usual.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::anyhow;
+use bevy::prelude::*;
+use wasmtime::{
+    Store,
+    component::{Component, ComponentExportIndex, Linker, types::ComponentItem},
+};
+
+use crate::{asset::WasmComponentAsset, plugin::Engine, runner::Runner, state::States};
+
+/// A WIT interface that a mod exports for other mods to import.
+#[derive(Clone)]
+pub struct ModInterface {
+    /// The mod that exports the interface.
+    pub provider: AssetId<WasmComponentAsset>,
+    pub component: Component,
+    /// The names of the functions in the interface.
+    pub functions: Vec<String>,
+}
+
+/// Registry of the WIT interfaces exported by mods, keyed by the interface name
+/// (e.g. `my-lib:math/vectors@0.1.0`).
+#[derive(Resource, Default, Clone, Deref, DerefMut)]
+pub struct ModInterfaces(pub HashMap<String, ModInterface>);
+
+impl ModInterfaces {
+    /// Register every interface exported by the component.
+    pub fn register(
+        &mut self,
+        engine: &wasmtime::Engine,
+        provider: AssetId<WasmComponentAsset>,
+        component: &Component,
+    ) {
+        for (name, item) in component.component_type().exports(engine) {
+            let ComponentItem::ComponentInstance(instance) = item else {
+                continue;
+            };
+
+            if let Some(existing) = self.get(name) {
+                if existing.provider != provider {
+                    warn!(
+                        "Interface `{name}` is already exported by another mod, the new export is ignored"
+                    );
+                    continue;
+                }
+            }
+
+            let functions = instance
+                .exports(engine)
+                .filter(|(_, item)| matches!(item, ComponentItem::ComponentFunc(_)))
+                .map(|(name, _)| name.to_string())
+                .collect();
+
+            self.insert(
+                name.to_string(),
+                ModInterface {
+                    provider,
+                    component: component.clone(),
+                    functions,
+                },
+            );
+        }
+    }
+
+    /// Remove every interface exported by the given mod.
+    pub fn unregister(&mut self, provider: AssetId<WasmComponentAsset>) {
+        self.retain(|_, interface| interface.provider != provider);
+    }
+
+    /// Define every registered interface in the linker, forwarding the calls to the instance of
+    /// the exporting mod that lives in the same store.
+    pub(crate) fn add_to_linker(&self, linker: &mut Linker<States<'_>>) {
+        for (name, interface) in self.iter() {
+            let Some(interface_index) = interface.component.get_export_index(None, name) else {
+                continue;
+            };
+            let mut linker_instance = match linker.instance(name) {
+                Ok(linker_instance) => linker_instance,
+                Err(err) => {
+                    warn!("Could not link interface `{name}` exported by a mod: {err}");
+                    continue;
+                }
+            };
+
+            for function in &interface.functions {
+                let Some(function_index) = interface
+                    .component
+                    .get_export_index(Some(&interface_index), function)
+                else {
+                    continue;
+                };
+
+                let result = linker_instance
+                    .func_new(function, forward_to_provider(name.clone(), function_index));
+                if let Err(err) = result {
+                    warn!("Could not link function `{function}` of interface `{name}`: {err}");
+                }
+            }
+        }
+    }
+
+    /// Instantiate the mods that export the interfaces imported by the component, so their
+    /// functions can be called from the component.
+    pub(crate) fn instantiate_imports(
+        &self,
+        runner: &Runner<States<'_>>,
+        store: &mut Store<States<'_>>,
+        component: &Component,
+    ) -> wasmtime::Result<()> {
+        self.instantiate_imports_inner(runner, store, component, &mut HashSet::new())
+    }
+
+    fn instantiate_imports_inner(
+        &self,
+        runner: &Runner<States<'_>>,
+        store: &mut Store<States<'_>>,
+        component: &Component,
+        visiting: &mut HashSet<AssetId<WasmComponentAsset>>,
+    ) -> wasmtime::Result<()> {
+        let imports: Vec<String> = component
+            .component_type()
+            .imports(&runner.engine)
+            .map(|(name, _)| name.to_string())
+            .collect();
+
+        for import in imports {
+            if store.data().linked_instances.contains_key(&import) {
+                continue;
+            }
+            let Some(interface) = self.get(&import) else {
+                continue;
+            };
+            if !visiting.insert(interface.provider) {
+                return Err(anyhow!(
+                    "mods that export `{import}` and import each other form a cycle"
+                ));
+            }
+
+            self.instantiate_imports_inner(runner, store, &interface.component, visiting)?;
+            let instance = runner.instantiate(store, &interface.component)?;
+
+            // One instance of the provider serves all of the interfaces it exports
+            for (name, other) in self.iter() {
+                if other.provider == interface.provider {
+                    store
+                        .data_mut()
+                        .linked_instances
+                        .insert(name.clone(), instance);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn forward_to_provider(
+    interface: String,
+    function_index: ComponentExportIndex,
+) -> impl Fn(
+    wasmtime::StoreContextMut<'_, States<'_>>,
+    &[wasmtime::component::Val],
+    &mut [wasmtime::component::Val],
+) -> wasmtime::Result<()>
++ Send
++ Sync
++ 'static {
+    move |mut store, params, results| {
+        let instance = *store
+            .data()
+            .linked_instances
+            .get(&interface)
+            .ok_or_else(|| anyhow!("the mod that exports `{interface}` isn't instantiated"))?;
+        let func = instance
+            .get_func(&mut store, function_index)
+            .ok_or_else(|| anyhow!("function wasn't found in `{interface}`"))?;
+
+        func.call(&mut store, params, results)?;
+        func.post_return(&mut store)
+    }
+}
+
+/// Keeps [`ModInterfaces`] up to date with the interfaces exported by the loaded mods.
+pub(crate) fn register_mod_interfaces(
+    mut events: MessageReader<AssetEvent<WasmComponentAsset>>,
+    assets: Res<Assets<WasmComponentAsset>>,
+    engine: Res<Engine>,
+    mut interfaces: ResMut<ModInterfaces>,
+) {
+    for event in events.read() {
+        match event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => {
+                interfaces.unregister(*id);
+                if let Some(asset) = assets.get(*id) {
+                    interfaces.register(&engine, *id, &asset.component);
+                }
+            }
+            AssetEvent::Removed { id } | AssetEvent::Unused { id } => {
+                interfaces.unregister(*id);
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//! The assets that mods load through the `load-asset` host function.
+
+use std::{
+    any::TypeId,
+    collections::HashMap,
+    path::{Component as PathComponent, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+use bevy::{
+    asset::{
+        AssetPath, LoadState, LoadedUntypedAsset, ReflectHandle, UntypedHandle, io::AssetSourceId,
+    },
+    prelude::*,
+    reflect::{
+        PartialReflect, TypeRegistration, TypeRegistry, serde::ReflectDeserializerProcessor,
+    },
+};
+use serde::{Deserialize, Deserializer, de::Error as _};
+
+use crate::{
+    asset::WasmComponentAsset,
+    asset_source::{self, MOD_ASSET_SOURCE},
+    bindings::wasvy::ecs::types,
+    mods::Mod,
+};
+
+/// Loads an asset of a known type, see [`ModAssets::register`].
+type LoadTyped = fn(&AssetServer, AssetPath<'static>) -> UntypedHandle;
+
+/// Which assets mods can load with the `load-asset` host function.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ModAssetScope {
+    /// Paths are relative to the mod's own asset directory, the directory next to the mod with the
+    /// name of its file (e.g. `mods/my_mod/` for `mods/my_mod.wasm`), and can't leave it.
+    ///
+    /// With the [`ModAssetSourcePlugin`](crate::asset_source::ModAssetSourcePlugin), the assets are
+    /// loaded from the `mod://` asset source (e.g. `mod://my_mod/ship.glb`).
+    #[default]
+    ModDirectory,
+    /// Paths are loaded as is, like with [`AssetServer::load`], so mods can load any asset of the game.
+    Unrestricted,
+}
+
+/// The assets that mods loaded, which are kept alive as long as the mod is loaded.
+///
+/// Mods refer to their assets by the index of the asset in the list of the mod, which they put in
+/// the value of a component wherever the Rust type has a [`Handle`].
+#[derive(Resource, Clone)]
+pub struct ModAssets {
+    server: AssetServer,
+    /// Which assets mods can load.
+    pub scope: ModAssetScope,
+    loaders: Arc<HashMap<TypeId, LoadTyped>>,
+    /// Whether the `mod://` asset source was added.
+    mod_source: bool,
+    handles: Arc<RwLock<HashMap<AssetId<WasmComponentAsset>, Vec<ModAsset>>>>,
+}
+
+struct ModAsset {
+    path: AssetPath<'static>,
+    handle: Handle<LoadedUntypedAsset>,
+}
+
+impl ModAssets {
+    pub(crate) fn new(server: AssetServer) -> Self {
+        Self {
+            mod_source: server.get_source(MOD_ASSET_SOURCE).is_ok(),
+            server,
+            scope: ModAssetScope::default(),
+            loaders: Arc::default(),
+            handles: Arc::default(),
+        }
+    }
+
+    /// Mods load assets without knowing their type, so a handle can only be created before the
+    /// asset finished loading if its type was registered.
+    pub(crate) fn register<A: Asset>(&mut self) {
+        Arc::make_mut(&mut self.loaders).insert(TypeId::of::<A>(), |server, path| {
+            server.load::<A>(path).untyped()
+        });
+    }
+
+    /// Start loading an asset for the mod, returning the index of its handle.
+    pub(crate) fn load(
+        &self,
+        mod_id: AssetId<WasmComponentAsset>,
+        path: &str,
+    ) -> Option<types::AssetHandle> {
+        let path = self.resolve_path(mod_id, path)?;
+
+        let mut handles = self.handles.write().unwrap();
+        let assets = handles.entry(mod_id).or_default();
+        if let Some(index) = assets.iter().position(|asset| asset.path == path) {
+            return Some(index as types::AssetHandle);
+        }
+
+        let handle = self.server.load_untyped(path.clone());
+        assets.push(ModAsset { path, handle });
+        Some((assets.len() - 1) as types::AssetHandle)
+    }
+
+    pub(crate) fn load_state(
+        &self,
+        mod_id: AssetId<WasmComponentAsset>,
+        asset: types::AssetHandle,
+    ) -> types::LoadState {
+        let handles = self.handles.read().unwrap();
+        let Some(asset) = handles
+            .get(&mod_id)
+            .and_then(|assets| assets.get(asset as usize))
+        else {
+            return types::LoadState::NotLoaded;
+        };
+
+        match self.server.load_state(&asset.handle) {
+            LoadState::NotLoaded => types::LoadState::NotLoaded,
+            LoadState::Loading => types::LoadState::Loading,
+            LoadState::Loaded => types::LoadState::Loaded,
+            LoadState::Failed(_) => types::LoadState::Failed,
+        }
+    }
+
+    /// The path the mod was loaded from.
+    pub(crate) fn mod_path(
+        &self,
+        mod_id: AssetId<WasmComponentAsset>,
+    ) -> Option<AssetPath<'static>> {
+        self.server.get_path(mod_id).map(AssetPath::into_owned)
+    }
+
+    /// The namespace of the components the mod registers, the id of the mod (e.g. `space_ship`
+    /// for `mods/space_ship.wasm`), see [`WasmComponentRegistry`](crate::component_registry::WasmComponentRegistry).
+    pub(crate) fn namespace(&self, mod_id: AssetId<WasmComponentAsset>) -> String {
+        self.mod_path(mod_id)
+            .and_then(|path| asset_source::mod_id(&path))
+            .unwrap_or_else(|| mod_id.to_string())
+    }
+
+    /// A handle of the given asset type for an asset of the mod.
+    fn typed_handle(
+        &self,
+        mod_id: AssetId<WasmComponentAsset>,
+        asset: types::AssetHandle,
+        type_id: TypeId,
+    ) -> Option<UntypedHandle> {
+        let handles = self.handles.read().unwrap();
+        let path = &handles.get(&mod_id)?.get(asset as usize)?.path;
+
+        // The untyped load creates the typed handle once it knows the type of the asset
+        if let Some(handle) = self.server.get_path_and_type_id_handle(path, type_id) {
+            return Some(handle);
+        }
+        let load = self.loaders.get(&type_id)?;
+        Some(load(&self.server, path.clone()))
+    }
+
+    fn resolve_path(
+        &self,
+        mod_id: AssetId<WasmComponentAsset>,
+        path: &str,
+    ) -> Option<AssetPath<'static>> {
+        let requested = AssetPath::try_parse(path).ok()?;
+        if self.scope == ModAssetScope::Unrestricted {
+            return Some(requested.into_owned());
+        }
+
+        let mod_path = self.server.get_path(mod_id)?;
+        let (mut asset_path, source) = if self.mod_source {
+            (
+                PathBuf::from(asset_source::mod_id(&mod_path)?),
+                AssetSourceId::from(MOD_ASSET_SOURCE),
+            )
+        } else {
+            (
+                mod_path.path().with_extension(""),
+                mod_path.source().clone_owned(),
+            )
+        };
+        // Paths with an explicit asset source (`source://path`) are outside of it too
+        let mut inside = *requested.source() == AssetSourceId::Default;
+        for component in requested.path().components() {
+            match component {
+                PathComponent::Normal(name) => asset_path.push(name),
+                PathComponent::CurDir => {}
+                _ => inside = false,
+            }
+        }
+        if !inside {
+            warn!("A mod can't load {path}: it's outside of the mod's asset directory");
+            return None;
+        }
+
+        let mut resolved = AssetPath::from(asset_path).with_source(source);
+        if let Some(label) = requested.label() {
+            resolved = resolved.with_label(label.to_string());
+        }
+        Some(resolved.into_owned())
+    }
+
+    /// Release the assets of a mod that isn't loaded anymore.
+    fn release(&self, mod_id: AssetId<WasmComponentAsset>) {
+        self.handles.write().unwrap().remove(&mod_id);
+    }
+}
+
+/// Resolves the asset handles that a mod put in the value of a component while it's deserialized.
+pub(crate) struct AssetHandleProcessor<'a> {
+    pub assets: &'a ModAssets,
+    pub mod_id: AssetId<WasmComponentAsset>,
+}
+
+impl ReflectDeserializerProcessor for AssetHandleProcessor<'_> {
+    fn try_deserialize<'de, D>(
+        &mut self,
+        registration: &TypeRegistration,
+        _registry: &TypeRegistry,
+        deserializer: D,
+    ) -> Result<Result<Box<dyn PartialReflect>, D>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let Some(reflect_handle) = registration.data::<ReflectHandle>() else {
+            return Ok(Err(deserializer));
+        };
+
+        let asset = types::AssetHandle::deserialize(deserializer)?;
+        let handle = self
+            .assets
+            .typed_handle(self.mod_id, asset, reflect_handle.asset_type_id())
+            .ok_or_else(|| {
+                D::Error::custom(format!(
+                    "{asset} isn't an asset handle of the mod, or its type ({}) can't be used \
+                    before it is loaded, register it with `ModloaderAppExt::register_mod_asset`",
+                    registration.type_info().type_path()
+                ))
+            })?;
+
+        Ok(Ok(reflect_handle.typed(handle).into_partial_reflect()))
+    }
+}
+
+/// Drops the handles of the assets a mod loaded once no entity uses the mod anymore.
+pub(crate) fn release_mod_assets(
+    remove: On<Remove, Mod>,
+    assets: Res<ModAssets>,
+    mods: Query<&Mod>,
+) {
+    let Ok(removed) = mods.get(remove.entity) else {
+        return;
+    };
+    let mod_id = removed.asset.id();
+    if mods.iter().filter(|m| m.asset.id() == mod_id).count() == 1 {
+        assets.release(mod_id);
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::Path,
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{asset_source::mod_id, mods::Mod, systems::WasmGuestSystem};
+
+/// The enabled/disabled state of every mod and its systems, keyed by the mod's id.
+///
+/// The id of a mod is the name of its file without the extension (e.g. `space_ship` for
+/// `mods/space_ship.wasm`), like everywhere else. Mods and systems that aren't in the list are
+/// enabled. The list can be persisted between
+/// sessions with [`ModList::save`] and [`ModList::load`].
+///
+/// ```rust
+/// # use bevy::prelude::*;
+/// # use wasvy::mod_list::ModList;
+/// fn load_mod_list(mut commands: Commands) {
+///     let mod_list = ModList::load("mods.json").unwrap_or_default();
+///     commands.insert_resource(mod_list);
+/// }
+/// ```
+#[derive(Resource, Default, Clone, Debug, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+pub struct ModList {
+    pub mods: BTreeMap<String, ModSettings>,
+}
+
+/// The settings of a single mod in the [`ModList`].
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct ModSettings {
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    /// The names of the systems of this mod that shouldn't run.
+    #[serde(default)]
+    pub disabled_systems: BTreeSet<String>,
+}
+
+impl Default for ModSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            disabled_systems: BTreeSet::new(),
+        }
+    }
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+/// Possible errors that can be produced when persisting the [`ModList`]
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum ModListError {
+    /// An [IO](std::io) Error
+    #[error("Could not access the mod list: {0}")]
+    Io(#[from] std::io::Error),
+    /// The mod list isn't valid JSON
+    #[error("Could not parse the mod list: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+impl ModList {
+    /// Read a mod list that was previously written with [`ModList::save`].
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ModListError> {
+        let contents = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Write the mod list as JSON to the given path.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ModListError> {
+        let contents = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Returns true if the mod with the given id should run its systems.
+    pub fn is_enabled(&self, mod_id: &str) -> bool {
+        self.mods
+            .get(mod_id)
+            .is_none_or(|settings| settings.enabled)
+    }
+
+    /// Enable or disable all the systems of the mod with the given id.
+    pub fn set_enabled(&mut self, mod_id: &str, enabled: bool) {
+        self.mods.entry(mod_id.to_string()).or_default().enabled = enabled;
+    }
+
+    /// Returns true if the given system of the mod should run.
+    pub fn is_system_enabled(&self, mod_id: &str, system_name: &str) -> bool {
+        self.mods
+            .get(mod_id)
+            .is_none_or(|settings| !settings.disabled_systems.contains(system_name))
+    }
+
+    /// Enable or disable a single system of the mod with the given id.
+    pub fn set_system_enabled(&mut self, mod_id: &str, system_name: &str, enabled: bool) {
+        let disabled_systems = &mut self
+            .mods
+            .entry(mod_id.to_string())
+            .or_default()
+            .disabled_systems;
+
+        if enabled {
+            disabled_systems.remove(system_name);
+        } else {
+            disabled_systems.insert(system_name.to_string());
+        }
+    }
+}
+
+/// Syncs the enabled state of [`Mod`] entities and their [`WasmGuestSystem`]s with the [`ModList`].
+pub(crate) fn apply_mod_list(
+    mod_list: Res<ModList>,
+    mut mods: Query<&mut Mod>,
+    mut systems: Query<&mut WasmGuestSystem>,
+) {
+    let mut mod_ids = HashMap::new();
+    for mut wasm_mod in mods.iter_mut() {
+        let Some(id) = wasm_mod.asset.path().and_then(mod_id) else {
+            continue;
+        };
+        let enabled = mod_list.is_enabled(&id);
+        if wasm_mod.enabled != enabled {
+            wasm_mod.enabled = enabled;
+        }
+        mod_ids.insert(wasm_mod.asset.id(), id);
+    }
+
+    for mut system in systems.iter_mut() {
+        let Some(id) = mod_ids.get(&system.wasm_asset_id) else {
+            continue;
+        };
+        let enabled = mod_list.is_system_enabled(id, &system.name);
+        if system.enabled != enabled {
+            system.enabled = enabled;
+        }
+    }
+}
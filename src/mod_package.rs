@@ -0,0 +1,354 @@
+//! Mods shipped as a single package, with their component and assets.
+//!
+//! A mod package is a zip archive with the `.wasvy` extension, which has a `manifest.json` at its
+//! root next to the files of the mod:
+//!
+//! ```text
+//! space_ship.wasvy
+//! ├── manifest.json
+//! ├── space_ship.wasm
+//! ├── ship.glb
+//! └── sounds/engine.ogg
+//! ```
+//!
+//! ```json
+//! {
+//!     "name": "Space Ship",
+//!     "version": "1.0.0",
+//!     "component": "space_ship.wasm",
+//!     "files": {
+//!         "space_ship.wasm": "<sha-256 of the file>",
+//!         "ship.glb": "<sha-256 of the file>",
+//!         "sounds/engine.ogg": "<sha-256 of the file>"
+//!     }
+//! }
+//! ```
+//!
+//! Packages are loaded like any other mod with [`Mods`](crate::mods::Mods). Every file of the
+//! package must be listed in the manifest with its hash, and the package fails to load if a file
+//! is missing, isn't listed or doesn't match its hash. Packages can be signed with a
+//! `manifest.sig` next to the manifest, see [`mod_trust`](crate::mod_trust). The files besides the component are the
+//! assets of the mod, which need the [`ModAssetSourcePlugin`](crate::asset_source::ModAssetSourcePlugin)
+//! to be loaded (e.g. `mod://space_ship/ship.glb`).
+//!
+//! A package is extracted in memory, so it fails to load if it has more files or is larger once
+//! extracted than its [`PackageLimits`] allow, which are set with
+//! [`ModloaderPlugin::package_limits`](crate::plugin::ModloaderPlugin::package_limits).
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    io::{Cursor, Read},
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use zip::{ZipArchive, result::ZipError};
+
+use crate::{
+    asset::{WasmComponentAsset, WasmComponentAssetLoader, WasmComponentAssetLoaderError},
+    asset_source::{ModAssetRoots, ModIdConflict},
+};
+
+/// The extension of mod packages.
+pub const PACKAGE_EXTENSION: &str = "wasvy";
+
+/// The path of the manifest in a mod package.
+pub const MANIFEST_PATH: &str = "manifest.json";
+
+/// The path of the signature of the manifest in a mod package, see [`mod_trust`](crate::mod_trust).
+pub const SIGNATURE_PATH: &str = "manifest.sig";
+
+/// The `manifest.json` of a mod package.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModManifest {
+    /// The name of the mod, for showing it to players.
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    /// The path of the WASM component in the package.
+    pub component: String,
+    /// The SHA-256 of every file in the package besides the manifest and its signature, as hex,
+    /// keyed by path.
+    pub files: BTreeMap<String, String>,
+}
+
+/// How large the mod packages that are loaded can be once extracted.
+#[derive(Clone, Copy, Debug)]
+pub struct PackageLimits {
+    /// The maximum number of files in a package, including its folders.
+    pub max_files: usize,
+    /// The maximum size of all the files of a package once extracted, in bytes.
+    pub max_size: u64,
+}
+
+impl Default for PackageLimits {
+    fn default() -> Self {
+        Self {
+            max_files: 10_000,
+            max_size: 512 * 1024 * 1024,
+        }
+    }
+}
+
+/// Loads a mod package as the [`WasmComponentAsset`] of its component.
+pub struct ModPackageLoader {
+    pub(crate) component_loader: WasmComponentAssetLoader,
+    pub(crate) roots: ModAssetRoots,
+    pub(crate) limits: PackageLimits,
+}
+
+/// Possible errors that can be produced by [`ModPackageLoader`]
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum ModPackageLoaderError {
+    /// An [IO](std::io) Error
+    #[error("Could not load mod package: {0}")]
+    Io(#[from] std::io::Error),
+    /// The package isn't a valid zip archive, or a file in it is corrupted
+    #[error("The mod package isn't a valid zip archive: {0}")]
+    Archive(#[from] ZipError),
+    #[error("The mod package has no {MANIFEST_PATH}")]
+    MissingManifest,
+    #[error("The {MANIFEST_PATH} of the mod package is invalid: {0}")]
+    Manifest(serde_json::Error),
+    /// A file would be outside of the package if it was extracted (e.g. `../game.wasm`)
+    #[error("{0} in the mod package has an invalid path")]
+    InvalidPath(String),
+    #[error("{0} is listed in the manifest but missing from the mod package")]
+    MissingFile(String),
+    #[error("{0} is in the mod package but isn't listed in its manifest")]
+    UnlistedFile(String),
+    #[error("The contents of {0} don't match its hash in the manifest")]
+    HashMismatch(String),
+    /// The package has more files than [`PackageLimits::max_files`]
+    #[error("The mod package has more than {0} files")]
+    TooManyFiles(usize),
+    /// The package is larger than [`PackageLimits::max_size`] once extracted
+    #[error("The mod package is larger than {0} bytes once extracted")]
+    TooLarge(u64),
+    #[error(transparent)]
+    Component(#[from] WasmComponentAssetLoaderError),
+    /// Another mod with the same id is already loaded
+    #[error(transparent)]
+    IdConflict(#[from] ModIdConflict),
+}
+
+impl AssetLoader for ModPackageLoader {
+    type Asset = WasmComponentAsset;
+    type Settings = ();
+    type Error = ModPackageLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes).await?;
+        let mut files = read_archive(bytes, self.limits)?;
+
+        let manifest_bytes = files
+            .remove(Path::new(MANIFEST_PATH))
+            .ok_or(ModPackageLoaderError::MissingManifest)?;
+        let signature = files.remove(Path::new(SIGNATURE_PATH));
+        let manifest: ModManifest =
+            serde_json::from_slice(&manifest_bytes).map_err(ModPackageLoaderError::Manifest)?;
+        verify_files(&manifest, &files)?;
+
+        // The manifest has the hashes of all the files, so signing it signs the whole package
+        let component = files
+            .remove(Path::new(&manifest.component))
+            .ok_or_else(|| ModPackageLoaderError::MissingFile(manifest.component.clone()))?;
+        let mut asset =
+            self.component_loader
+                .compile(&component, &manifest_bytes, signature.as_deref())?;
+        asset.manifest = Some(manifest);
+
+        self.roots
+            .register_package(load_context.asset_path(), files)?;
+
+        Ok(asset)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[PACKAGE_EXTENSION]
+    }
+}
+
+/// The files in the archive, keyed by their path.
+fn read_archive(
+    bytes: Vec<u8>,
+    limits: PackageLimits,
+) -> Result<HashMap<PathBuf, Vec<u8>>, ModPackageLoaderError> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+    if archive.len() > limits.max_files {
+        return Err(ModPackageLoaderError::TooManyFiles(limits.max_files));
+    }
+
+    let mut remaining = limits.max_size;
+    let mut files = HashMap::new();
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        if file.is_dir() {
+            continue;
+        }
+        let path = file
+            .enclosed_name()
+            .ok_or_else(|| ModPackageLoaderError::InvalidPath(file.name().to_string()))?;
+
+        if file.size() > remaining {
+            return Err(ModPackageLoaderError::TooLarge(limits.max_size));
+        }
+
+        // The size in the archive can be a lie, so don't read more than what is left. The archive
+        // checks the CRC-32 of the file once it is read to the end.
+        let mut contents = Vec::new();
+        (&mut file).take(remaining + 1).read_to_end(&mut contents)?;
+        let size = contents.len() as u64;
+        if size > remaining {
+            return Err(ModPackageLoaderError::TooLarge(limits.max_size));
+        }
+        remaining -= size;
+        files.insert(path, contents);
+    }
+
+    Ok(files)
+}
+
+/// Check that the package has exactly the files of the manifest, with the same contents.
+fn verify_files(
+    manifest: &ModManifest,
+    files: &HashMap<PathBuf, Vec<u8>>,
+) -> Result<(), ModPackageLoaderError> {
+    for (path, hash) in &manifest.files {
+        let contents = files
+            .get(Path::new(path))
+            .ok_or_else(|| ModPackageLoaderError::MissingFile(path.clone()))?;
+        if format!("{:x}", Sha256::digest(contents)) != hash.to_ascii_lowercase() {
+            return Err(ModPackageLoaderError::HashMismatch(path.clone()));
+        }
+    }
+
+    let listed: HashSet<&Path> = manifest.files.keys().map(Path::new).collect();
+    if let Some(unlisted) = files.keys().find(|path| !listed.contains(path.as_path())) {
+        return Err(ModPackageLoaderError::UnlistedFile(
+            unlisted.display().to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use zip::{ZipWriter, write::SimpleFileOptions};
+
+    use super::*;
+
+    fn archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (path, contents) in files {
+            writer
+                .start_file(*path, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(contents).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn manifest(files: &[(&str, &[u8])]) -> ModManifest {
+        ModManifest {
+            name: None,
+            version: None,
+            component: "mod.wasm".to_string(),
+            files: files
+                .iter()
+                .map(|(path, contents)| {
+                    (path.to_string(), format!("{:X}", Sha256::digest(contents)))
+                })
+                .collect(),
+        }
+    }
+
+    fn files(files: &[(&str, &[u8])]) -> HashMap<PathBuf, Vec<u8>> {
+        files
+            .iter()
+            .map(|(path, contents)| (PathBuf::from(path), contents.to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn verify_files() {
+        let listed: &[(&str, &[u8])] = &[("mod.wasm", b"component"), ("sounds/a.ogg", b"sound")];
+        let manifest = manifest(listed);
+        assert!(super::verify_files(&manifest, &files(listed)).is_ok());
+
+        let missing = files(&listed[..1]);
+        assert!(matches!(
+            super::verify_files(&manifest, &missing),
+            Err(ModPackageLoaderError::MissingFile(path)) if path == "sounds/a.ogg"
+        ));
+
+        let modified = files(&[("mod.wasm", b"component"), ("sounds/a.ogg", b"other")]);
+        assert!(matches!(
+            super::verify_files(&manifest, &modified),
+            Err(ModPackageLoaderError::HashMismatch(path)) if path == "sounds/a.ogg"
+        ));
+
+        let mut unlisted = files(listed);
+        unlisted.insert(PathBuf::from("extra.wasm"), b"extra".to_vec());
+        assert!(matches!(
+            super::verify_files(&manifest, &unlisted),
+            Err(ModPackageLoaderError::UnlistedFile(path)) if path == "extra.wasm"
+        ));
+    }
+
+    #[test]
+    fn paths_outside_of_the_package_are_rejected() {
+        for path in ["../game.wasm", "/etc/game.wasm", "sounds/../../game.wasm"] {
+            let bytes = archive(&[("mod.wasm", b"component"), (path, b"escape")]);
+            assert!(matches!(
+                read_archive(bytes, PackageLimits::default()),
+                Err(ModPackageLoaderError::InvalidPath(name)) if name == path
+            ));
+        }
+
+        let bytes = archive(&[("mod.wasm", b"component"), ("sounds/a.ogg", b"sound")]);
+        let files = read_archive(bytes, PackageLimits::default()).unwrap();
+        assert_eq!(files[Path::new("sounds/a.ogg")], b"sound");
+    }
+
+    #[test]
+    fn limits() {
+        let bytes = archive(&[("a", &[0; 600]), ("b", &[0; 600])]);
+        let limits = |max_files, max_size| PackageLimits {
+            max_files,
+            max_size,
+        };
+
+        assert!(read_archive(bytes.clone(), limits(2, 1200)).is_ok());
+        assert!(matches!(
+            read_archive(bytes.clone(), limits(1, 1200)),
+            Err(ModPackageLoaderError::TooManyFiles(1))
+        ));
+        // The second file only fits in what the first one left
+        assert!(matches!(
+            read_archive(bytes.clone(), limits(2, 1000)),
+            Err(ModPackageLoaderError::TooLarge(1000))
+        ));
+        assert!(matches!(
+            read_archive(bytes, limits(2, 500)),
+            Err(ModPackageLoaderError::TooLarge(500))
+        ));
+    }
+}
@@ -0,0 +1,404 @@
+//! Deciding which types of the host mods may read and write.
+//!
+//! Mods can use any component or resource that is in the host's type registry by its type path,
+//! including the internal state of the game. The [`ModPermissions`] resource limits this to the
+//! modding API the game wants to offer, with an [`AccessPolicy`] for every mod:
+//!
+//! ```rust,ignore
+//! app.insert_resource(
+//!     ModPermissions::new(AccessPolicy::deny_all().allow::<Transform>(Access::Write))
+//!         .with_group("tools", AccessPolicy::allow_all().deny::<Inventory>())
+//!         .with_member("tools", "level_editor"),
+//! );
+//! ```
+//!
+//! Mods are identified by the name of their file without the extension (e.g. `level_editor` for
+//! `mods/level_editor.wasm`). The policy of a mod is the one set for it with
+//! [`ModPermissions::with_mod`], or else the policy of its group, or else the default policy.
+//!
+//! The components that mods register themselves aren't affected, a mod always has full access to
+//! them.
+//!
+//! The hierarchy is protected too. Changing the parent of an entity needs write access to
+//! [`ChildOf`], and a mod can only move or despawn the entities it spawned (see [`SpawnedBy`]) and
+//! the ones whose components it can all read. Despawning entities the mod didn't spawn also needs
+//! [`AccessPolicy::despawn`]. Mods can never insert or remove [`SpawnedBy`], [`ChildOf`] or
+//! [`Children`] themselves, whatever their policy says, they change the hierarchy with the
+//! hierarchy functions instead.
+
+use std::any::TypeId;
+
+use bevy::{
+    platform::collections::HashMap,
+    prelude::*,
+    reflect::{TypePath, TypeRegistry},
+};
+
+use crate::component_registry::{WasmComponentRegistry, component_key};
+
+/// What a mod may do with a type of the host.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Access {
+    /// The mod can't use the type, as if it didn't exist.
+    None,
+    /// The mod can query and get the component, but not insert, set or remove it.
+    Read,
+    /// The mod can read the component and change it.
+    #[default]
+    Write,
+}
+
+/// The access of a mod to the types of the host, by type path.
+#[derive(Clone, Debug)]
+pub struct AccessPolicy {
+    /// The access to the types that aren't listed in `types`.
+    pub default: Access,
+    pub types: HashMap<String, Access>,
+    /// Whether the mod can despawn the entities it didn't spawn, as long as it can read all of
+    /// their components and the components of their descendants.
+    pub despawn: bool,
+}
+
+impl Default for AccessPolicy {
+    fn default() -> Self {
+        Self::allow_all()
+    }
+}
+
+impl AccessPolicy {
+    /// A policy that allows everything, and can deny some types (a denylist).
+    pub fn allow_all() -> Self {
+        Self {
+            default: Access::Write,
+            types: HashMap::default(),
+            despawn: true,
+        }
+    }
+
+    /// A policy that denies everything, and can allow some types (an allowlist).
+    pub fn deny_all() -> Self {
+        Self {
+            default: Access::None,
+            types: HashMap::default(),
+            despawn: false,
+        }
+    }
+
+    /// Allow or deny despawning the entities the mod didn't spawn.
+    pub fn with_despawn(mut self, despawn: bool) -> Self {
+        self.despawn = despawn;
+        self
+    }
+
+    /// Set the access to the type `T`.
+    pub fn allow<T: TypePath>(self, access: Access) -> Self {
+        self.allow_path(T::type_path(), access)
+    }
+
+    /// Set the access to the type with the given type path.
+    pub fn allow_path(mut self, path: impl Into<String>, access: Access) -> Self {
+        self.types.insert(path.into(), access);
+        self
+    }
+
+    /// Deny any access to the type `T`.
+    pub fn deny<T: TypePath>(self) -> Self {
+        self.allow::<T>(Access::None)
+    }
+
+    /// Deny any access to the type with the given type path.
+    pub fn deny_path(self, path: impl Into<String>) -> Self {
+        self.allow_path(path, Access::None)
+    }
+
+    /// The access to the type with the given type path.
+    pub fn access(&self, path: &str) -> Access {
+        self.types.get(path).copied().unwrap_or(self.default)
+    }
+}
+
+/// The [`AccessPolicy`] of every mod, see [`mod_permissions`](crate::mod_permissions).
+///
+/// By default every mod can read and write every type, insert your own to restrict them.
+///
+/// The queries of a guest system are checked when the system is added to the schedule, so
+/// changing the permissions of a mod only applies to the queries of systems it registers later.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct ModPermissions {
+    /// The policy of the mods that have no policy of their own and aren't in a group.
+    pub default: AccessPolicy,
+    groups: HashMap<String, AccessPolicy>,
+    /// The group of each mod, keyed by mod id.
+    members: HashMap<String, String>,
+    /// The policies of single mods, keyed by mod id.
+    mods: HashMap<String, AccessPolicy>,
+}
+
+impl ModPermissions {
+    /// Use `policy` for the mods that aren't given another one.
+    pub fn new(policy: AccessPolicy) -> Self {
+        Self {
+            default: policy,
+            ..default()
+        }
+    }
+
+    /// Add a permission group, or replace its policy.
+    pub fn with_group(mut self, group: impl Into<String>, policy: AccessPolicy) -> Self {
+        self.groups.insert(group.into(), policy);
+        self
+    }
+
+    /// Put the mod with the given id in a group, which replaces its previous group.
+    pub fn with_member(mut self, group: impl Into<String>, mod_id: impl Into<String>) -> Self {
+        self.members.insert(mod_id.into(), group.into());
+        self
+    }
+
+    /// Give the mod with the given id its own policy, which takes precedence over its group.
+    pub fn with_mod(mut self, mod_id: impl Into<String>, policy: AccessPolicy) -> Self {
+        self.mods.insert(mod_id.into(), policy);
+        self
+    }
+
+    /// The policy that applies to the mod with the given id.
+    ///
+    /// A mod in a group that doesn't exist gets the default policy.
+    pub fn policy(&self, mod_id: &str) -> &AccessPolicy {
+        self.mods
+            .get(mod_id)
+            .or_else(|| {
+                self.members
+                    .get(mod_id)
+                    .and_then(|group| self.groups.get(group))
+            })
+            .unwrap_or(&self.default)
+    }
+
+    /// The access of the mod with the given id to the type with the given type path.
+    pub fn access(&self, mod_id: &str, path: &str) -> Access {
+        self.policy(mod_id).access(path)
+    }
+
+    /// Whether the mod with the given id may read the type with the given type path.
+    pub fn can_read(&self, mod_id: &str, path: &str) -> bool {
+        self.access(mod_id, path) >= Access::Read
+    }
+
+    /// Whether the mod with the given id may change the type with the given type path.
+    pub fn can_write(&self, mod_id: &str, path: &str) -> bool {
+        self.access(mod_id, path) >= Access::Write
+    }
+}
+
+/// The id of the mod that spawned an entity.
+///
+/// A mod can always despawn the entities it spawned and change their place in the hierarchy, even
+/// if it can't read all of their components (e.g. the ones required by the components it
+/// inserted).
+#[derive(Component, Clone, Debug, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct SpawnedBy(pub String);
+
+/// Whether the component with the given type path is only changed by the host.
+///
+/// [`SpawnedBy`] decides which entities a mod owns, and changing the hierarchy directly would skip
+/// the checks of [`can_reparent`] on both entities.
+pub(crate) fn is_protected(path: &str) -> bool {
+    [
+        SpawnedBy::type_path(),
+        ChildOf::type_path(),
+        Children::type_path(),
+    ]
+    .contains(&path)
+}
+
+/// Whether the mod may change the parent of the entity, or make it the parent of another one.
+pub(crate) fn can_reparent(world: &World, entity: Entity, mod_id: &str) -> bool {
+    world
+        .resource::<ModPermissions>()
+        .can_write(mod_id, ChildOf::type_path())
+        && (spawned_by(world, entity, mod_id) || can_read_entity(world, entity, mod_id))
+}
+
+/// Whether the mod may despawn the entity along with its descendants.
+pub(crate) fn can_despawn(world: &World, entity: Entity, mod_id: &str) -> bool {
+    let despawn = world.resource::<ModPermissions>().policy(mod_id).despawn;
+    let mut entities = vec![entity];
+    while let Some(entity) = entities.pop() {
+        let allowed = spawned_by(world, entity, mod_id)
+            || (despawn && can_read_entity(world, entity, mod_id));
+        if !allowed {
+            return false;
+        }
+        if let Some(children) = world.get::<Children>(entity) {
+            entities.extend(children.iter());
+        }
+    }
+    true
+}
+
+fn spawned_by(world: &World, entity: Entity, mod_id: &str) -> bool {
+    world
+        .get::<SpawnedBy>(entity)
+        .is_some_and(|spawned_by| spawned_by.0 == mod_id)
+}
+
+/// Whether the mod can read every component of the entity, besides its place in the hierarchy.
+fn can_read_entity(world: &World, entity: Entity, mod_id: &str) -> bool {
+    let Ok(components) = world.inspect_entity(entity) else {
+        return false;
+    };
+    let permissions = world.resource::<ModPermissions>();
+    let registry = world.resource::<WasmComponentRegistry>();
+    let type_registry = world.resource::<AppTypeRegistry>().read();
+    let hierarchy = [TypeId::of::<ChildOf>(), TypeId::of::<Children>()];
+
+    components
+        .filter(|info| info.type_id().is_none_or(|id| !hierarchy.contains(&id)))
+        .all(|info| {
+            // The components of mods have no type, they're registered under their key
+            if let Some((key, _)) = registry.iter().find(|(_, id)| *id == info.id()) {
+                // The keys of the components of the mod start with its id
+                let own = key.starts_with(&component_key(mod_id, ""));
+                return own || registry.exporter(key).is_some();
+            }
+            let path = type_path(&type_registry, info.type_id(), info.name().to_string());
+            permissions.can_read(mod_id, &path)
+        })
+}
+
+/// The type path of a component, or its name if it isn't in the type registry.
+fn type_path(type_registry: &TypeRegistry, type_id: Option<TypeId>, name: String) -> String {
+    type_id
+        .and_then(|type_id| type_registry.get(type_id))
+        .map(|registration| registration.type_info().type_path().to_string())
+        .unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::component_access;
+
+    #[derive(Component, Reflect)]
+    struct Secret;
+
+    fn world(permissions: ModPermissions) -> World {
+        let mut world = World::new();
+        let type_registry = AppTypeRegistry::default();
+        {
+            let mut type_registry = type_registry.write();
+            type_registry.register::<Name>();
+            type_registry.register::<Secret>();
+            type_registry.register::<SpawnedBy>();
+        }
+        world.insert_resource(type_registry);
+        world.init_resource::<WasmComponentRegistry>();
+        world.insert_resource(permissions);
+        world
+    }
+
+    fn child_of_writer() -> AccessPolicy {
+        AccessPolicy::deny_all().allow::<ChildOf>(Access::Write)
+    }
+
+    #[test]
+    fn policy_precedence() {
+        let permissions = ModPermissions::new(AccessPolicy::deny_all())
+            .with_group("tools", AccessPolicy::allow_all())
+            .with_member("tools", "editor")
+            .with_member("tools", "inspector")
+            .with_mod(
+                "inspector",
+                AccessPolicy::deny_all().allow::<Name>(Access::Read),
+            )
+            .with_member("missing", "lost");
+
+        let name = Name::type_path();
+        assert_eq!(permissions.access("editor", name), Access::Write);
+        assert_eq!(permissions.access("inspector", name), Access::Read);
+        assert_eq!(permissions.access("other", name), Access::None);
+        assert_eq!(permissions.access("lost", name), Access::None);
+        assert!(permissions.can_read("inspector", name));
+        assert!(!permissions.can_write("inspector", name));
+    }
+
+    #[test]
+    fn reparent_needs_child_of_and_a_readable_or_owned_entity() {
+        let mut world = world(ModPermissions::default());
+        let host = world.spawn(Name::new("player")).id();
+        assert!(can_reparent(&world, host, "a"));
+
+        let mut world = self::world(ModPermissions::new(AccessPolicy::deny_all()));
+        let own = world.spawn(SpawnedBy("a".to_string())).id();
+        assert!(!can_reparent(&world, own, "a"));
+
+        let mut world = self::world(ModPermissions::new(child_of_writer()));
+        let own = world.spawn((SpawnedBy("a".to_string()), Secret)).id();
+        let other = world.spawn(SpawnedBy("b".to_string())).id();
+        let host = world.spawn(Name::new("player")).id();
+        assert!(can_reparent(&world, own, "a"));
+        assert!(!can_reparent(&world, other, "a"));
+        assert!(!can_reparent(&world, host, "a"));
+
+        let policy = child_of_writer().allow::<Name>(Access::Read);
+        let mut world = self::world(ModPermissions::new(policy));
+        let readable = world.spawn(Name::new("player")).id();
+        let secret = world.spawn((Name::new("vault"), Secret)).id();
+        let child = world.spawn((Name::new("sword"), ChildOf(readable))).id();
+        assert!(can_reparent(&world, readable, "a"));
+        assert!(can_reparent(&world, child, "a"));
+        assert!(!can_reparent(&world, secret, "a"));
+    }
+
+    #[test]
+    fn despawn_checks_every_descendant() {
+        let mut world = world(ModPermissions::default());
+        let host = world.spawn(Name::new("player")).id();
+        assert!(can_despawn(&world, host, "a"));
+
+        let policy = AccessPolicy::allow_all().with_despawn(false);
+        let mut world = self::world(ModPermissions::new(policy));
+        let host = world.spawn(Name::new("player")).id();
+        let own = world.spawn(SpawnedBy("a".to_string())).id();
+        assert!(!can_despawn(&world, host, "a"));
+        assert!(can_despawn(&world, own, "a"));
+        assert!(!can_despawn(&world, own, "b"));
+
+        // Despawning an entity of the mod would also despawn the entity of the host under it
+        world.spawn((Name::new("sword"), ChildOf(own)));
+        assert!(!can_despawn(&world, own, "a"));
+
+        let mut world = self::world(ModPermissions::new(AccessPolicy::deny_all()));
+        let own = world.spawn(SpawnedBy("a".to_string())).id();
+        world.spawn((SpawnedBy("a".to_string()), ChildOf(own)));
+        assert!(can_despawn(&world, own, "a"));
+
+        let policy = AccessPolicy::allow_all().deny::<Secret>();
+        let mut world = self::world(ModPermissions::new(policy));
+        let host = world.spawn(Name::new("player")).id();
+        world.spawn((Name::new("vault"), Secret, ChildOf(host)));
+        assert!(!can_despawn(&world, host, "a"));
+    }
+
+    #[test]
+    fn mods_never_write_protected_components() {
+        let permissions = ModPermissions::default();
+        let registry = WasmComponentRegistry::default();
+        for path in [
+            SpawnedBy::type_path(),
+            ChildOf::type_path(),
+            Children::type_path(),
+        ] {
+            assert_eq!(
+                component_access("a", path, &registry, &permissions),
+                Access::Read
+            );
+        }
+        assert_eq!(
+            component_access("a", Name::type_path(), &registry, &permissions),
+            Access::Write
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Saving the components of mods in scenes.
+//!
+//! Every mod component is a [`WasmComponent`] under a [`ComponentId`] that is created at runtime,
+//! so the [`DynamicSceneBuilder`](bevy::scene::DynamicSceneBuilder) can't extract them. Add them
+//! to the scene with [`ModSceneExt::extract_mod_components`], which stores them in a
+//! [`ModComponents`] component keyed by their key in the
+//! [`WasmComponentRegistry`] (the id of the mod and the type path):
+//!
+//! ```rust,ignore
+//! let mut scene = DynamicSceneBuilder::from_world(world)
+//!     .extract_entities(entities.into_iter())
+//!     .build();
+//! scene.extract_mod_components(world);
+//! ```
+//!
+//! When the scene is spawned, the [`ModComponents`] of every entity are turned back into the
+//! components of the mods, under the ids their keys are registered with in this session. Keys that
+//! can't belong to a mod component, like the type paths of the host, are skipped with a warning.
+
+use std::{alloc::Layout, collections::BTreeMap};
+
+use bevy::{
+    ecs::{component::ComponentId, ptr::OwningPtr},
+    prelude::*,
+    scene::DynamicScene,
+};
+
+use crate::{
+    component_registry::WasmComponentRegistry, host::register_wasm_component, plugin::WasmComponent,
+};
+
+/// The mod components of an entity in a scene, as their serialized values keyed by their key in
+/// the [`WasmComponentRegistry`].
+///
+/// It only exists in scenes: once the entity is spawned, it is replaced by the components.
+#[derive(Component, Clone, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct ModComponents(pub BTreeMap<String, String>);
+
+/// Adds the components of mods to a [`DynamicScene`].
+pub trait ModSceneExt {
+    /// Add the mod components of the entities in the scene, as they are in `world`.
+    ///
+    /// Entities that only have mod components are removed by
+    /// [`DynamicSceneBuilder::remove_empty_entities`](bevy::scene::DynamicSceneBuilder::remove_empty_entities),
+    /// so call it only if they don't matter.
+    fn extract_mod_components(&mut self, world: &World) -> &mut Self;
+}
+
+impl ModSceneExt for DynamicScene {
+    fn extract_mod_components(&mut self, world: &World) -> &mut Self {
+        let Some(registry) = world.get_resource::<WasmComponentRegistry>() else {
+            return self;
+        };
+
+        for scene_entity in &mut self.entities {
+            let Ok(entity) = world.get_entity(scene_entity.entity) else {
+                continue;
+            };
+
+            let components: BTreeMap<_, _> = registry
+                .iter()
+                .filter_map(|(key, id)| {
+                    let component = entity.get_by_id(id).ok()?;
+                    // SAFETY: every id in the registry is a `WasmComponent`
+                    let component = unsafe { component.deref::<WasmComponent>() };
+                    Some((key.to_string(), component.serialized_value.clone()))
+                })
+                .collect();
+            if !components.is_empty() {
+                scene_entity
+                    .components
+                    .push(Box::new(ModComponents(components)));
+            }
+        }
+
+        self
+    }
+}
+
+/// Replace the [`ModComponents`] of a spawned entity with the components of the mods.
+pub(crate) fn restore_mod_components(add: On<Add, ModComponents>, mut commands: Commands) {
+    let entity = add.entity;
+    commands.queue(move |world: &mut World| {
+        let Some(components) = world
+            .get_entity_mut(entity)
+            .ok()
+            .and_then(|mut entity| entity.take::<ModComponents>())
+        else {
+            return;
+        };
+
+        let type_registry = world.resource::<AppTypeRegistry>().clone();
+        for (key, serialized_value) in components.0 {
+            let restorable = world
+                .resource::<WasmComponentRegistry>()
+                .is_restorable_key(&key, &type_registry.read());
+            if !restorable {
+                warn!("Can't restore the component {key} of a scene: it isn't a mod component");
+                continue;
+            }
+
+            // The mod might not have registered its components yet, or not in the same order
+            let id = register_wasm_component(world, key);
+            insert_wasm_component_in_world(world, entity, id, serialized_value);
+        }
+    });
+}
+
+fn insert_wasm_component_in_world(
+    world: &mut World,
+    entity: Entity,
+    id: ComponentId,
+    serialized_value: String,
+) {
+    debug_assert!(
+        world.components().get_info(id).is_some_and(|info| {
+            info.type_id().is_none() && info.layout() == Layout::new::<WasmComponent>()
+        }),
+        "the component of the id must be a `WasmComponent`"
+    );
+
+    OwningPtr::make(WasmComponent { serialized_value }, |ptr| {
+        // SAFETY: the component of the id is a `WasmComponent`
+        unsafe {
+            world.entity_mut(entity).insert_by_id(id, ptr);
+        }
+    });
+}
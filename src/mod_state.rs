@@ -0,0 +1,148 @@
+use bevy::{asset::AssetLoadFailedEvent, prelude::*};
+
+use crate::{asset::WasmComponentAsset, mods::Mod};
+
+/// Where a mod is in its lifecycle.
+///
+/// This component lives on the mod entity next to its [`Name`] and its
+/// [`ModSignature`](crate::mod_trust::ModSignature), so a UI can show the status of every mod.
+/// Changes are also announced with the [`ModLoaded`], [`ModSetupComplete`], [`ModFailed`],
+/// [`ModReloaded`] and [`ModUnloaded`] messages.
+#[derive(Component, Clone, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub enum ModState {
+    /// The asset is being read and compiled.
+    #[default]
+    Loading,
+    /// The component compiled and its `setup` function is about to run.
+    Compiled,
+    /// The mod imports an interface that no loaded mod exports yet, see
+    /// [`linking`](crate::linking).
+    ///
+    /// `setup` runs once a mod that exports the interface is loaded.
+    WaitingForImports { missing: String },
+    /// `setup` ran successfully and the mod's systems are running.
+    Ready,
+    /// Compiling the component or running one of its functions failed.
+    ///
+    /// A failed mod doesn't run its systems until it is reloaded.
+    Failed { error: String },
+}
+
+/// Sent when the component of a mod compiled successfully.
+#[derive(Message, Clone, Debug)]
+pub struct ModLoaded {
+    pub entity: Entity,
+}
+
+/// Sent when the `setup` function of a mod ran successfully.
+#[derive(Message, Clone, Debug)]
+pub struct ModSetupComplete {
+    pub entity: Entity,
+}
+
+/// Sent when a mod failed to compile or one of its functions returned an error.
+#[derive(Message, Clone, Debug)]
+pub struct ModFailed {
+    pub entity: Entity,
+    pub error: String,
+}
+
+/// Sent when the file of a loaded mod changed and it was compiled again (hot-reloading).
+#[derive(Message, Clone, Debug)]
+pub struct ModReloaded {
+    pub entity: Entity,
+}
+
+/// Sent when a mod entity is despawned, which unloads its component.
+#[derive(Message, Clone, Debug)]
+pub struct ModUnloaded {
+    pub entity: Entity,
+}
+
+/// Updates the [`ModState`] of mods whose assets finished loading, were reloaded or failed.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn track_mod_loading(
+    mut commands: Commands,
+    assets: Res<Assets<WasmComponentAsset>>,
+    mut asset_events: MessageReader<AssetEvent<WasmComponentAsset>>,
+    mut failed_events: MessageReader<AssetLoadFailedEvent<WasmComponentAsset>>,
+    mut mods: Query<(Entity, &Mod, &mut ModState)>,
+    mut loaded: MessageWriter<ModLoaded>,
+    mut reloaded: MessageWriter<ModReloaded>,
+    mut failed: MessageWriter<ModFailed>,
+) {
+    for event in asset_events.read() {
+        match event {
+            AssetEvent::LoadedWithDependencies { id } => {
+                for (entity, _, mut state) in
+                    mods.iter_mut().filter(|(_, m, _)| m.asset.id() == *id)
+                {
+                    *state = ModState::Compiled;
+                    if let Some(asset) = assets.get(*id) {
+                        commands.entity(entity).insert(asset.signature);
+                    }
+                    loaded.write(ModLoaded { entity });
+                }
+            }
+            AssetEvent::Modified { id } => {
+                for (entity, _, mut state) in
+                    mods.iter_mut().filter(|(_, m, _)| m.asset.id() == *id)
+                {
+                    *state = ModState::Compiled;
+                    if let Some(asset) = assets.get(*id) {
+                        commands.entity(entity).insert(asset.signature);
+                    }
+                    reloaded.write(ModReloaded { entity });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for event in failed_events.read() {
+        for (entity, _, mut state) in mods.iter_mut().filter(|(_, m, _)| m.asset.id() == event.id) {
+            let error = event.error.to_string();
+            *state = ModState::Failed {
+                error: error.clone(),
+            };
+            failed.write(ModFailed { entity, error });
+        }
+    }
+}
+
+pub(crate) fn on_mod_removed(remove: On<Remove, Mod>, mut unloaded: MessageWriter<ModUnloaded>) {
+    unloaded.write(ModUnloaded {
+        entity: remove.entity,
+    });
+}
+
+/// Set the [`ModState`] of every mod that uses the given asset from an exclusive system.
+pub(crate) fn set_mod_state(
+    world: &mut World,
+    asset_id: AssetId<WasmComponentAsset>,
+    state: ModState,
+) {
+    let entities: Vec<Entity> = world
+        .query::<(Entity, &Mod)>()
+        .iter(world)
+        .filter(|(_, wasm_mod)| wasm_mod.asset.id() == asset_id)
+        .map(|(entity, _)| entity)
+        .collect();
+
+    for entity in entities {
+        world.entity_mut(entity).insert(state.clone());
+        match &state {
+            ModState::Ready => {
+                world.write_message(ModSetupComplete { entity });
+            }
+            ModState::Failed { error } => {
+                world.write_message(ModFailed {
+                    entity,
+                    error: error.clone(),
+                });
+            }
+            _ => {}
+        }
+    }
+}
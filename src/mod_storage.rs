@@ -0,0 +1,343 @@
+//! The data that mods keep between sessions through the `wasvy:ecs/storage` interface.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use bevy::prelude::*;
+use thiserror::Error;
+
+use crate::bindings::wasvy::ecs::storage::StorageError;
+
+/// The key-value stores of mods, which are saved to a file per mod in the save directory.
+///
+/// The store of a mod is keyed by its id, the name of its file without the extension (e.g.
+/// `space_ship` for `mods/space_ship.wasm`), so it's kept when the mod is updated. The file of
+/// the store is `<directory>/<id>.json`.
+///
+/// A file that isn't valid JSON is moved to `<id>.json.corrupt` and the mod starts with an empty
+/// store. If the file can't be read or moved, the mod can't save anything until
+/// [`ModStorage::clear`] is called, so its data is never overwritten.
+///
+/// Insert your own to change where the data is saved or the quota of mods:
+///
+/// ```rust,ignore
+/// app.insert_resource(ModStorage::new("saves/mods").with_quota(64 * 1024));
+/// ```
+#[derive(Resource, Clone)]
+pub struct ModStorage {
+    directory: PathBuf,
+    quota: usize,
+    /// The stores that were read from their file, keyed by mod id.
+    stores: Arc<Mutex<HashMap<String, Store>>>,
+}
+
+/// The data of a mod, as it was read from its file.
+#[derive(Default)]
+struct Store {
+    data: BTreeMap<String, String>,
+    /// Why the file of the mod couldn't be read, in which case it must not be overwritten.
+    unreadable: Option<String>,
+}
+
+/// Possible errors that can be produced by [`ModStorage`]
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum ModStorageError {
+    /// The keys and values of the mod would be larger than its quota
+    #[error("The data of the mod would be larger than its quota of {quota} bytes")]
+    QuotaExceeded { quota: usize },
+    /// An [IO](std::io) Error
+    #[error("Could not save the data of the mod: {0}")]
+    Io(#[from] io::Error),
+    /// The file of the mod couldn't be read, so it isn't overwritten
+    #[error("The saved data of the mod can't be read, clear it to save again: {0}")]
+    Unreadable(String),
+}
+
+impl From<ModStorageError> for StorageError {
+    fn from(err: ModStorageError) -> Self {
+        match err {
+            ModStorageError::QuotaExceeded { quota } => StorageError::QuotaExceeded(quota as u64),
+            ModStorageError::Io(err) => StorageError::Io(err.to_string()),
+            ModStorageError::Unreadable(_) => StorageError::Io(err.to_string()),
+        }
+    }
+}
+
+impl Default for ModStorage {
+    fn default() -> Self {
+        Self::new("saves/mods")
+    }
+}
+
+impl ModStorage {
+    /// The quota of mods if it isn't set with [`ModStorage::with_quota`], 1 MiB.
+    pub const DEFAULT_QUOTA: usize = 1024 * 1024;
+
+    /// Save the data of mods in `directory`, which is created when the first mod saves something.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            quota: Self::DEFAULT_QUOTA,
+            stores: Arc::default(),
+        }
+    }
+
+    /// Limit the size of the keys and values of each mod to `quota` bytes.
+    pub fn with_quota(mut self, quota: usize) -> Self {
+        self.quota = quota;
+        self
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    pub fn quota(&self) -> usize {
+        self.quota
+    }
+
+    /// The value the mod stored under `key`.
+    pub fn get(&self, mod_id: &str, key: &str) -> Option<String> {
+        self.with_store(mod_id, |store| store.data.get(key).cloned())
+    }
+
+    /// All the keys and values of a mod.
+    pub fn data(&self, mod_id: &str) -> BTreeMap<String, String> {
+        self.with_store(mod_id, |store| store.data.clone())
+    }
+
+    /// The size of the keys and values of a mod, which counts towards its quota.
+    pub fn size(&self, mod_id: &str) -> usize {
+        self.with_store(mod_id, |store| size(&store.data))
+    }
+
+    /// Store `value` under `key` for the mod, and save its data.
+    pub fn set(&self, mod_id: &str, key: &str, value: &str) -> Result<(), ModStorageError> {
+        self.update(mod_id, |store| {
+            store.insert(key.to_string(), value.to_string());
+        })
+    }
+
+    /// Remove the value under `key` for the mod, and save its data.
+    pub fn remove(&self, mod_id: &str, key: &str) -> Result<(), ModStorageError> {
+        self.update(mod_id, |store| {
+            store.remove(key);
+        })
+    }
+
+    /// Delete all the data of a mod, including its file.
+    ///
+    /// This also lets the mod save again if its file couldn't be read. A `<id>.json.corrupt` file
+    /// is kept for the game to inspect.
+    pub fn clear(&self, mod_id: &str) -> io::Result<()> {
+        let mut stores = self.stores.lock().unwrap();
+        stores.remove(mod_id);
+        match fs::remove_file(self.path(mod_id)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    fn path(&self, mod_id: &str) -> PathBuf {
+        self.directory.join(format!("{mod_id}.json"))
+    }
+
+    fn with_store<T>(&self, mod_id: &str, f: impl FnOnce(&mut Store) -> T) -> T {
+        let mut stores = self.stores.lock().unwrap();
+        let store = stores
+            .entry(mod_id.to_string())
+            .or_insert_with(|| self.read(mod_id));
+        f(store)
+    }
+
+    /// Change the store of a mod and save it, unless it would exceed the quota.
+    fn update(
+        &self,
+        mod_id: &str,
+        change: impl FnOnce(&mut BTreeMap<String, String>),
+    ) -> Result<(), ModStorageError> {
+        // The lock is held while the file is written, so saves of a mod can't overtake each other
+        self.with_store(mod_id, |store| {
+            if let Some(reason) = &store.unreadable {
+                return Err(ModStorageError::Unreadable(reason.clone()));
+            }
+
+            let mut changed = store.data.clone();
+            change(&mut changed);
+            // Changes that shrink the data are fine, in case the quota was lowered
+            if size(&changed) > self.quota && size(&changed) > size(&store.data) {
+                return Err(ModStorageError::QuotaExceeded { quota: self.quota });
+            }
+
+            self.write(mod_id, &changed)?;
+            store.data = changed;
+            Ok(())
+        })
+    }
+
+    fn read(&self, mod_id: &str) -> Store {
+        let path = self.path(mod_id);
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Store::default(),
+            Err(err) => {
+                error!("Could not read the data of the mod {mod_id}: {err}");
+                return Store {
+                    data: BTreeMap::new(),
+                    unreadable: Some(err.to_string()),
+                };
+            }
+        };
+
+        let err = match serde_json::from_slice(&bytes) {
+            Ok(data) => {
+                return Store {
+                    data,
+                    unreadable: None,
+                };
+            }
+            Err(err) => err,
+        };
+
+        // The file is kept, the mod would replace it with its next change otherwise
+        let corrupt = self.directory.join(format!("{mod_id}.json.corrupt"));
+        match fs::rename(&path, &corrupt) {
+            Ok(()) => {
+                error!(
+                    "The data of the mod {mod_id} is invalid, it was moved to {}: {err}",
+                    corrupt.display()
+                );
+                Store::default()
+            }
+            Err(rename_err) => {
+                error!("Could not move the invalid data of the mod {mod_id} ({err}): {rename_err}");
+                Store {
+                    data: BTreeMap::new(),
+                    unreadable: Some(err.to_string()),
+                }
+            }
+        }
+    }
+
+    /// Replace the file of the mod, by writing the data to a temporary file that is renamed once
+    /// it's complete, so a crash never leaves a partially written file.
+    fn write(&self, mod_id: &str, store: &BTreeMap<String, String>) -> io::Result<()> {
+        fs::create_dir_all(&self.directory)?;
+        let path = self.path(mod_id);
+        let temporary = self.directory.join(format!("{mod_id}.json.tmp"));
+
+        let mut file = File::create(&temporary)?;
+        file.write_all(&serde_json::to_vec(store)?)?;
+        file.sync_all()?;
+        fs::rename(&temporary, &path)
+    }
+}
+
+/// The size of the keys and values of a store.
+fn size(store: &BTreeMap<String, String>) -> usize {
+    store
+        .iter()
+        .map(|(key, value)| key.len() + value.len())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty directory for the test with the given name.
+    fn directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("wasvy-storage-{name}"));
+        let _ = fs::remove_dir_all(&directory);
+        directory
+    }
+
+    #[test]
+    fn quota() {
+        let storage = ModStorage::new(directory("quota")).with_quota(10);
+        storage.set("a", "key", "value").unwrap();
+        assert!(matches!(
+            storage.set("a", "other", "value"),
+            Err(ModStorageError::QuotaExceeded { quota: 10 })
+        ));
+        assert_eq!(storage.size("a"), 8);
+        assert_eq!(storage.get("a", "other"), None);
+
+        // Every mod has its own quota
+        storage.set("b", "key", "value").unwrap();
+    }
+
+    #[test]
+    fn shrinking_is_allowed_over_the_quota() {
+        let directory = directory("shrink");
+        let storage = ModStorage::new(&directory);
+        storage.set("a", "key", "a long value").unwrap();
+
+        // The quota was lowered since the data was saved
+        let storage = ModStorage::new(&directory).with_quota(5);
+        assert_eq!(storage.size("a"), 15);
+        assert!(storage.set("a", "key", "a longer value").is_err());
+        storage.set("a", "key", "short").unwrap();
+        assert!(storage.set("a", "more", "x").is_err());
+        storage.remove("a", "key").unwrap();
+        assert_eq!(storage.size("a"), 0);
+    }
+
+    #[test]
+    fn writes_replace_the_file() {
+        let directory = directory("write");
+        fs::create_dir_all(&directory).unwrap();
+        // A partial write of a previous session that crashed
+        fs::write(directory.join("a.json.tmp"), "{\"key\":").unwrap();
+
+        let storage = ModStorage::new(&directory);
+        storage.set("a", "key", "value").unwrap();
+        assert!(!directory.join("a.json.tmp").exists());
+        assert_eq!(
+            fs::read_to_string(directory.join("a.json")).unwrap(),
+            r#"{"key":"value"}"#
+        );
+
+        let storage = ModStorage::new(&directory);
+        assert_eq!(storage.get("a", "key").as_deref(), Some("value"));
+    }
+
+    #[test]
+    fn corrupt_files_are_kept() {
+        let directory = directory("corrupt");
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("a.json"), "{not json").unwrap();
+
+        let storage = ModStorage::new(&directory);
+        assert_eq!(storage.get("a", "key"), None);
+        assert_eq!(
+            fs::read_to_string(directory.join("a.json.corrupt")).unwrap(),
+            "{not json"
+        );
+        storage.set("a", "key", "value").unwrap();
+        assert_eq!(
+            fs::read_to_string(directory.join("a.json.corrupt")).unwrap(),
+            "{not json"
+        );
+    }
+
+    #[test]
+    fn unreadable_files_are_not_overwritten() {
+        let directory = directory("unreadable");
+        // Reading a directory fails with another error than `NotFound`
+        fs::create_dir_all(directory.join("a.json")).unwrap();
+
+        let storage = ModStorage::new(&directory);
+        assert!(matches!(
+            storage.set("a", "key", "value"),
+            Err(ModStorageError::Unreadable(_))
+        ));
+        assert!(directory.join("a.json").is_dir());
+    }
+}
@@ -0,0 +1,192 @@
+//! Telling apart the mods of trusted publishers from the others with ed25519 signatures.
+//!
+//! Publishers sign their mods with their ed25519 key, and games trust the public keys of the
+//! publishers they know with [`ModloaderPlugin::trust_key`](crate::plugin::ModloaderPlugin::trust_key).
+//!
+//! - A `.wasm` mod is signed by a file next to it with the `.sig` extension (e.g.
+//!   `space_ship.wasm.sig`), which holds the signature of the bytes of the component.
+//! - A [mod package](crate::mod_package) is signed by the `manifest.sig` file in the package,
+//!   which holds the signature of its `manifest.json`. The manifest has the hash of the component
+//!   and every other file, so the signature covers the whole package.
+//!
+//! Signature files contain the 64 bytes of the signature, either as is or as hex.
+//!
+//! What happens to the mods that aren't signed by a trusted key is decided by the
+//! [`UnsignedModPolicy`], and the result is in the [`ModSignature`] component of mod entities.
+
+use bevy::prelude::*;
+use ed25519_dalek::{Signature, VerifyingKey};
+
+/// Whether a mod is signed by a trusted key.
+///
+/// This component lives on the mod entity next to its [`ModState`](crate::mod_state::ModState)
+/// once the mod is loaded.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub enum ModSignature {
+    /// The mod isn't signed.
+    #[default]
+    Unsigned,
+    /// The mod is signed, but not by a trusted key, or it was changed after it was signed.
+    Untrusted,
+    /// The mod is signed by one of the trusted keys.
+    Trusted,
+}
+
+/// What to do with mods that aren't signed by a trusted key.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnsignedModPolicy {
+    /// Run them like trusted mods.
+    #[default]
+    Allow,
+    /// Run them in a stricter sandbox, without network access.
+    Restrict,
+    /// Don't load them, their [`ModState`](crate::mod_state::ModState) is `Failed`.
+    Refuse,
+}
+
+/// The keys the game trusts and its [`UnsignedModPolicy`], which the mod loaders check mods with.
+#[derive(Clone, Debug, Default)]
+pub struct ModTrust {
+    pub keys: Vec<VerifyingKey>,
+    pub policy: UnsignedModPolicy,
+}
+
+impl ModTrust {
+    /// Check the signature file of a mod (if it has one) against the signed `message`.
+    pub fn verify(&self, message: &[u8], signature: Option<&[u8]>) -> ModSignature {
+        let Some(signature) = signature else {
+            return ModSignature::Unsigned;
+        };
+        let Some(signature) = parse_signature(signature) else {
+            return ModSignature::Untrusted;
+        };
+
+        let trusted = self
+            .keys
+            .iter()
+            .any(|key| key.verify_strict(message, &signature).is_ok());
+        if trusted {
+            ModSignature::Trusted
+        } else {
+            ModSignature::Untrusted
+        }
+    }
+
+    /// Whether a mod with the given signature is loaded.
+    pub fn allows(&self, signature: ModSignature) -> bool {
+        signature == ModSignature::Trusted || self.policy != UnsignedModPolicy::Refuse
+    }
+
+    /// Whether a mod with the given signature runs in the stricter sandbox.
+    pub fn restricts(&self, signature: ModSignature) -> bool {
+        signature != ModSignature::Trusted && self.policy == UnsignedModPolicy::Restrict
+    }
+}
+
+/// A signature file, with the signature as is or as hex.
+fn parse_signature(bytes: &[u8]) -> Option<Signature> {
+    if let Ok(bytes) = <[u8; Signature::BYTE_SIZE]>::try_from(bytes) {
+        return Some(Signature::from_bytes(&bytes));
+    }
+
+    let hex = std::str::from_utf8(bytes).ok()?.trim();
+    if hex.len() != Signature::BYTE_SIZE * 2 || !hex.is_ascii() {
+        return None;
+    }
+    let mut signature = [0; Signature::BYTE_SIZE];
+    for (byte, digits) in signature.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(digits).ok()?, 16).ok()?;
+    }
+    Some(Signature::from_bytes(&signature))
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::{Signer, SigningKey};
+
+    use super::*;
+
+    const MESSAGE: &[u8] = b"the bytes of the mod";
+
+    fn key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    fn trust(policy: UnsignedModPolicy) -> ModTrust {
+        ModTrust {
+            keys: vec![key(1).verifying_key()],
+            policy,
+        }
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    #[test]
+    fn raw_and_hex_signatures() {
+        let trust = trust(UnsignedModPolicy::Allow);
+        let signature = key(1).sign(MESSAGE).to_bytes();
+
+        assert_eq!(
+            trust.verify(MESSAGE, Some(&signature)),
+            ModSignature::Trusted
+        );
+        let hex = format!("{}\n", hex(&signature));
+        assert_eq!(
+            trust.verify(MESSAGE, Some(hex.as_bytes())),
+            ModSignature::Trusted
+        );
+        let upper = hex.to_uppercase();
+        assert_eq!(
+            trust.verify(MESSAGE, Some(upper.as_bytes())),
+            ModSignature::Trusted
+        );
+    }
+
+    #[test]
+    fn invalid_signatures_are_untrusted() {
+        let trust = trust(UnsignedModPolicy::Allow);
+        let signature = key(1).sign(MESSAGE).to_bytes();
+
+        assert_eq!(trust.verify(MESSAGE, None), ModSignature::Unsigned);
+        assert_eq!(
+            trust.verify(b"the bytes of another mod", Some(&signature)),
+            ModSignature::Untrusted
+        );
+        let untrusted = key(2).sign(MESSAGE).to_bytes();
+        assert_eq!(
+            trust.verify(MESSAGE, Some(&untrusted)),
+            ModSignature::Untrusted
+        );
+        assert_eq!(
+            trust.verify(MESSAGE, Some(b"not a signature")),
+            ModSignature::Untrusted
+        );
+        let not_hex = "zz".repeat(Signature::BYTE_SIZE);
+        assert_eq!(
+            trust.verify(MESSAGE, Some(not_hex.as_bytes())),
+            ModSignature::Untrusted
+        );
+    }
+
+    #[test]
+    fn policies() {
+        use ModSignature::*;
+
+        let allow = trust(UnsignedModPolicy::Allow);
+        let restrict = trust(UnsignedModPolicy::Restrict);
+        let refuse = trust(UnsignedModPolicy::Refuse);
+        for signature in [Unsigned, Untrusted, Trusted] {
+            assert!(allow.allows(signature));
+            assert!(!allow.restricts(signature));
+            assert!(restrict.allows(signature));
+            assert_eq!(refuse.allows(signature), signature == Trusted);
+            assert!(!refuse.restricts(signature));
+        }
+        assert!(restrict.restricts(Unsigned));
+        assert!(restrict.restricts(Untrusted));
+        assert!(!restrict.restricts(Trusted));
+    }
+}
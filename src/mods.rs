@@ -1,40 +1,289 @@
-use bevy::{asset::AssetPath, ecs::system::SystemParam, prelude::*};
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
 
-use crate::asset::WasmComponentAsset;
+use bevy::{
+    asset::{AssetPath, LoadedFolder},
+    ecs::system::SystemParam,
+    prelude::*,
+};
+
+use crate::{
+    asset::WasmComponentAsset, asset_source::ModAssetRoots, mod_list::ModList,
+    mod_package::PACKAGE_EXTENSION, mod_state::ModState,
+};
 
 /// This system param provides an interface to load and manage Wasvy mods
 #[derive(SystemParam)]
 pub struct Mods<'w, 's> {
     commands: Commands<'w, 's>,
     asset_server: Res<'w, AssetServer>,
+    mod_list: ResMut<'w, ModList>,
+    mod_asset_roots: Res<'w, ModAssetRoots>,
     mods: Query<'w, 's, Entity, With<Mod>>,
+    folders: Query<'w, 's, Entity, With<ModFolder>>,
 }
 
 /// Bevy drops assets if there are no active handles
 /// so this component exists to keep the handles alive.
 #[derive(Component, Reflect)]
-struct Mod {
+#[require(ModState)]
+pub(crate) struct Mod {
     pub asset: Handle<WasmComponentAsset>,
+    /// Disabled mods stay loaded, but their systems don't run.
+    ///
+    /// This is kept in sync with the [`ModList`].
+    pub enabled: bool,
+}
+
+impl Mod {
+    fn new(asset: Handle<WasmComponentAsset>) -> Self {
+        Self {
+            asset,
+            enabled: true,
+        }
+    }
+}
+
+/// A directory of mods loaded with [`Mods::load_folder`].
+///
+/// Every mod found in the folder is spawned as a child of this entity.
+#[derive(Component, Reflect)]
+pub(crate) struct ModFolder {
+    pub folder: Handle<LoadedFolder>,
+    #[reflect(ignore)]
+    pub filter: ModFilter,
+}
+
+/// Decides which files of a folder are loaded as mods by [`Mods::load_folder_filtered`].
+///
+/// Patterns are matched against the path of the file relative to the loaded folder
+/// (e.g. `"physics/gravity.wasm"`) and support `*` as a wildcard for any number of characters.
+///
+/// A file is loaded if it matches at least one `include` pattern (or there are none)
+/// and doesn't match any `exclude` pattern.
+///
+/// ```rust
+/// # use wasvy::mods::ModFilter;
+/// let filter = ModFilter::default()
+///     .include("*.wasm")
+///     .exclude("experimental/*");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ModFilter {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl ModFilter {
+    /// Only load files that match the given pattern.
+    pub fn include(mut self, pattern: impl Into<String>) -> Self {
+        self.include.push(pattern.into());
+        self
+    }
+
+    /// Skip files that match the given pattern.
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.exclude.push(pattern.into());
+        self
+    }
+
+    /// Returns true if the file at the given path (relative to the folder) should be loaded.
+    pub fn matches(&self, path: &Path) -> bool {
+        let path = path.to_string_lossy().replace('\\', "/");
+
+        let included = self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|pattern| wildcard_match(pattern, &path));
+        let excluded = self
+            .exclude
+            .iter()
+            .any(|pattern| wildcard_match(pattern, &path));
+
+        included && !excluded
+    }
 }
 
 impl Mods<'_, '_> {
     /// Load a single wasm file from the given path.
+    ///
+    /// The directory next to it with the name of the file (e.g. `mods/space_ship/` for
+    /// `mods/space_ship.wasm`) holds the assets of the mod, which are available in the `mod://`
+    /// asset source as `mod://space_ship/...` if the
+    /// [`ModAssetSourcePlugin`](crate::asset_source::ModAssetSourcePlugin) was added.
+    ///
+    /// The path can also be a [mod package](crate::mod_package) (e.g. `mods/space_ship.wasvy`),
+    /// which has its assets inside.
+    ///
+    /// Mods are identified by the name of their file without the extension, so a mod isn't loaded
+    /// if another mod with the same id is (e.g. `a/space_ship.wasm` and `b/space_ship.wasm`).
     pub fn load<'a>(&mut self, path: impl Into<AssetPath<'a>>) {
         let path: AssetPath = path.into();
-        let name = path
-            .path()
-            .file_name()
-            .and_then(|s| s.to_str())
-            .map(|s| s.to_string())
-            .unwrap_or("unknown".to_string());
+        let name = mod_name(&path);
+        if let Err(err) = self.mod_asset_roots.register(&path) {
+            error!("{err}");
+            return;
+        }
         let asset = self.asset_server.load::<WasmComponentAsset>(path);
-        self.commands.spawn((Name::new(name), Mod { asset }));
+        self.commands.spawn((Name::new(name), Mod::new(asset)));
+    }
+
+    /// Load every wasm file and [mod package](crate::mod_package) in the given directory
+    /// (including subdirectories).
+    ///
+    /// When Bevy's file watcher is enabled, mods that are dropped into the folder while the
+    /// game is running are loaded automatically, and mods that are deleted from it are unloaded.
+    pub fn load_folder<'a>(&mut self, path: impl Into<AssetPath<'a>>) {
+        self.load_folder_filtered(path, ModFilter::default());
+    }
+
+    /// Same as [`Mods::load_folder`], but only the files accepted by the [`ModFilter`] are loaded as mods.
+    pub fn load_folder_filtered<'a>(&mut self, path: impl Into<AssetPath<'a>>, filter: ModFilter) {
+        let path: AssetPath = path.into();
+        let name = format!("{} (folder)", path.path().display());
+        let folder = self.asset_server.load_folder(path);
+        self.commands
+            .spawn((Name::new(name), ModFolder { folder, filter }));
+    }
+
+    /// Stop running the systems of the mod with the given id, without unloading it.
+    ///
+    /// The id of a mod is the name of its file without the extension (e.g. `"simple"` for
+    /// `mods/simple.wasm`).
+    pub fn disable(&mut self, mod_id: &str) {
+        self.mod_list.set_enabled(mod_id, false);
+    }
+
+    /// Resume running the systems of a mod that was disabled with [`Mods::disable`].
+    pub fn enable(&mut self, mod_id: &str) {
+        self.mod_list.set_enabled(mod_id, true);
+    }
+
+    /// Stop running a single system of the mod with the given id.
+    pub fn disable_system(&mut self, mod_id: &str, system_name: &str) {
+        self.mod_list.set_system_enabled(mod_id, system_name, false);
+    }
+
+    /// Resume running a system that was disabled with [`Mods::disable_system`].
+    pub fn enable_system(&mut self, mod_id: &str, system_name: &str) {
+        self.mod_list.set_system_enabled(mod_id, system_name, true);
+    }
+
+    /// The enabled state of all mods and their systems, for persisting between sessions.
+    pub fn mod_list(&self) -> &ModList {
+        &self.mod_list
     }
 
     /// Unload all currently loaded mods.
     pub fn clear(&mut self) {
-        for entity in self.mods.iter() {
-            self.commands.entity(entity).despawn();
+        for entity in self.mods.iter().chain(self.folders.iter()) {
+            self.commands.entity(entity).try_despawn();
         }
     }
 }
+
+/// Spawns a [`Mod`] for every new mod file that shows up in a [`ModFolder`], and despawns the
+/// mods whose file was removed from it.
+pub(crate) fn load_folder_mods(
+    mut commands: Commands,
+    mut events: MessageReader<AssetEvent<LoadedFolder>>,
+    loaded_folders: Res<Assets<LoadedFolder>>,
+    folders: Query<(Entity, &ModFolder, Option<&Children>)>,
+    mods: Query<&Mod>,
+    mod_asset_roots: Res<ModAssetRoots>,
+) {
+    let changed_folders: HashSet<AssetId<LoadedFolder>> = events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    for (entity, mod_folder, children) in folders.iter() {
+        if !changed_folders.contains(&mod_folder.folder.id()) {
+            continue;
+        }
+        let Some(loaded_folder) = loaded_folders.get(&mod_folder.folder) else {
+            continue;
+        };
+        let Some(folder_path) = mod_folder.folder.path() else {
+            continue;
+        };
+
+        let already_loaded: HashMap<AssetId<WasmComponentAsset>, Entity> = children
+            .map(|children| children.to_vec())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|child| Some((mods.get(child).ok()?.asset.id(), child)))
+            .collect();
+
+        let mut in_folder = HashSet::new();
+        for handle in &loaded_folder.handles {
+            let Ok(asset) = handle.clone().try_typed::<WasmComponentAsset>() else {
+                continue;
+            };
+            let Some(path) = asset.path() else {
+                continue;
+            };
+            let relative_path = path
+                .path()
+                .strip_prefix(folder_path.path())
+                .unwrap_or(path.path());
+
+            if !is_mod_file(relative_path)
+                || !mod_folder.filter.matches(relative_path)
+                || !in_folder.insert(asset.id())
+                || already_loaded.contains_key(&asset.id())
+            {
+                continue;
+            }
+
+            if let Err(err) = mod_asset_roots.register(path) {
+                error!("{err}");
+                continue;
+            }
+            commands.spawn((Name::new(mod_name(path)), Mod::new(asset), ChildOf(entity)));
+        }
+
+        // The file of the mod was deleted, or doesn't pass the filter anymore
+        for (id, child) in already_loaded {
+            if !in_folder.contains(&id) {
+                commands.entity(child).try_despawn();
+            }
+        }
+    }
+}
+
+/// Returns true for the files that are loaded as mods: wasm files and mod packages, which have
+/// their manifest inside.
+fn is_mod_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension == "wasm" || extension == PACKAGE_EXTENSION)
+}
+
+fn mod_name(path: &AssetPath) -> String {
+    path.path()
+        .file_name()
+        .and_then(|s| s.to_str())
+        .map(|s| s.to_string())
+        .unwrap_or("unknown".to_string())
+}
+
+/// Matches `text` against `pattern`, where `*` matches any number of characters.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let Some((prefix, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+    let Some(text) = text.strip_prefix(prefix) else {
+        return false;
+    };
+
+    (0..=text.len())
+        .filter(|i| text.is_char_boundary(*i))
+        .any(|i| wildcard_match(rest, &text[i..]))
+}
@@ -1,16 +1,37 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 
 use bevy::{ecs::system::SystemState, prelude::*};
+use ed25519_dalek::{PUBLIC_KEY_LENGTH, VerifyingKey};
 use serde::{Deserialize, Serialize};
 
 use crate::{
     asset::{WasmComponentAsset, WasmComponentAssetLoader},
+    asset_source::{ModAssetRoots, unregister_mod_asset_root},
     bindings,
     component_registry::WasmComponentRegistry,
+    determinism::{Determinism, ModRng, ModTime, update_mod_time, wasi_seed},
     host::WasmHost,
+    host_interfaces::{HostInterfaces, ModloaderAppExt},
+    linking::{ModInterfaces, register_mod_interfaces},
+    mod_assets::{ModAssets, release_mod_assets},
+    mod_list::{ModList, apply_mod_list},
+    mod_package::{ModPackageLoader, PackageLimits},
+    mod_permissions::{ModPermissions, SpawnedBy},
+    mod_scene::{ModComponents, restore_mod_components},
+    mod_state::{
+        ModFailed, ModLoaded, ModReloaded, ModSetupComplete, ModState, ModUnloaded, on_mod_removed,
+        set_mod_state, track_mod_loading,
+    },
+    mod_storage::ModStorage,
+    mod_trust::{ModSignature, ModTrust, UnsignedModPolicy},
+    mods::load_folder_mods,
     runner::{Runner, WasmRunState},
     state::States,
-    systems::{WasmGuestSystem, WasmSystemWithParams},
+    systems::{
+        GuestRunner, GuestSchedules, GuestSystems, WasmGuestSystem, despawn_guest_systems,
+        despawn_removed_mod_systems, link_guest_systems, run_guest_schedules,
+        schedule_guest_systems,
+    },
 };
 
 /// This plugin adds Wasvy modding support to [`App`]
@@ -18,13 +39,50 @@ use crate::{
 /// ```rust
 ///  App::new()
 ///    .add_plugins(DefaultPlugins)
-///    .add_plugins(ModloaderPlugin)
+///    .add_plugins(ModloaderPlugin::default())
 ///    // etc
 /// ```
 ///
 /// Looking for next steps? See: [`Mods`](crate::mods::Mods)
 /// ```
-pub struct ModloaderPlugin;
+#[derive(Default)]
+pub struct ModloaderPlugin {
+    /// The ed25519 public keys of the publishers whose mods are trusted, see
+    /// [`mod_trust`](crate::mod_trust).
+    pub trusted_keys: Vec<[u8; PUBLIC_KEY_LENGTH]>,
+    /// What to do with mods that aren't signed by one of the `trusted_keys`.
+    pub unsigned_mods: UnsignedModPolicy,
+    /// Run mods in deterministic mode, see [`determinism`](crate::determinism).
+    pub deterministic: Option<Determinism>,
+    /// How large mod packages can be once extracted, see [`mod_package`](crate::mod_package).
+    pub package_limits: PackageLimits,
+}
+
+impl ModloaderPlugin {
+    /// Trust the mods signed by the given ed25519 public key.
+    pub fn trust_key(mut self, key: [u8; PUBLIC_KEY_LENGTH]) -> Self {
+        self.trusted_keys.push(key);
+        self
+    }
+
+    /// Decide what to do with mods that aren't signed by a trusted key.
+    pub fn unsigned_mods(mut self, policy: UnsignedModPolicy) -> Self {
+        self.unsigned_mods = policy;
+        self
+    }
+
+    /// Run mods the same way on every machine, see [`determinism`](crate::determinism).
+    pub fn deterministic(mut self, determinism: Determinism) -> Self {
+        self.deterministic = Some(determinism);
+        self
+    }
+
+    /// Set how large mod packages can be once extracted.
+    pub fn package_limits(mut self, limits: PackageLimits) -> Self {
+        self.package_limits = limits;
+        self
+    }
+}
 
 /// Cross engine instatiation of WASM components is not supported.
 /// This resources is the global [`Engine`] that is used for instatiation.
@@ -49,19 +107,95 @@ pub struct WasmComponent {
 
 impl Plugin for ModloaderPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (run_setup, run_systems));
+        app.add_systems(PreUpdate, (update_mod_time, schedule_guest_systems));
+        app.add_systems(
+            Update,
+            (
+                load_folder_mods,
+                track_mod_loading.before(run_setup),
+                register_mod_interfaces.before(run_setup),
+                run_setup,
+                apply_mod_list.before(GuestSystems),
+                link_guest_systems.after(run_setup).before(GuestSystems),
+                run_guest_schedules.in_set(GuestSystems),
+            ),
+        );
+        app.configure_sets(Update, GuestSystems.after(run_setup));
         app.register_type::<WasmGuestSystem>();
         app.register_type::<WasmComponent>();
+        app.register_type::<ModList>();
+        app.register_type::<ModState>();
+        app.register_type::<ModSignature>();
+        app.register_type::<SpawnedBy>();
+        app.register_type::<ModComponents>();
 
-        let engine = wasmtime::Engine::default();
+        app.add_message::<ModLoaded>()
+            .add_message::<ModSetupComplete>()
+            .add_message::<ModFailed>()
+            .add_message::<ModReloaded>()
+            .add_message::<ModUnloaded>()
+            .add_observer(on_mod_removed)
+            .add_observer(release_mod_assets)
+            .add_observer(unregister_mod_asset_root)
+            .add_observer(despawn_removed_mod_systems)
+            .add_observer(restore_mod_components);
+
+        let mut config = wasmtime::Config::new();
+        if self.deterministic.is_some() {
+            config
+                .cranelift_nan_canonicalization(true)
+                .relaxed_simd_deterministic(true);
+        }
+        let engine = wasmtime::Engine::new(&config).expect("Could not create the wasmtime engine");
+        let trust = ModTrust {
+            keys: self
+                .trusted_keys
+                .iter()
+                .map(|key| {
+                    VerifyingKey::from_bytes(key).expect("A trusted key isn't a valid ed25519 key")
+                })
+                .collect(),
+            policy: self.unsigned_mods,
+        };
+        let mod_asset_roots = app
+            .world_mut()
+            .get_resource_or_init::<ModAssetRoots>()
+            .clone();
 
         app.init_asset::<WasmComponentAsset>()
             .register_asset_loader(WasmComponentAssetLoader {
                 engine: engine.clone(),
+                trust: trust.clone(),
+            })
+            .register_asset_loader(ModPackageLoader {
+                component_loader: WasmComponentAssetLoader {
+                    engine: engine.clone(),
+                    trust: trust.clone(),
+                },
+                roots: mod_asset_roots,
+                limits: self.package_limits,
             });
 
         app.insert_resource(Engine(engine))
-            .init_resource::<WasmComponentRegistry>();
+            .init_resource::<WasmComponentRegistry>()
+            .init_resource::<ModList>()
+            .init_resource::<ModInterfaces>()
+            .init_resource::<HostInterfaces>()
+            .init_resource::<ModStorage>()
+            .init_resource::<ModPermissions>()
+            .init_resource::<ModTime>()
+            .init_resource::<GuestSchedules>()
+            .init_resource::<GuestRunner>();
+
+        match self.deterministic {
+            Some(determinism) => {
+                app.insert_resource(ModRng::new(determinism.seed))
+                    .insert_resource(determinism);
+            }
+            None => {
+                app.init_resource::<ModRng>();
+            }
+        }
 
         let asset_plugins = app.get_added_plugins::<AssetPlugin>();
         let asset_plugin = asset_plugins
@@ -86,92 +220,97 @@ impl Plugin for ModloaderPlugin {
                 );
             }
         }
-    }
-}
-
-fn run_systems(world: &mut World) {
-    let wasm_systems: Vec<WasmGuestSystem> = world
-        .query::<&WasmGuestSystem>()
-        .iter(world)
-        .cloned()
-        .collect();
 
-    let assests: HashMap<AssetId<WasmComponentAsset>, WasmComponentAsset> = world
-        .get_resource::<Assets<WasmComponentAsset>>()
-        .unwrap()
-        .iter()
-        .map(|(id, asset)| (id, asset.clone()))
-        .collect();
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        app.insert_resource(ModAssets::new(asset_server));
 
-    let engine = world.get_resource::<Engine>().unwrap().clone();
+        // The asset types that mods can use in their components right away
+        app.register_mod_asset::<Image>()
+            .register_mod_asset::<Scene>()
+            .register_mod_asset::<AudioSource>()
+            .register_mod_asset::<Font>();
+    }
+}
 
-    let runner = create_runner(engine.0);
+/// The asset events [`run_setup`] reads, kept between runs so every event is only read once.
+type SetupAssetEvents = SystemState<(
+    EventReader<'static, 'static, AssetEvent<WasmComponentAsset>>,
+    ResMut<'static, Assets<WasmComponentAsset>>,
+)>;
 
-    let systems: Vec<WasmSystemWithParams> = wasm_systems
-        .into_iter()
-        .map(|system| WasmSystemWithParams::new(system, world))
-        .collect();
+fn run_setup(
+    world: &mut World,
+    asset_events: &mut SetupAssetEvents,
+    mut already_ran: Local<HashSet<AssetId<WasmComponentAsset>>>,
+    mut waiting_for_imports: Local<HashSet<AssetId<WasmComponentAsset>>>,
+) {
+    let mut assets_to_setup = get_assets_to_setup(world, asset_events, &mut already_ran);
 
-    for wasm_system in systems.iter() {
-        let wasm_host = WasmHost {
-            world,
-            wasm_asset_id: wasm_system.system.wasm_asset_id,
-        };
-        let wasi_view = States::new(wasm_host);
-        let store = wasmtime::Store::new(&runner.engine, wasi_view);
-        let module = assests.get(&wasm_system.system.wasm_asset_id).unwrap();
-
-        let mut results = vec![];
-        runner.run_function(WasmRunState {
-            component: &module.component,
-            store,
-            function_name: wasm_system.system.name.clone(),
-            params: &[wasmtime::component::Val::List(
-                wasm_system.system_param.clone(),
-            )],
-            results: &mut results,
-        });
+    // A mod that imports an interface from a mod that wasn't loaded yet is retried once the
+    // interfaces exported by mods change.
+    if world.is_resource_changed::<ModInterfaces>() {
+        let assets = world.get_resource::<Assets<WasmComponentAsset>>().unwrap();
+        for id in waiting_for_imports.drain() {
+            if let Some(asset) = assets.get(id) {
+                assets_to_setup.push((id, asset.clone()));
+            }
+        }
     }
-}
 
-fn run_setup(world: &mut World, mut already_ran: Local<HashSet<AssetId<WasmComponentAsset>>>) {
-    let assets_to_setup = get_assets_to_setup(world, &mut already_ran);
     if assets_to_setup.is_empty() {
         return;
     }
 
     let engine = world.get_resource::<Engine>().unwrap().clone();
-    let runner = create_runner(engine.0);
+    let interfaces = world.get_resource::<ModInterfaces>().unwrap().clone();
+    let host_interfaces = world.get_resource::<HostInterfaces>().unwrap().clone();
+    let runner = create_runner(engine.0, &interfaces, &host_interfaces);
 
     for (id, asset) in assets_to_setup {
-        let wasm_host = WasmHost {
-            world,
-            wasm_asset_id: id,
+        if let Err(err) = runner.check_imports(&asset.component) {
+            waiting_for_imports.insert(id);
+            let missing = format!("{err:?}");
+            info!("A mod is waiting for imports: {missing}");
+            set_mod_state(world, id, ModState::WaitingForImports { missing });
+            continue;
+        }
+
+        // The systems of the previous run of `setup` are registered again
+        despawn_guest_systems(world, id);
+
+        let wasi_seed = wasi_seed(world, id);
+        let wasm_host = WasmHost::new(world, id);
+        let wasi_view = States::new(wasm_host, asset.restricted, wasi_seed);
+        let mut store = wasmtime::Store::new(&runner.engine, wasi_view);
+
+        let result = interfaces
+            .instantiate_imports(&runner, &mut store, &asset.component)
+            .and_then(|()| {
+                runner.run_function(WasmRunState {
+                    component: &asset.component,
+                    function_name: "setup".to_string(),
+                    store,
+                    params: &[],
+                })
+            });
+
+        let state = match result {
+            Ok(_) => ModState::Ready,
+            Err(err) => {
+                let error = format!("setup failed: {err:?}");
+                error!("{error}");
+                ModState::Failed { error }
+            }
         };
-        let wasi_view = States::new(wasm_host);
-        let store = wasmtime::Store::new(&runner.engine, wasi_view);
-
-        let mut results = vec![];
-        runner.run_function(WasmRunState {
-            component: &asset.component,
-            function_name: "setup".to_string(),
-            store,
-            params: &[],
-            results: &mut results,
-        });
+        set_mod_state(world, id, state);
     }
 }
 
 fn get_assets_to_setup(
     world: &mut World,
+    system_state: &mut SetupAssetEvents,
     already_ran: &mut Local<HashSet<AssetId<WasmComponentAsset>>>,
 ) -> Vec<(AssetId<WasmComponentAsset>, WasmComponentAsset)> {
-    #[allow(clippy::type_complexity)]
-    let mut system_state: SystemState<(
-        EventReader<AssetEvent<WasmComponentAsset>>,
-        ResMut<Assets<WasmComponentAsset>>,
-    )> = SystemState::new(world);
-
     let (mut asset_events, assets) = system_state.get_mut(world);
     let mut assets_to_setup = Vec::new();
 
@@ -197,7 +336,11 @@ fn get_assets_to_setup(
     assets_to_setup
 }
 
-fn create_runner<'a>(engine: wasmtime::Engine) -> Runner<States<'a>> {
+pub(crate) fn create_runner<'a>(
+    engine: wasmtime::Engine,
+    interfaces: &ModInterfaces,
+    host_interfaces: &HostInterfaces,
+) -> Runner<States<'a>> {
     let mut runner = Runner::new(engine);
     runner.add_wasi_sync();
     runner.add_functionality(|linker| {
@@ -205,6 +348,13 @@ fn create_runner<'a>(engine: wasmtime::Engine) -> Runner<States<'a>> {
             &mut state.host_ecs
         })
         .unwrap();
+        bindings::wasvy::ecs::types::add_to_linker(linker, |state: &mut States| state).unwrap();
+        bindings::wasvy::ecs::storage::add_to_linker(linker, |state: &mut States| {
+            &mut state.host_ecs
+        })
+        .unwrap();
     });
+    runner.add_functionality(|linker| host_interfaces.add_to_linker(linker));
+    runner.add_functionality(|linker| interfaces.add_to_linker(linker));
     runner
 }
@@ -1,4 +1,18 @@
 pub use crate::asset::WasmComponentAsset;
+pub use crate::asset_source::ModAssetSourcePlugin;
+pub use crate::determinism::{Determinism, ModRng, ModTime};
 
-pub use crate::mods::Mods;
+pub use crate::host_interfaces::ModloaderAppExt;
+pub use crate::mod_assets::{ModAssetScope, ModAssets};
+pub use crate::mod_list::ModList;
+pub use crate::mod_package::{ModManifest, PackageLimits};
+pub use crate::mod_permissions::{Access, AccessPolicy, ModPermissions, SpawnedBy};
+pub use crate::mod_scene::{ModComponents, ModSceneExt};
+pub use crate::mod_state::{
+    ModFailed, ModLoaded, ModReloaded, ModSetupComplete, ModState, ModUnloaded,
+};
+pub use crate::mod_storage::ModStorage;
+pub use crate::mod_trust::{ModSignature, UnsignedModPolicy};
+pub use crate::mods::{ModFilter, Mods};
 pub use crate::plugin::ModloaderPlugin;
+pub use crate::systems::GuestSystems;
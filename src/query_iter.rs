@@ -0,0 +1,94 @@
+//! Query results that guests read in batches, instead of receiving all of them up front.
+
+use std::sync::Arc;
+
+use bevy::ecs::entity::Entity;
+use wasmtime::{
+    Engine, Store,
+    component::{Component, Resource, ResourceAny, Type, types::ComponentItem},
+};
+
+use crate::{
+    bindings::wasvy::ecs::types::{self, HostQueryIter, QueryResultEntry},
+    state::States,
+    systems::QuerySlots,
+};
+
+/// The `query-iter` resource: the rows of one query of a guest system, which are serialized as
+/// the guest reads them.
+pub struct QueryIter {
+    /// The index of the query in the system's queries.
+    pub(crate) query: usize,
+    /// The entities matched by the query, only their components are serialized lazily.
+    pub(crate) entities: Vec<Entity>,
+    /// How many rows were already read.
+    pub(crate) position: usize,
+    pub(crate) slots: Arc<QuerySlots>,
+}
+
+impl QueryIter {
+    /// Create the resource in the store, for passing it to the guest.
+    pub(crate) fn new_resource(
+        store: &mut Store<States<'_>>,
+        query: usize,
+        entities: Vec<Entity>,
+        slots: Arc<QuerySlots>,
+    ) -> wasmtime::Result<ResourceAny> {
+        let iter = store.data_mut().table.push(QueryIter {
+            query,
+            entities,
+            position: 0,
+            slots,
+        })?;
+        ResourceAny::try_from_resource(iter, store)
+    }
+
+    /// The entities of the next batch of at most `n` rows, which are then marked as read.
+    pub(crate) fn next_entities(&mut self, n: u32) -> &[Entity] {
+        let start = self.position;
+        self.position = (start + n as usize).min(self.entities.len());
+        &self.entities[start..self.position]
+    }
+}
+
+/// Whether the exported function of a system takes a `list<query-iter>` instead of a
+/// `list<query-result>`.
+pub(crate) fn takes_query_iters(
+    engine: &Engine,
+    component: &Component,
+    function_name: &str,
+) -> bool {
+    let Some(ComponentItem::ComponentFunc(function)) =
+        component.component_type().get_export(engine, function_name)
+    else {
+        return false;
+    };
+    function.params().next().is_some_and(
+        |(_, param)| matches!(param, Type::List(list) if matches!(list.ty(), Type::Own(_))),
+    )
+}
+
+impl types::Host for States<'_> {}
+
+impl HostQueryIter for States<'_> {
+    fn next_batch(&mut self, iter: Resource<QueryIter>, n: u32) -> Vec<QueryResultEntry> {
+        let iter = self
+            .table
+            .get_mut(&iter)
+            .expect("the guest passed a query-iter that doesn't exist");
+        self.host_ecs.next_query_batch(iter, n)
+    }
+
+    fn remaining(&mut self, iter: Resource<QueryIter>) -> u32 {
+        let iter = self
+            .table
+            .get(&iter)
+            .expect("the guest passed a query-iter that doesn't exist");
+        (iter.entities.len() - iter.position) as u32
+    }
+
+    fn drop(&mut self, iter: Resource<QueryIter>) -> wasmtime::Result<()> {
+        self.table.delete(iter)?;
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@
 
 use wasmtime::{
     Engine, Store,
-    component::{Component, Func, Linker, Val},
+    component::{Component, Func, Instance, InstancePre, Linker, Val},
 };
 
 pub struct Runner<T: wasmtime_wasi::WasiView> {
@@ -16,7 +16,6 @@ pub struct WasmRunState<'a, T: wasmtime_wasi::WasiView> {
     pub store: Store<T>,
     pub function_name: String,
     pub params: &'a [Val],
-    pub results: &'a mut [Val],
 }
 
 impl<T: wasmtime_wasi::WasiView> Runner<T> {
@@ -42,17 +41,55 @@ impl<T: wasmtime_wasi::WasiView> Runner<T> {
         f(&mut self.linker);
     }
 
-    pub fn run_function(&self, mut state: WasmRunState<'_, T>) {
-        let instance = self
-            .linker
-            .instantiate(&mut state.store, state.component)
-            .unwrap();
+    /// Instantiate the component in the store with everything that was added to the linker.
+    pub fn instantiate(
+        &self,
+        store: &mut Store<T>,
+        component: &Component,
+    ) -> wasmtime::Result<Instance> {
+        self.linker.instantiate(store, component)
+    }
+
+    /// Link the component with everything that was added to the linker, so it can be instantiated
+    /// many times without linking it again.
+    pub fn instantiate_pre(&self, component: &Component) -> wasmtime::Result<InstancePre<T>> {
+        self.linker.instantiate_pre(component)
+    }
 
-        let func: Func = instance
-            .get_func(&mut state.store, state.function_name.clone())
-            .expect("WASM function with the given name wasn't found");
+    /// Returns an error if the linker doesn't satisfy all the imports of the component.
+    pub fn check_imports(&self, component: &Component) -> wasmtime::Result<()> {
+        self.instantiate_pre(component).map(|_| ())
+    }
 
-        func.call(state.store, state.params, state.results)
-            .expect("failed to run the desired function");
+    /// Instantiate the component and call the function, returning what the function returned.
+    ///
+    /// Returns an error if any step fails (e.g. missing imports, a missing function or a trap
+    /// inside the guest).
+    pub fn run_function(&self, mut state: WasmRunState<'_, T>) -> wasmtime::Result<Vec<Val>> {
+        let instance = self.instantiate(&mut state.store, state.component)?;
+        call_function(
+            &mut state.store,
+            instance,
+            &state.function_name,
+            state.params,
+        )
     }
 }
+
+/// Call a function of an instance, returning what the function returned.
+pub fn call_function<T: wasmtime_wasi::WasiView>(
+    store: &mut Store<T>,
+    instance: Instance,
+    function_name: &str,
+    params: &[Val],
+) -> wasmtime::Result<Vec<Val>> {
+    let func: Func = instance
+        .get_func(&mut *store, function_name)
+        .ok_or_else(|| {
+            anyhow::anyhow!("WASM function with the name `{function_name}` wasn't found")
+        })?;
+
+    let mut results = vec![Val::Bool(false); func.results(&*store).len()];
+    func.call(&mut *store, params, &mut results)?;
+    Ok(results)
+}
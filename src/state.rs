@@ -1,26 +1,54 @@
-use wasmtime_wasi::{IoView, ResourceTable, WasiCtx, WasiCtxBuilder, WasiView};
+use std::collections::HashMap;
 
+use wasmtime::component::Instance;
+use wasmtime_wasi::{IoView, ResourceTable, RngCore, WasiCtx, WasiCtxBuilder, WasiView};
+
+use crate::determinism::{FixedClock, SeededRng};
 use crate::host::WasmHost;
 
 /// The state object that houses the functionality that is passed to WASM components.
 pub struct States<'a> {
-    table: ResourceTable,
+    pub(crate) table: ResourceTable,
     ctx: WasiCtx,
     pub host_ecs: WasmHost<'a>,
+    /// Instances of the mods that export interfaces imported by the running mod, keyed by the
+    /// interface name. See [`ModInterfaces`](crate::linking::ModInterfaces).
+    pub linked_instances: HashMap<String, Instance>,
 }
 
 impl<'a> States<'a> {
-    pub fn new(host_ecs: WasmHost<'a>) -> Self {
+    /// `restricted` mods run without network access, see
+    /// [`UnsignedModPolicy::Restrict`](crate::mod_trust::UnsignedModPolicy::Restrict).
+    ///
+    /// In deterministic mode, `deterministic` is the seed of the random numbers of WASI, which
+    /// then can't tell the time or reach the network, see [`determinism`](crate::determinism).
+    pub fn new(host_ecs: WasmHost<'a>, restricted: bool, deterministic: Option<u64>) -> Self {
         let table = ResourceTable::new();
-        let ctx = WasiCtxBuilder::new()
-            .inherit_stdio()
-            .inherit_network()
-            .allow_ip_name_lookup(true)
-            .build();
+        let mut ctx = WasiCtxBuilder::new();
+        match deterministic {
+            Some(seed) => {
+                let mut random = SeededRng(seed);
+                ctx.inherit_stdout()
+                    .inherit_stderr()
+                    .wall_clock(FixedClock)
+                    .monotonic_clock(FixedClock)
+                    .insecure_random_seed(u128::from(random.next_u64()))
+                    .insecure_random(SeededRng(random.next_u64()))
+                    .secure_random(random);
+            }
+            None => {
+                ctx.inherit_stdio();
+                if !restricted {
+                    ctx.inherit_network().allow_ip_name_lookup(true);
+                }
+            }
+        }
+        let ctx = ctx.build();
         Self {
             table,
             ctx,
             host_ecs,
+            linked_instances: HashMap::new(),
         }
     }
 }
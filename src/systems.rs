@@ -1,16 +1,39 @@
-use std::{any::TypeId, collections::HashMap};
+use std::{collections::HashMap, sync::Arc};
 
 use bevy::{
-    ecs::{component::ComponentId, world::FilteredEntityRef},
+    ecs::{
+        component::{ComponentId, Components, Tick},
+        entity::Entities,
+        lifecycle::{RemovedComponentEntity, RemovedComponentMessages},
+        message::MessageCursor,
+        schedule::{ScheduleConfigs, ScheduleLabel},
+        system::{ParamBuilder, QueryParamBuilder, ScheduleSystem, SystemChangeTick, SystemParam},
+        world::FilteredEntityRef,
+    },
     prelude::*,
+    ptr::Ptr,
     reflect::{ReflectFromPtr, TypeRegistry, serde::TypedReflectSerializer},
 };
+use wasmtime::component::{InstancePre, Val};
 
 use crate::{
     asset::WasmComponentAsset,
     bindings::wasvy::ecs::types::{self, Component as BindingComponent, QueryResultEntry},
+    commands::apply_guest_commands,
     component_registry::WasmComponentRegistry,
-    plugin::WasmComponent,
+    determinism::{Determinism, ModRng, ModTime},
+    host::{ComponentIds, WasmHost, component_access, component_id},
+    host_interfaces::HostInterfaces,
+    linking::ModInterfaces,
+    mod_assets::ModAssets,
+    mod_permissions::{Access, ModPermissions},
+    mod_state::{ModState, set_mod_state},
+    mod_storage::ModStorage,
+    mods::Mod,
+    plugin::{Engine, WasmComponent, create_runner},
+    query_iter::{QueryIter, takes_query_iters},
+    runner::{Runner, call_function},
+    state::States,
 };
 
 #[derive(Clone, Component, Reflect)]
@@ -19,179 +42,788 @@ pub struct WasmGuestSystem {
     #[reflect(ignore)]
     pub queries: wasmtime::component::__internal::Vec<types::Query>,
     pub wasm_asset_id: AssetId<WasmComponentAsset>,
+    /// Disabled systems are skipped when they run.
+    ///
+    /// This is kept in sync with the [`ModList`](crate::mod_list::ModList).
+    pub enabled: bool,
 }
 
-/// This struct contains the Query data that will be sent to the WASM guest system.
-pub struct WasmSystemWithParams {
-    pub system: WasmGuestSystem,
-    pub system_param: Vec<wasmtime::component::Val>,
+/// The [`SystemSet`] in [`Update`] that the systems of all mods run in.
+///
+/// Order your own systems relative to the mods with it, e.g. `my_system.before(GuestSystems)`.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GuestSystems;
+
+/// Marks a [`WasmGuestSystem`] that was already added to the schedule of its mod.
+#[derive(Component)]
+pub(crate) struct Scheduled;
+
+/// The schedule of the systems of a mod, which runs in the [`GuestSystems`] set.
+///
+/// Systems can't be removed from a schedule, so the schedule of a mod is dropped and built again
+/// once one of its systems is despawned (e.g. when the mod is hot-reloaded), instead of growing.
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct GuestSchedule(AssetId<WasmComponentAsset>);
+
+/// The mods that have a [`GuestSchedule`], and the systems in it.
+#[derive(Resource, Default)]
+pub(crate) struct GuestSchedules(HashMap<AssetId<WasmComponentAsset>, ScheduledSystems>);
+
+/// The systems in the [`GuestSchedule`] of a mod.
+struct ScheduledSystems {
+    /// The id of the mod, which the schedules run in the order of.
+    namespace: String,
+    entities: Vec<Entity>,
+    /// The place of each system in deterministic mode, sorted.
+    order: Vec<GuestSystemOrder>,
+}
+
+/// The place of a guest system of a mod in deterministic mode, where guest systems run one after
+/// the other: its name, and how many systems of the mod had the same name before.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) struct GuestSystemOrder(String, usize);
+
+/// Adds the systems registered by mods to the [`GuestSchedule`] of their mod.
+///
+/// Every [`WasmGuestSystem`] becomes its own Bevy system, whose access is derived from its `query`
+/// records. This lets the scheduler run it in parallel with the systems of the mod it doesn't
+/// conflict with, and report ambiguities with them like it does for native systems.
+pub(crate) fn schedule_guest_systems(world: &mut World) {
+    drop_outdated_schedules(world);
+
+    let mut new_systems: Vec<(Entity, WasmGuestSystem)> = world
+        .query_filtered::<(Entity, &WasmGuestSystem), Without<Scheduled>>()
+        .iter(world)
+        .map(|(entity, system)| (entity, system.clone()))
+        .collect();
+
+    if new_systems.is_empty() {
+        return;
+    }
+
+    // Mods are loaded in a different order on every machine
+    let deterministic = world.contains_resource::<Determinism>();
+    if deterministic {
+        new_systems.sort_by(|(_, a), (_, b)| a.name.cmp(&b.name));
+    }
+
+    for (entity, system) in new_systems {
+        world.entity_mut(entity).insert(Scheduled);
+        let built = match build_guest_system(world, entity, &system) {
+            Ok(built) => built,
+            Err(error) => {
+                let error = format!("system {} failed: {error}", system.name);
+                error!("{error}");
+                set_mod_state(world, system.wasm_asset_id, ModState::Failed { error });
+                continue;
+            }
+        };
+
+        let id = system.wasm_asset_id;
+        let namespace = world.resource::<ModAssets>().namespace(id);
+        let mut schedules = world.resource_mut::<GuestSchedules>();
+        let scheduled = schedules.0.entry(id).or_insert_with(|| ScheduledSystems {
+            namespace,
+            entities: vec![],
+            order: vec![],
+        });
+        scheduled.entities.push(entity);
+        let built = if deterministic {
+            in_order(built, &mut scheduled.order, system.name.clone())
+        } else {
+            built
+        };
+
+        world
+            .resource_mut::<Schedules>()
+            .add_systems(GuestSchedule(id), built);
+    }
+}
+
+/// Drop the schedules of the mods that had a system despawned, so their other systems are
+/// scheduled again.
+fn drop_outdated_schedules(world: &mut World) {
+    let outdated: Vec<AssetId<WasmComponentAsset>> = world
+        .resource::<GuestSchedules>()
+        .0
+        .iter()
+        .filter(|(_, scheduled)| {
+            scheduled
+                .entities
+                .iter()
+                .any(|entity| world.get::<WasmGuestSystem>(*entity).is_none())
+        })
+        .map(|(id, _)| *id)
+        .collect();
+
+    for id in outdated {
+        let scheduled = world.resource_mut::<GuestSchedules>().0.remove(&id);
+        world.resource_mut::<Schedules>().remove(GuestSchedule(id));
+        for entity in scheduled
+            .map(|scheduled| scheduled.entities)
+            .unwrap_or_default()
+        {
+            if let Ok(mut entity) = world.get_entity_mut(entity) {
+                entity.remove::<Scheduled>();
+            }
+        }
+    }
+}
+
+/// Runs the [`GuestSchedule`] of every mod, ordered by the id of the mod.
+pub(crate) fn run_guest_schedules(world: &mut World) {
+    let mut mods: Vec<(String, AssetId<WasmComponentAsset>)> = world
+        .resource::<GuestSchedules>()
+        .0
+        .iter()
+        .map(|(id, scheduled)| (scheduled.namespace.clone(), *id))
+        .collect();
+    mods.sort_by(|a, b| a.0.cmp(&b.0));
+
+    for (_, id) in mods {
+        world.run_schedule(GuestSchedule(id));
+    }
+}
+
+/// Order a guest system after the ones of its mod that come before it and before the others,
+/// which may have been scheduled earlier.
+fn in_order(
+    mut built: ScheduleConfigs<ScheduleSystem>,
+    order: &mut Vec<GuestSystemOrder>,
+    name: String,
+) -> ScheduleConfigs<ScheduleSystem> {
+    let same_name = order.iter().filter(|other| other.0 == name).count();
+    let place = GuestSystemOrder(name, same_name);
+
+    for other in order.iter() {
+        built = if *other < place {
+            built.after(other.clone())
+        } else {
+            built.before(other.clone())
+        };
+    }
+    built = built.in_set(place.clone());
+    let index = order.partition_point(|other| *other < place);
+    order.insert(index, place);
+    built
+}
+
+/// The query of a guest system, whose components are set by a `query` record.
+///
+/// Like the queries of native systems, its `QueryState` is kept by the system between runs and
+/// only updated with the archetypes that were created since, instead of being rebuilt every frame.
+pub(crate) type GuestQuery<'w, 's> = Query<'w, 's, FilteredEntityRef<'static, 'static>>;
+
+type GuestQueryBuilder =
+    QueryParamBuilder<Box<dyn FnOnce(&mut QueryBuilder<FilteredEntityRef<'static, 'static>>)>>;
+
+/// The resources every guest system needs, next to its queries.
+#[derive(SystemParam)]
+struct GuestSystemContext<'w, 's> {
+    commands: Commands<'w, 's>,
+    runner: Res<'w, GuestRunner>,
+    assets: Res<'w, Assets<WasmComponentAsset>>,
+    type_registry: Res<'w, AppTypeRegistry>,
+    registry: Res<'w, WasmComponentRegistry>,
+    mod_assets: Res<'w, ModAssets>,
+    mod_storage: Res<'w, ModStorage>,
+    permissions: Res<'w, ModPermissions>,
+    mod_rng: Res<'w, ModRng>,
+    time: Res<'w, ModTime>,
+    determinism: Option<Res<'w, Determinism>>,
+    interfaces: Res<'w, ModInterfaces>,
+    components: &'w Components,
+    entities: &'w Entities,
+    systems: Query<'w, 's, &'static WasmGuestSystem>,
+    mods: Query<'w, 's, (&'static Mod, &'static ModState)>,
+    ticks: SystemChangeTick,
+    removed: &'w RemovedComponentMessages,
+    /// The ids of the registered components, which the host functions look up by type and name.
+    component_ids: Local<'s, ComponentIds>,
+    /// What each `removed` query already read, keyed by the index of the query.
+    removed_cursors:
+        Local<'s, HashMap<(usize, ComponentId), MessageCursor<RemovedComponentEntity>>>,
+}
+
+/// What a guest system needs to know about one of its queries when it runs.
+struct GuestQueryInfo {
+    filters: ChangeFilters,
+    slots: Arc<QuerySlots>,
+    /// Whether the rows are sent as a `query-iter`, which serializes them as the guest reads them,
+    /// instead of a `list<query-result>`.
+    lazy: bool,
+}
+
+/// The change detection filters of a `query` record, which can't be expressed with a
+/// [`QueryBuilder`].
+#[derive(Default)]
+struct ChangeFilters {
+    added: Vec<ComponentId>,
+    changed: Vec<ComponentId>,
+    removed: Vec<ComponentId>,
+}
+
+impl ChangeFilters {
+    fn matches(&self, row: &FilteredEntityRef, last_run: Tick, this_run: Tick) -> bool {
+        let ticks = |id: &ComponentId| row.get_change_ticks_by_id(*id);
+        self.added
+            .iter()
+            .all(|id| ticks(id).is_some_and(|ticks| ticks.is_added(last_run, this_run)))
+            && self
+                .changed
+                .iter()
+                .all(|id| ticks(id).is_some_and(|ticks| ticks.is_changed(last_run, this_run)))
+    }
+}
+
+/// The components that are sent for each row of a query.
+#[derive(Default)]
+pub(crate) struct QuerySlots {
+    /// The type path and id of each component, followed by the optional ones.
+    components: Vec<(String, ComponentId)>,
+    has: Vec<ComponentId>,
+    /// Whether the `parent` and `children` of the rows are sent.
+    hierarchy: bool,
+}
+
+/// The linker that guest systems are instantiated with, and the mods that were linked with it.
+///
+/// Linking mods with WASI and all of the host and mod interfaces is slow, so it only happens again
+/// when the interfaces change or a mod is reloaded, instead of every time a system runs.
+#[derive(Resource)]
+pub(crate) struct GuestRunner {
+    runner: Runner<States<'static>>,
+    /// The component of each mod with systems, linked ahead of time, or why it couldn't be.
+    linked: HashMap<AssetId<WasmComponentAsset>, Result<InstancePre<States<'static>>, String>>,
 }
 
-impl WasmSystemWithParams {
-    pub fn new(guest_system: WasmGuestSystem, world: &mut World) -> Self {
+impl FromWorld for GuestRunner {
+    fn from_world(world: &mut World) -> Self {
         Self {
-            system_param: Self::create_system_param(guest_system.queries.clone(), world),
-            system: guest_system,
+            runner: create_runner(
+                (**world.resource::<Engine>()).clone(),
+                world.resource::<ModInterfaces>(),
+                world.resource::<HostInterfaces>(),
+            ),
+            linked: HashMap::new(),
         }
     }
+}
+
+/// Keeps the [`GuestRunner`] up to date with the interfaces and the mods that have systems.
+pub(crate) fn link_guest_systems(
+    mut guest_runner: ResMut<GuestRunner>,
+    mut events: MessageReader<AssetEvent<WasmComponentAsset>>,
+    engine: Res<Engine>,
+    interfaces: Res<ModInterfaces>,
+    host_interfaces: Res<HostInterfaces>,
+    assets: Res<Assets<WasmComponentAsset>>,
+    systems: Query<&WasmGuestSystem>,
+) {
+    if interfaces.is_changed() || host_interfaces.is_changed() {
+        guest_runner.runner = create_runner((**engine).clone(), &interfaces, &host_interfaces);
+        guest_runner.linked.clear();
+    }
+
+    for event in events.read() {
+        if let AssetEvent::Modified { id }
+        | AssetEvent::Removed { id }
+        | AssetEvent::Unused { id } = event
+        {
+            guest_runner.linked.remove(id);
+        }
+    }
+
+    for system in &systems {
+        let id = system.wasm_asset_id;
+        if guest_runner.linked.contains_key(&id) {
+            continue;
+        }
+        let Some(asset) = assets.get(id) else {
+            continue;
+        };
+        let linked = guest_runner
+            .runner
+            .instantiate_pre(&asset.component)
+            .map_err(|err| format!("{err:?}"));
+        guest_runner.linked.insert(id, linked);
+    }
+}
+
+/// Extend the lifetime of the host of a guest system to the one the [`GuestRunner`] links for.
+///
+/// # Safety
+///
+/// The store the host is moved into must be dropped before anything the host borrows is used
+/// again, and before the system returns.
+unsafe fn extend_lifetime(host: WasmHost<'_>) -> WasmHost<'static> {
+    unsafe { std::mem::transmute::<WasmHost<'_>, WasmHost<'static>>(host) }
+}
+
+fn build_guest_system(
+    world: &mut World,
+    entity: Entity,
+    system: &WasmGuestSystem,
+) -> Result<ScheduleConfigs<ScheduleSystem>, String> {
+    let namespace = world
+        .resource::<ModAssets>()
+        .namespace(system.wasm_asset_id);
+    let lazy = world
+        .resource::<Assets<WasmComponentAsset>>()
+        .get(system.wasm_asset_id)
+        .is_some_and(|asset| {
+            takes_query_iters(world.resource::<Engine>(), &asset.component, &system.name)
+        });
+    let (queries, infos): (Vec<_>, Vec<_>) = system
+        .queries
+        .iter()
+        .map(|query| query_param_builder(world, &namespace, query, lazy))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .unzip();
 
-    fn create_system_param(
-        queries: wasmtime::component::__internal::Vec<types::Query>,
-        world: &mut World,
-    ) -> Vec<wasmtime::component::Val> {
-        let type_registry_guard = world.get_resource::<AppTypeRegistry>().unwrap().clone();
-        let type_registry = type_registry_guard.read();
-        let registry = world
-            .get_resource::<WasmComponentRegistry>()
-            .unwrap()
-            .clone();
-        let world_components = Self::get_world_components(world);
+    let built = (queries, ParamBuilder::of::<GuestSystemContext>())
+        .build_state(world)
+        .build_system(
+            move |queries: Vec<GuestQuery>, context: GuestSystemContext| {
+                run_guest_system(entity, queries, &infos, context);
+            },
+        )
+        .with_name(format!("wasvy::{}", system.name));
+
+    Ok(built.into_configs())
+}
+
+/// Despawn the systems of the mod, which stops them from running.
+///
+/// This runs before the `setup` of a mod runs again (hot-reloading), which registers them again.
+pub(crate) fn despawn_guest_systems(world: &mut World, wasm_asset_id: AssetId<WasmComponentAsset>) {
+    let systems: Vec<Entity> = world
+        .query::<(Entity, &WasmGuestSystem)>()
+        .iter(world)
+        .filter(|(_, system)| system.wasm_asset_id == wasm_asset_id)
+        .map(|(entity, _)| entity)
+        .collect();
 
-        queries
+    for entity in systems {
+        world.despawn(entity);
+    }
+}
+
+/// Despawns the systems of a mod once no entity uses the mod anymore.
+pub(crate) fn despawn_removed_mod_systems(
+    remove: On<Remove, Mod>,
+    mut commands: Commands,
+    mods: Query<&Mod>,
+    systems: Query<(Entity, &WasmGuestSystem)>,
+) {
+    let Ok(removed) = mods.get(remove.entity) else {
+        return;
+    };
+    let mod_id = removed.asset.id();
+    if mods.iter().filter(|m| m.asset.id() == mod_id).count() > 1 {
+        return;
+    }
+
+    for (entity, system) in systems.iter() {
+        if system.wasm_asset_id == mod_id {
+            commands.entity(entity).try_despawn();
+        }
+    }
+}
+
+/// Declares the access of a `query` record, so the scheduler knows which components it reads.
+fn query_param_builder(
+    world: &World,
+    namespace: &str,
+    query: &types::Query,
+    lazy: bool,
+) -> Result<(GuestQueryBuilder, GuestQueryInfo), String> {
+    let type_registry = world.resource::<AppTypeRegistry>().read();
+    let registry = world.resource::<WasmComponentRegistry>();
+    let permissions = world.resource::<ModPermissions>();
+    let ids = |paths: &[String]| {
+        paths
             .iter()
-            .map(|query| {
-                let mut query_state =
-                    Self::build_query_state(query, world, &registry, &world_components);
-                let query_rows = Self::process_query_results(
-                    query_state.iter(world),
-                    query,
-                    &registry,
+            .map(|path| {
+                let id = component_id(
+                    world.components(),
+                    namespace,
+                    path,
                     &type_registry,
-                    &world_components,
-                );
-                wasmtime::component::Val::List(query_rows)
+                    registry,
+                )
+                .ok_or_else(|| format!("{path} isn't a registered component"))?;
+                // Filters also tell the mod about the component, so they need read access too
+                if component_access(namespace, path, registry, permissions) < Access::Read {
+                    return Err(format!("the mod isn't allowed to read {path}"));
+                }
+                Ok(id)
             })
-            .collect()
+            .collect::<Result<Vec<_>, _>>()
+    };
+
+    let filters = ChangeFilters {
+        added: ids(&query.added)?,
+        changed: ids(&query.changed)?,
+        removed: ids(&query.removed)?,
+    };
+
+    // Removed components are read from their messages, the query itself isn't used
+    if !filters.removed.is_empty() {
+        let info = GuestQueryInfo {
+            filters,
+            slots: Arc::default(),
+            lazy,
+        };
+        return Ok((QueryParamBuilder::new_box(|_| {}), info));
     }
 
-    fn get_world_components(world: &World) -> HashMap<TypeId, ComponentId> {
-        world
-            .components()
-            .iter_registered()
-            .filter_map(|component_info| {
-                component_info
-                    .type_id()
-                    .map(|type_id| (type_id, component_info.id()))
-            })
-            .collect()
-    }
-
-    fn build_query_state<'w>(
-        query: &types::Query,
-        world: &mut World,
-        registry: &WasmComponentRegistry,
-        world_components: &HashMap<TypeId, ComponentId>,
-    ) -> QueryState<FilteredEntityRef<'w>> {
-        let type_registry = world.get_resource::<AppTypeRegistry>().unwrap().clone();
-        let type_registry = type_registry.read();
-
-        let mut data = QueryBuilder::<FilteredEntityRef<'w>>::new(world);
-        for component_type_path in &query.components {
-            if let Some(component_id) = registry.get(component_type_path) {
-                data.ref_id(*component_id);
-            } else {
-                let type_data = type_registry
-                    .get_with_type_path(component_type_path)
-                    .unwrap();
-                let component_id = world_components.get(&type_data.type_id()).unwrap();
-                data.ref_id(*component_id);
+    let required = ids(&query.components)?;
+    let optional = ids(&query.optional)?;
+    let with = ids(&query.with)?;
+    let without = ids(&query.without)?;
+
+    let slots = QuerySlots {
+        components: query
+            .components
+            .iter()
+            .chain(&query.optional)
+            .cloned()
+            .zip(required.iter().chain(&optional).copied())
+            .collect(),
+        // `has` components only need to exist, checking them doesn't need any access
+        has: ids(&query.has)?,
+        hierarchy: query.hierarchy,
+    };
+
+    // The ticks of `added` and `changed` components are read, so they need read access too
+    let components: Vec<_> = required
+        .into_iter()
+        .chain(filters.added.iter().copied())
+        .chain(filters.changed.iter().copied())
+        .collect();
+
+    let hierarchy = query.hierarchy;
+    let builder = QueryParamBuilder::new_box(move |builder| {
+        for id in components {
+            builder.ref_id(id);
+        }
+        builder.optional(|builder| {
+            for id in &optional {
+                builder.ref_id(*id);
             }
+        });
+        for id in with {
+            builder.with_id(id);
         }
-        data.build()
-    }
-
-    fn process_query_results<'w>(
-        query_results: impl Iterator<Item = FilteredEntityRef<'w>>,
-        query: &types::Query,
-        registry: &WasmComponentRegistry,
-        type_registry: &TypeRegistry,
-        world_components: &HashMap<TypeId, ComponentId>,
-    ) -> Vec<wasmtime::component::Val> {
-        query_results
-            .map(|row| {
-                let components = query
-                    .components
-                    .iter()
-                    .map(|component_type_path| {
-                        Self::create_binding_component(
-                            &row,
-                            component_type_path,
-                            registry,
-                            type_registry,
-                            world_components,
-                        )
-                    })
-                    .collect();
-
-                record_from_query_result_entry(QueryResultEntry {
-                    entity: row.id().index() as u64,
-                    components,
-                })
-            })
-            .collect()
-    }
-
-    fn create_binding_component(
-        row: &FilteredEntityRef,
-        component_type_path: &str,
-        registry: &WasmComponentRegistry,
-        type_registry: &TypeRegistry,
-        world_components: &HashMap<TypeId, ComponentId>,
-    ) -> BindingComponent {
-        if let Some(component_id) = registry.get(component_type_path) {
-            let component = unsafe {
-                row.get_by_id(*component_id)
-                    .unwrap()
-                    .deref::<WasmComponent>()
-            };
-            BindingComponent {
-                path: component_type_path.to_string(),
-                value: component.serialized_value.clone(),
+        for id in without {
+            builder.without_id(id);
+        }
+        if hierarchy {
+            builder.data::<(Option<&ChildOf>, Option<&Children>)>();
+        }
+    });
+
+    let info = GuestQueryInfo {
+        filters,
+        slots: Arc::new(slots),
+        lazy,
+    };
+    Ok((builder, info))
+}
+
+fn run_guest_system(
+    entity: Entity,
+    queries: Vec<GuestQuery>,
+    infos: &[GuestQueryInfo],
+    mut context: GuestSystemContext,
+) {
+    // The system may have been despawned since its schedule was built
+    let Ok(system) = context.systems.get(entity) else {
+        return;
+    };
+    let wasm_asset_id = system.wasm_asset_id;
+
+    let mod_disabled = context.mods.iter().any(|(wasm_mod, state)| {
+        wasm_mod.asset.id() == wasm_asset_id
+            && (!wasm_mod.enabled || matches!(state, ModState::Failed { .. }))
+    });
+    if !system.enabled || mod_disabled {
+        return;
+    }
+
+    let Some(asset) = context.assets.get(wasm_asset_id) else {
+        return;
+    };
+
+    let linked = match context.runner.linked.get(&wasm_asset_id) {
+        Some(Ok(linked)) => linked,
+        Some(Err(error)) => {
+            let error = format!("system {} failed: {error}", system.name);
+            error!("{error}");
+            context.commands.queue(move |world: &mut World| {
+                set_mod_state(world, wasm_asset_id, ModState::Failed { error })
+            });
+            return;
+        }
+        // The mod is linked before its systems run again
+        None => return,
+    };
+
+    let (last_run, this_run) = (context.ticks.last_run(), context.ticks.this_run());
+    let matched: Vec<Vec<Entity>> = queries
+        .iter()
+        .zip(infos)
+        .enumerate()
+        .map(|(index, (query, info))| {
+            if !info.filters.removed.is_empty() {
+                return removed_entities(
+                    index,
+                    &info.filters,
+                    context.removed,
+                    &mut context.removed_cursors,
+                );
             }
+
+            query
+                .iter()
+                .filter(|row| info.filters.matches(row, last_run, this_run))
+                .map(|row| row.id())
+                .collect()
+        })
+        .map(|mut entities: Vec<Entity>| {
+            // The order of the tables depends on the order their archetypes were created in
+            if context.determinism.is_some() {
+                entities.sort();
+            }
+            entities
+        })
+        .collect();
+
+    let lazy = infos.first().is_some_and(|info| info.lazy);
+
+    let wasi_seed = context.determinism.is_some().then(|| {
+        let mod_id = context.mod_assets.namespace(wasm_asset_id);
+        context.mod_rng.next(&mod_id)
+    });
+
+    context.component_ids.update(context.components);
+    let wasm_host = WasmHost::deferred(
+        context.commands.reborrow(),
+        &queries,
+        context.entities,
+        &context.component_ids,
+        context.type_registry.clone(),
+        &context.registry,
+        context.mod_assets.clone(),
+        context.mod_storage.clone(),
+        &context.permissions,
+        context.mod_rng.clone(),
+        *context.time,
+        wasm_asset_id,
+    );
+    // SAFETY: the store is dropped at the end of this block, before the commands are used again
+    let wasm_host = unsafe { extend_lifetime(wasm_host) };
+    let result = {
+        let states = States::new(wasm_host, asset.restricted, wasi_seed);
+        let mut store = wasmtime::Store::new(&context.runner.runner.engine, states);
+
+        // Systems that take a `list<query-iter>` serialize their rows as they read them, the
+        // others get all of them up front
+        let params = if lazy {
+            matched
+                .into_iter()
+                .zip(infos)
+                .enumerate()
+                .map(|(index, (entities, info))| {
+                    let iter =
+                        QueryIter::new_resource(&mut store, index, entities, info.slots.clone())?;
+                    Ok(Val::Resource(iter))
+                })
+                .collect::<wasmtime::Result<Vec<_>>>()
         } else {
-            let type_data = type_registry
-                .get_with_type_path(component_type_path)
-                .unwrap();
-            let component_id = world_components.get(&type_data.type_id()).unwrap();
-            let reflect_from_ptr = type_data.data::<ReflectFromPtr>().unwrap();
-            let reflected_component =
-                unsafe { reflect_from_ptr.as_reflect(row.get_by_id(*component_id).unwrap()) };
-            let serializer = TypedReflectSerializer::new(reflected_component, type_registry);
-
-            BindingComponent {
-                path: component_type_path.to_string(),
-                value: serde_json::to_string(&serializer).unwrap(),
+            let type_registry = context.type_registry.read();
+            Ok(queries
+                .iter()
+                .zip(infos)
+                .zip(matched)
+                .map(|((query, info), entities)| {
+                    let rows = entities
+                        .into_iter()
+                        .map(|entity| {
+                            record_from_query_result_entry(query_entry(
+                                query,
+                                entity,
+                                &info.slots,
+                                &type_registry,
+                            ))
+                        })
+                        .collect();
+                    Val::List(rows)
+                })
+                .collect())
+        };
+
+        params.and_then(|params| {
+            context.interfaces.instantiate_imports(
+                &context.runner.runner,
+                &mut store,
+                &asset.component,
+            )?;
+            let instance = linked.instantiate(&mut store)?;
+            call_function(&mut store, instance, &system.name, &[Val::List(params)])
+        })
+    };
+
+    match result {
+        // Systems that return a `list<command>` have their commands applied with the commands
+        // queued by the host functions, in order
+        Ok(results) => {
+            if let Some(Val::List(commands)) = results.into_iter().next() {
+                context.commands.queue(move |world: &mut World| {
+                    apply_guest_commands(world, commands, wasm_asset_id)
+                });
             }
         }
+        Err(err) => {
+            let error = format!("system {} failed: {err:?}", system.name);
+            error!("{error}");
+            context.commands.queue(move |world: &mut World| {
+                set_mod_state(world, wasm_asset_id, ModState::Failed { error })
+            });
+        }
     }
 }
 
-fn record_from_query_result_entry(data: QueryResultEntry) -> wasmtime::component::Val {
-    let components: Vec<wasmtime::component::Val> = data
+/// One row of a query, with the components of its slots.
+///
+/// Entities that aren't matched by the query anymore (e.g. the ones of `removed` queries) are sent
+/// without any components.
+pub(crate) fn query_entry(
+    query: &GuestQuery,
+    entity: Entity,
+    slots: &QuerySlots,
+    type_registry: &TypeRegistry,
+) -> QueryResultEntry {
+    let Ok(row) = query.get(entity) else {
+        return QueryResultEntry {
+            entity: entity.index() as u64,
+            components: vec![],
+            has: vec![],
+            parent: None,
+            children: vec![],
+        };
+    };
+
+    // Optional components come after the required ones, and are `None` when they're missing
+    let components = slots
+        .components
+        .iter()
+        .map(|(path, id)| {
+            let component = row.get_by_id(*id)?;
+            Some(serialize_component(component, path, type_registry))
+        })
+        .collect();
+
+    let (parent, children) = if slots.hierarchy {
+        let parent = row.get::<ChildOf>().map(|child_of| child_of.parent());
+        let children = row.get::<Children>().map(|children| children.to_vec());
+        (parent, children.unwrap_or_default())
+    } else {
+        (None, vec![])
+    };
+
+    QueryResultEntry {
+        entity: entity.index() as u64,
+        components,
+        has: slots.has.iter().map(|id| row.contains_id(*id)).collect(),
+        parent: parent.map(|parent| parent.index() as u64),
+        children: children
+            .into_iter()
+            .map(|child| child.index() as u64)
+            .collect(),
+    }
+}
+
+/// The entities that had a component of a `removed` query taken away since the last run.
+fn removed_entities(
+    index: usize,
+    filters: &ChangeFilters,
+    removed: &RemovedComponentMessages,
+    cursors: &mut HashMap<(usize, ComponentId), MessageCursor<RemovedComponentEntity>>,
+) -> Vec<Entity> {
+    let mut entities = Vec::new();
+    for id in &filters.removed {
+        let cursor = cursors.entry((index, *id)).or_default();
+        let Some(messages) = removed.get(*id) else {
+            continue;
+        };
+
+        entities.extend(
+            cursor
+                .read(messages)
+                .map(|removed| Entity::from(removed.clone())),
+        );
+    }
+    entities
+}
+
+/// Serialize the component with the given type path to JSON, for sending it to a guest.
+///
+/// Mods can't register the type paths of the host, so the components that aren't in the type
+/// registry are the ones of mods.
+pub(crate) fn serialize_component(
+    component: Ptr,
+    component_type_path: &str,
+    type_registry: &TypeRegistry,
+) -> BindingComponent {
+    if let Some(type_data) = type_registry.get_with_type_path(component_type_path) {
+        let reflect_from_ptr = type_data.data::<ReflectFromPtr>().unwrap();
+        let reflected_component = unsafe { reflect_from_ptr.as_reflect(component) };
+        let serializer = TypedReflectSerializer::new(reflected_component, type_registry);
+
+        BindingComponent {
+            path: component_type_path.to_string(),
+            value: serde_json::to_string(&serializer).unwrap(),
+        }
+    } else {
+        let component = unsafe { component.deref::<WasmComponent>() };
+        BindingComponent {
+            path: component_type_path.to_string(),
+            value: component.serialized_value.clone(),
+        }
+    }
+}
+
+fn record_from_query_result_entry(data: QueryResultEntry) -> Val {
+    let components: Vec<Val> = data
         .components
         .into_iter()
-        .map(|component: BindingComponent| {
-            wasmtime::component::Val::Record(vec![
-                (
-                    "path".to_string(),
-                    wasmtime::component::Val::String(component.path),
-                ),
-                (
-                    "value".to_string(),
-                    wasmtime::component::Val::String(component.value),
-                ),
-            ])
+        .map(|component: Option<BindingComponent>| {
+            Val::Option(component.map(|component| {
+                Box::new(Val::Record(vec![
+                    ("path".to_string(), Val::String(component.path)),
+                    ("value".to_string(), Val::String(component.value)),
+                ]))
+            }))
         })
         .collect();
 
-    wasmtime::component::Val::Record(vec![
+    Val::Record(vec![
+        ("components".to_string(), Val::List(components)),
+        ("entity".to_string(), Val::U64(data.entity)),
+        (
+            "has".to_string(),
+            Val::List(data.has.into_iter().map(Val::Bool).collect()),
+        ),
         (
-            "components".to_string(),
-            wasmtime::component::Val::List(components),
+            "parent".to_string(),
+            Val::Option(data.parent.map(|parent| Box::new(Val::U64(parent)))),
         ),
         (
-            "entity".to_string(),
-            wasmtime::component::Val::U64(data.entity),
+            "children".to_string(),
+            Val::List(data.children.into_iter().map(Val::U64).collect()),
         ),
     ])
 }